@@ -0,0 +1,81 @@
+//! Interpreter benchmarks: runs every benches/*.ask script plus host-side object churn.
+//!
+//! Run with `cargo bench`, a single script can be profiled with `asterisk bench <file>`.
+
+use std::{fs, path::PathBuf, time::{Duration, Instant}};
+
+use asterisk::{
+    objects::hash_table::HashTable,
+    utils::{alloc::{allocations, CountingAlloc}, bench::bench_file},
+};
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+const RUNS: u32 = 5;
+
+fn main() {
+    let mut scripts: Vec<PathBuf> = fs::read_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/benches"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "ask"))
+        .collect();
+    scripts.sort();
+
+    for script in scripts {
+        let reports: Vec<_> = (0..RUNS).map(|_| bench_file(&script).unwrap()).collect();
+        let times: Vec<Duration> = reports.iter().map(|r| r.elapsed).collect();
+        let last = reports.last().unwrap();
+
+        summary(&last.name, &times, last.profile.total(), last.allocations);
+    }
+
+    hash_table_churn();
+}
+
+/// Insert, read and delete a batch of keys over and over.
+///
+fn hash_table_churn() {
+    const KEYS: usize = 1000;
+    const ROUNDS: usize = 20;
+
+    let keys: Vec<String> = (0..KEYS).map(|i| format!("key{i}")).collect();
+    let mut times = vec![];
+    let mut allocated = 0;
+
+    for _ in 0..RUNS {
+        let (before, _) = allocations();
+        let start = Instant::now();
+
+        let mut table = HashTable::<String, i64>::default();
+
+        for _ in 0..ROUNDS {
+            for (i, key) in keys.iter().enumerate() {
+                table.insert(key, i as i64);
+            }
+            for key in keys.iter() {
+                assert!(table.get(key).is_some());
+            }
+            for key in keys.iter() {
+                table.delete(key);
+            }
+        }
+
+        times.push(start.elapsed());
+        allocated = allocations().0 - before;
+    }
+
+    summary("hash_table_churn", &times, (KEYS * ROUNDS * 3) as u64, allocated);
+}
+
+fn summary(name: &str, times: &[Duration], ops: u64, allocations: u64) {
+    let best = times.iter().min().unwrap();
+    let mean = times.iter().sum::<Duration>() / times.len() as u32;
+
+    println!(
+        "{name:<20} best {:>9.2}ms  mean {:>9.2}ms  {:>8.2}M ops/sec  {allocations} allocations",
+        best.as_secs_f64() * 1000.0,
+        mean.as_secs_f64() * 1000.0,
+        ops as f64 / best.as_secs_f64() / 1_000_000.0,
+    );
+}
@@ -14,10 +14,15 @@ impl VmError {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum InterpretResult {
     RuntimeError,
     CompilerError,
+    /* Instructions budget ran out, the Vm can be resumed after refueling */
+    OutOfFuel,
+    CallDepthExceeded,
+    StackOverflow,
+    OutOfMemory,
 }
 
 impl fmt::Display for VmError {
@@ -27,6 +32,10 @@ impl fmt::Display for VmError {
             InterpretResult::CompilerError => {
                 write!(f, "An compilation error occurred: {}", self.message)
             }
+            InterpretResult::OutOfFuel
+            | InterpretResult::CallDepthExceeded
+            | InterpretResult::StackOverflow
+            | InterpretResult::OutOfMemory => write!(f, "Limit exceeded: {}", self.message),
         }
     }
 }
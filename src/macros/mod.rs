@@ -20,7 +20,7 @@ macro_rules! gen_primitives_operations {
                     (
                         Primitive::String(str1), Primitive::String(str2)
                     ) => {
-                        Primitive::String(str1.add(&str2[..]))
+                        Primitive::String(str1.concat(&str2))
                     },
                     _ => panic!("Add not allowed.")
                 }
@@ -59,6 +59,38 @@ macro_rules! gen_primitives_operations {
             }
         }
 
+        impl Sub for Primitive {
+            type Output = Primitive;
+
+            fn sub(self, other: Self) -> Primitive {
+                match (self, other) {
+                    $(
+                        (
+                            Primitive::$variant(value_a),
+                            Primitive::$variant(value_b)
+                        ) => { Primitive::$variant(value_a - value_b) }
+                    ), *
+                    _ => panic!("Operation sub not allowed")
+                }
+            }
+        }
+
+        impl Rem for Primitive {
+            type Output = Primitive;
+
+            fn rem(self, other: Self) -> Primitive {
+                match (self, other) {
+                    $(
+                        (
+                            Primitive::$variant(value_a),
+                            Primitive::$variant(value_b)
+                        ) => { Primitive::$variant(value_a % value_b) }
+                    ), *
+                    _ => panic!("Operation rem not allowed")
+                }
+            }
+        }
+
         impl PartialOrd for Primitive {
             fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
                 match (self, other) {
@@ -68,7 +100,14 @@ macro_rules! gen_primitives_operations {
                             Primitive::$variant(value_a), Primitive::$variant(value_b)
                         ) => { value_a.partial_cmp(value_b) }
                     ), *
-                    _ => panic!("Cannot compare different types.")
+                    (Primitive::String(a), Primitive::String(b)) => a.as_str().partial_cmp(b.as_str()),
+                    (Primitive::Bool(a), Primitive::Bool(b)) => a.partial_cmp(b),
+                    /* Derived ordering: items, or fields in declaration order, compared one by one */
+                    (Primitive::Tuple(a), Primitive::Tuple(b)) => a.items.iter().partial_cmp(b.items.iter()),
+                    (Primitive::Instance(a), Primitive::Instance(b)) if a._struct == b._struct => {
+                        a.values.iter().partial_cmp(b.values.iter())
+                    }
+                    _ => None
                 }
             }
         }
@@ -95,7 +134,7 @@ macro_rules! gen_values_operations {
                         Value { value: Primitive::String(str1), modifier, _type },
                         Value { value: Primitive::String(str2), .. },
                     ) => {
-                        Value { value: Primitive::String(str1.add(&str2[..])), modifier, _type }
+                        Value { value: Primitive::String(str1.concat(&str2)), modifier, _type }
                     },
                     _ => panic!("Add not allowed.")
                 }
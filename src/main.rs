@@ -1,15 +1,14 @@
-mod errors;
-mod macros;
-mod objects;
-mod parser;
-mod primitives;
-mod utils;
-mod vm;
-
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
 use std::{env, io};
-use vm::Vm;
+use asterisk::utils::bench::bench_file;
+use asterisk::vm::Vm;
+
+/* Counted so `asterisk bench` can report allocations */
+#[cfg(feature = "count-alloc")]
+#[global_allocator]
+static GLOBAL: asterisk::utils::alloc::CountingAlloc = asterisk::utils::alloc::CountingAlloc;
 
 fn main() {
     let mut vm = Vm::default();
@@ -21,8 +20,36 @@ fn check_cmd_args(vm: &mut Vm) {
 
     match args.len() {
         1 => repl(vm),
+        _ if args[1] == "bench" => bench(&args[2..]),
         2 => run_file(vm, &args[1]),
-        _ => panic!("Usage: cargo run -- [file]"),
+        _ => panic!("Usage: cargo run -- [file] | bench [files]"),
+    }
+}
+
+/// Run each script reporting it's time, executed instructions and allocations.
+///
+/// Defaults to the benches/ suite when no script is given.
+///
+fn bench(paths: &[String]) {
+    let mut paths: Vec<PathBuf> = paths.iter().map(PathBuf::from).collect();
+
+    if paths.is_empty() {
+        paths = fs::read_dir("benches")
+            .expect("Could not find benches directory.")
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "ask"))
+            .collect();
+        paths.sort();
+    }
+
+    for path in paths {
+        match bench_file(&path) {
+            Ok(report) => println!("{report}"),
+            Err(e) => {
+                eprintln!("{}: {e}", path.display());
+                std::process::exit(70);
+            }
+        }
     }
 }
 
@@ -43,7 +70,9 @@ fn repl(vm: &mut Vm) {
             break;
         }
 
-        vm.interpret(buffer);
+        if let Err(e) = vm.interpret(buffer) {
+            eprintln!("{e}");
+        }
     }
 }
 
@@ -51,5 +80,8 @@ fn run_file(vm: &mut Vm, file_path: &str) {
     let input = File::open(file_path).unwrap();
     let source = BufReader::new(input);
 
-    vm.interpret(source);
+    if let Err(e) = vm.interpret(source) {
+        eprintln!("{e}");
+        std::process::exit(70);
+    }
 }
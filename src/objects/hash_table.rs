@@ -1,4 +1,4 @@
-use crate::utils::hasher::FNV1aHasher;
+use crate::{objects::string::IString, utils::hasher::FNV1aHasher};
 use std::{
     cell::RefCell,
     fmt::Display,
@@ -11,12 +11,15 @@ pub type Entry<K, V> = (K, Rc<RefCell<V>>);
 #[derive(Debug, Clone)]
 pub struct HashTable<K, V> {
     pub entries: Vec<Option<(K, Rc<RefCell<V>>)>>,
+    /* Filled buckets */
+    count: usize,
 }
 
 impl<K: Clone, V: Clone> Default for HashTable<K, V> {
     fn default() -> Self {
         Self {
             entries: vec![None; 4],
+            count: 0,
         }
     }
 }
@@ -40,6 +43,7 @@ where
         // Create new bucket with associated Rc if new; Otherside internally mut already set RefCell.
         if is_new {
             *entry = Some((key.clone(), Rc::new(RefCell::new(value))));
+            self.count += 1;
         } else {
             *entry.as_ref().unwrap().1.borrow_mut() = value;
         }
@@ -49,26 +53,54 @@ where
         return is_new;
     }
 
+    /// Count of entries.
+    ///
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
     /// Get value given a key
     ///
     pub fn get(&self, key: &K) -> Option<Rc<RefCell<V>>> {
         self.find(key)
     }
 
+    /// Remove entry of key, freeing it's bucket.
+    /// Return true if key was present.
+    ///
     pub fn delete(&mut self, key: &K) -> bool {
-        let entry = self.find_mut(key);
+        let cap = self.entries.capacity();
+        let mut index = hash_key(key, cap);
 
-        if entry.is_none() {
-            return false;
+        loop {
+            match &self.entries[index] {
+                None => return false,
+                Some((k, _)) if k == key => break,
+                _ => index = (index + 1) % cap,
+            }
         }
 
-        /* Take already set key and a tombstone value */
-        *entry.as_ref().unwrap().1.borrow_mut() = V::default();
+        self.entries[index] = None;
+        self.count -= 1;
+
+        /* Entries probed past the freed bucket are placed again, so lookups don't stop short of them */
+        let mut next = (index + 1) % cap;
+
+        while let Some((k, v)) = self.entries[next].take() {
+            let mut t_idx = hash_key(&k, cap);
+
+            if self.entries[t_idx].is_some() {
+                t_idx = Self::probe_idx(&self.entries, t_idx);
+            }
+
+            self.entries[t_idx] = Some((k, v));
+            next = (next + 1) % cap;
+        }
 
         true
     }
 
-    /// Checks with tombstone compatibility if value is present using cap arithmetic
+    /// Find bucket of key by linear probing from it's hash index
     ///
     fn find(&self, key: &K) -> Option<Rc<RefCell<V>>> {
         let current_cap = self.entries.capacity();
@@ -86,13 +118,11 @@ where
                 return Some(Rc::clone(val_ref));
             }
 
-            /* TODO Add tombstone handling */
-
             index = (index + 1) % current_cap;
         }
     }
 
-    /// Checks with tombstone compatibility if value is present using cap arithmetic
+    /// Find bucket of key by linear probing from it's hash index
     ///
     fn find_mut(&mut self, key: &K) -> &mut Option<Entry<K, V>> {
         let current_cap = self.entries.capacity();
@@ -106,8 +136,6 @@ where
                 return &mut self.entries[index];
             }
 
-            /* TODO Add tombstone handling */
-
             index = (index + 1) % current_cap;
         }
     }
@@ -117,7 +145,7 @@ where
          *
          * + 1 because it checks for future entry (assume it is a new one)
          */
-        if (self.entries.iter().filter(|e| e.is_some()).count() + 1) as f64
+        if (self.count + 1) as f64
             > (self.entries.capacity() as f64 * Self::MAX_LOAD_FACTOR)
         {
             self.resize();
@@ -147,20 +175,62 @@ where
         self.entries = new_entries;
     }
 
+    /// Keep only entries whose key satisfies `keep`, returning the removed count.
+    ///
+    pub fn retain(&mut self, keep: impl Fn(&K) -> bool) -> usize {
+        let removed = self.entries.iter().flatten().filter(|(k, _)| !keep(k)).count();
+
+        if removed == 0 { return 0; }
+
+        /* Probe sequences may pass through removed entries, so survivors are re-hashed */
+        let cap = self.entries.capacity();
+        let mut new_entries: Vec<Option<Entry<K, V>>> = vec![None; cap];
+
+        for (k, v) in self.entries.drain(..).flatten().filter(|(k, _)| keep(k)) {
+            let mut t_idx = hash_key(&k, cap);
+
+            if new_entries[t_idx].is_some() {
+                t_idx = Self::probe_idx(&new_entries, t_idx);
+            }
+
+            new_entries[t_idx] = Some((k, v));
+        }
+
+        self.entries = new_entries;
+        self.count -= removed;
+
+        removed
+    }
+
     pub fn probe_idx(new_entries: &Vec<Option<Entry<K, V>>>, init_idx: usize) -> usize {
         let cap = new_entries.capacity();
         /* Set bounds for idx handling based on current cap */
         let mut probe_idx = (init_idx + 1) % cap;
 
         // This operation never fails, as the array objectivelly has new empty space with the resize
-        for bucket in new_entries[probe_idx..].iter().cycle() {
-            if bucket.is_none() { return probe_idx }
+        loop {
+            if new_entries[probe_idx].is_none() { return probe_idx }
 
             probe_idx = (probe_idx + 1) % cap;
         }
+    }
+}
 
-        /* Dummy panic */
-        panic!();
+impl<V> HashTable<IString, V> {
+    /// Find interned string by it's contents, as keys are compared by identity.
+    ///
+    pub fn find_string(&self, chars: &str, hash: u64) -> Option<IString> {
+        let current_cap = self.entries.capacity();
+        /* Same index as `hash_key`, which hashes strings by their cached hash */
+        let mut index = hash_key(&hash, current_cap);
+
+        loop {
+            let (key, _) = self.entries[index].as_ref()?;
+
+            if **key == *chars { return Some(key.clone()); }
+
+            index = (index + 1) % current_cap;
+        }
     }
 }
 
@@ -1 +1,2 @@
 pub mod hash_table;
+pub mod string;
@@ -0,0 +1,179 @@
+use std::{
+    cell::RefCell,
+    fmt::{self, Debug, Display},
+    hash::{Hash, Hasher},
+    ops::Deref,
+    rc::{Rc, Weak},
+};
+
+use crate::{objects::hash_table::HashTable, utils::hasher::FNV1aHasher};
+
+/// Longer strings built at runtime are not interned, as hashing them on every concatenation costs more than comparing.
+///
+pub const MAX_INTERNED_LEN: usize = 40;
+
+/// Strings count which triggers the first sweep of unreferenced strings.
+///
+const INITIAL_SWEEP: usize = 256;
+
+thread_local! {
+    /*
+        Interned strings, shared by the compiler and every Vm on the thread,
+        so identifiers and literals compiled once are the same objects the Vm compares.
+    */
+    static STRINGS: RefCell<Interner> = RefCell::new(Interner::default());
+}
+
+struct Interner {
+    strings: HashTable<IString, ()>,
+    /* Strings count which triggers next sweep */
+    next_sweep: usize,
+}
+
+impl Default for Interner {
+    fn default() -> Self {
+        Interner { strings: HashTable::default(), next_sweep: INITIAL_SWEEP }
+    }
+}
+
+#[derive(Debug)]
+pub struct StringObj {
+    /* Only cached for interned strings */
+    hash: u64,
+    interned: bool,
+    pub chars: Box<str>,
+}
+
+impl StringObj {
+    fn new(chars: &str, hash: u64, interned: bool) -> Self {
+        StringObj { hash, interned, chars: chars.into() }
+    }
+}
+
+/// Handle to an immutable string.
+///
+/// Identifiers, literals and short strings are interned, so equal ones are the same object
+/// and are compared by identity; long strings built at runtime fall back to comparing contents.
+///
+#[derive(Clone)]
+pub struct IString(Rc<StringObj>);
+
+/// Get the interned string of chars, interning it on it's first use.
+///
+pub fn intern(chars: &str) -> IString {
+    let hash = hash_str(chars);
+
+    STRINGS.with_borrow_mut(|interner| {
+        if let Some(string) = interner.strings.find_string(chars, hash) {
+            return string;
+        }
+
+        /* Strings only held by the table are dropped as it grows, instead of resizing it */
+        if interner.strings.count() >= interner.next_sweep {
+            interner.strings.retain(|string| Rc::strong_count(&string.0) > 1);
+            interner.next_sweep = (interner.strings.count() * 2).max(INITIAL_SWEEP);
+        }
+
+        let string = IString(Rc::new(StringObj::new(chars, hash, true)));
+        interner.strings.insert(&string, ());
+
+        string
+    })
+}
+
+/// Drop interned strings no longer referenced outside of the strings table, returning the dropped count.
+///
+pub fn sweep_strings() -> usize {
+    STRINGS.with_borrow_mut(|interner| interner.strings.retain(|string| Rc::strong_count(&string.0) > 1))
+}
+
+pub fn hash_str(chars: &str) -> u64 {
+    let mut hasher = FNV1aHasher::new();
+    hasher.write(chars.as_bytes());
+
+    hasher.finish()
+}
+
+impl IString {
+    /// String built at runtime, interned if short.
+    ///
+    pub fn new(chars: String) -> IString {
+        if chars.len() <= MAX_INTERNED_LEN {
+            return intern(&chars);
+        }
+
+        IString(Rc::new(StringObj::new(&chars, 0, false)))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0.chars
+    }
+
+    /// Handle which doesn't keep the string alive, e.g: for the heap counting it's bytes.
+    ///
+    pub fn downgrade(&self) -> Weak<StringObj> {
+        Rc::downgrade(&self.0)
+    }
+
+    /// Join both strings.
+    ///
+    pub fn concat(&self, other: &IString) -> IString {
+        IString::new([self.as_str(), other.as_str()].concat())
+    }
+}
+
+impl PartialEq for IString {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+            || (!(self.0.interned && other.0.interned) && self.0.chars == other.0.chars)
+    }
+}
+
+impl Eq for IString {}
+
+impl Hash for IString {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self.0.interned {
+            true => self.0.hash.hash(state),
+            false => hash_str(&self.0.chars).hash(state),
+        }
+    }
+}
+
+impl Deref for IString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Display for IString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl Debug for IString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.as_str())
+    }
+}
+
+impl From<&str> for IString {
+    fn from(chars: &str) -> Self {
+        intern(chars)
+    }
+}
+
+impl From<String> for IString {
+    fn from(chars: String) -> Self {
+        intern(&chars)
+    }
+}
+
+impl Default for IString {
+    fn default() -> Self {
+        intern("")
+    }
+}
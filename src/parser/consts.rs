@@ -0,0 +1,153 @@
+use crate::{parser::{lexer::Token, Parser}, primitives::primitive::Primitive};
+
+/// Binding power of operators allowed on constant expressions, higher binds tighter.
+///
+fn binding_power(token: &Token) -> Option<u8> {
+    match token {
+        Token::Or => Some(1),
+        Token::And => Some(2),
+        Token::EqualEqual | Token::BangEqual => Some(3),
+        Token::Less | Token::LessEqual | Token::Greater | Token::GreaterEqual => Some(4),
+        Token::Plus | Token::Minus => Some(5),
+        Token::Star | Token::Slash | Token::Percent => Some(6),
+        _ => None,
+    }
+}
+
+impl<R: std::io::Read> Parser<R> {
+    /// Evaluate expression at compile time, it may only use literals, other constants,
+    /// grouping, unary and binary operators.
+    ///
+    pub fn constant_expression(&mut self) -> Primitive {
+        self.fold(0)
+    }
+
+    /// Fold operands and operators binding at least as tight as min_power.
+    ///
+    fn fold(&mut self, min_power: u8) -> Primitive {
+        let mut left = self.fold_operand();
+
+        while let Some(power) = binding_power(&self.current).filter(|power| *power >= min_power) {
+            let operator = self.current.clone();
+            self.advance();
+
+            /* Left associative: 1 - 2 - 3 -> (1 - 2) - 3 */
+            let right = self.fold(power + 1);
+            left = self.fold_binary(&operator, left, right);
+        }
+
+        left
+    }
+
+    fn fold_operand(&mut self) -> Primitive {
+        let operand = match self.get_current() {
+            Token::Integer(i) => Primitive::Int(i),
+            Token::Float(f) => Primitive::Float(f),
+            Token::String(s) => Primitive::String(String::from_utf8(s).unwrap().into()),
+            Token::True => Primitive::Bool(true),
+            Token::False => Primitive::Bool(false),
+            Token::Nil => Primitive::Nil,
+            Token::Identifier(name) => match self.resolve_const(&name) {
+                Some(value) => value,
+                None => self.error(&format!("'{name}' is not a constant.")),
+            },
+            Token::LeftParen => {
+                self.advance();
+                let value = self.fold(0);
+
+                if !self.check(Token::RightParen) {
+                    self.error("Expect ')' after expression.");
+                }
+
+                value
+            }
+            Token::Minus => {
+                self.advance();
+
+                return match self.fold_operand() {
+                    Primitive::Int(i) => match i.checked_neg() {
+                        Some(i) => Primitive::Int(i),
+                        None => self.error(&format!("Integer overflow negating {i} in constant expression.")),
+                    },
+                    Primitive::Float(f) => Primitive::Float(-f),
+                    v => self.error(&format!("Cannot negate {v} in constant expression.")),
+                };
+            }
+            Token::Bang => {
+                self.advance();
+
+                return match self.fold_operand() {
+                    Primitive::Bool(b) => Primitive::Bool(!b),
+                    v => self.error(&format!("Cannot negate {v} in constant expression.")),
+                };
+            }
+            _ => self.error("Initializer of constant must be a constant expression."),
+        };
+        self.advance();
+
+        operand
+    }
+
+    fn fold_binary(&mut self, operator: &Token, a: Primitive, b: Primitive) -> Primitive {
+        let symbol = match operator {
+            Token::Or => "or",
+            Token::And => "and",
+            Token::EqualEqual => "==",
+            Token::BangEqual => "!=",
+            Token::Less => "<",
+            Token::LessEqual => "<=",
+            Token::Greater => ">",
+            Token::GreaterEqual => ">=",
+            Token::Plus => "+",
+            Token::Minus => "-",
+            Token::Star => "*",
+            Token::Slash => "/",
+            _ => "%",
+        };
+
+        match (operator, &a, &b) {
+            (Token::Slash | Token::Percent, Primitive::Int(_), Primitive::Int(0)) => {
+                self.error("Division by zero in constant expression.")
+            }
+            (Token::Or, Primitive::Bool(x), Primitive::Bool(y)) => Primitive::Bool(*x || *y),
+            (Token::And, Primitive::Bool(x), Primitive::Bool(y)) => Primitive::Bool(*x && *y),
+            (Token::EqualEqual, _, _) => Primitive::Bool(a == b),
+            (Token::BangEqual, _, _) => Primitive::Bool(a != b),
+            (Token::Less | Token::LessEqual | Token::Greater | Token::GreaterEqual, _, _) if a.partial_cmp(&b).is_some() => {
+                Primitive::Bool(match operator {
+                    Token::Less => a < b,
+                    Token::LessEqual => a <= b,
+                    Token::Greater => a > b,
+                    _ => a >= b,
+                })
+            }
+            (Token::Plus, Primitive::String(_), Primitive::String(_)) => a + b,
+            (_, Primitive::Int(x), Primitive::Int(y)) if matches!(symbol, "+" | "-" | "*" | "/" | "%") => {
+                let result = match operator {
+                    Token::Plus => x.checked_add(*y),
+                    Token::Minus => x.checked_sub(*y),
+                    Token::Star => x.checked_mul(*y),
+                    Token::Slash => x.checked_div(*y),
+                    _ => x.checked_rem(*y),
+                };
+
+                match result {
+                    Some(i) => Primitive::Int(i),
+                    None => self.error(&format!("Integer overflow in {x} {symbol} {y} in constant expression.")),
+                }
+            }
+            (_, Primitive::Float(_), Primitive::Float(_))
+                if matches!(symbol, "+" | "-" | "*" | "/" | "%") =>
+            {
+                match operator {
+                    Token::Plus => a + b,
+                    Token::Minus => a - b,
+                    Token::Star => a * b,
+                    Token::Slash => a / b,
+                    _ => a % b,
+                }
+            }
+            _ => self.error(&format!("Operation '{symbol}' not allowed between {a} and {b} in constant expression.")),
+        }
+    }
+}
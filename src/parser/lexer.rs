@@ -1,4 +1,7 @@
-use std::{io::Bytes, iter::Peekable};
+use std::{
+    fmt::{self, Display},
+    io::{Bytes, Read},
+};
 
 use crate::primitives::types::Type;
 
@@ -18,10 +21,18 @@ pub enum Token {
     SemiColon,
     Slash,
     Star,
+    Percent,
     Ampersand,
+    Question,
 
     // One or two char tokens
     Arrow,
+    ThinArrow,
+    ColonColon,
+    DotDot,
+    Ellipsis,
+    QuestionQuestion,
+    QuestionDot,
     Bang,
     BangEqual,
     Equal,
@@ -30,21 +41,36 @@ pub enum Token {
     GreaterEqual,
     Less,
     LessEqual,
+    // Compound assignments
+    PlusEqual,
+    MinusEqual,
+    StarEqual,
+    SlashEqual,
+    PercentEqual,
+    // Increment and decrement: x++, --x
+    PlusPlus,
+    MinusMinus,
     // Literals
     Identifier(String),
     String(Vec<u8>),
+    /// String part before an interpolated expression, the string goes on after it's closing brace.
+    Interpolation(Vec<u8>),
     Float(f64),
     Integer(i64),
     Nil,
     // Keywords
+    Ok,
+    Err,
     And,
     Class,
     Case,
+    Catch,
     Const,
     Continue,
     Default,
     Else,
     False,
+    Finally,
     For,
     Fun,
     If,
@@ -54,10 +80,15 @@ pub enum Token {
     Print,
     Return,
     StructDef,
+    Impl,
+    Trait,
+    TypeAlias,
     Switch,
     Super,
     This,
+    Throw,
     True,
+    Try,
     Var,
     While,
     Comment,
@@ -65,131 +96,283 @@ pub enum Token {
     Eof,
 }
 
-#[derive(Debug)]
-pub struct Lexer<R: std::io::Read> {
-    source: Peekable<Bytes<R>>,
+/// Location on source, lines and columns start at 1 and columns count chars.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Position {
+    /// Bytes before it.
+    pub offset: usize,
     pub line: u32,
+    pub column: u32,
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Position { offset: 0, line: 1, column: 1 }
+    }
 }
 
-impl<R: std::io::Read> Lexer<R> {
+impl Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {} (byte {})", self.line, self.column, self.offset)
+    }
+}
+
+/// Scan tokens out of UTF-8 source.
+///
+/// Invalid UTF-8 and read failures are reported as `Token::Error`, with `start` set to where they happened.
+///
+#[derive(Debug)]
+pub struct Lexer<R: Read> {
+    source: Bytes<R>,
+    /* Char decoded by peek_char, not consumed yet */
+    peeked: Option<char>,
+    /* Source has no more bytes, a NUL byte is just another char */
+    eof: bool,
+    /// Position of next char.
+    pub position: Position,
+    /// Position of last scanned token.
+    pub start: Position,
+    /* Source can't be scanned past this point */
+    failure: Option<(&'static str, Position)>,
+    /* Last token was a '.', so a number after it is a tuple index and not a Float: t.0.1 */
+    member: bool,
+    /* Quote and open braces count of each string being interpolated, innermost last */
+    interpolations: Vec<(char, usize)>,
+}
+
+impl<R: Read> Lexer<R> {
     pub fn new(source: R) -> Self {
         Lexer {
-            source: source.bytes().peekable(),
-            line: 0,
+            source: source.bytes(),
+            peeked: None,
+            eof: false,
+            position: Position::default(),
+            start: Position::default(),
+            failure: None,
+            member: false,
+            interpolations: vec![],
         }
     }
 
     pub fn next(&mut self) -> Token {
-        let byt = self.read_byte();
+        let token = self.scan();
+        self.member = token == Token::Dot;
+
+        match self.failure {
+            Some((msg, position)) => {
+                self.start = position;
+                Token::Error(msg)
+            }
+            None => token,
+        }
+    }
+
+    fn scan(&mut self) -> Token {
+        self.start = self.position;
+        let Some(ch) = self.read_char() else { return Token::Eof };
+
+        match ch {
+            ' ' | '\t' | '\r' | '\n' => self.scan(),
+            '(' => Token::LeftParen,
+            ')' => Token::RightParen,
+            '{' => {
+                if let Some((_, depth)) = self.interpolations.last_mut() {
+                    *depth += 1;
+                }
+
+                Token::LeftBrace
+            }
+            '}' => match self.interpolations.last_mut() {
+                /* End of interpolated expression, scan the rest of it's string */
+                Some((quote, 0)) => {
+                    let quote = *quote;
+                    self.interpolations.pop();
+
+                    self.string(quote)
+                }
+                Some((_, depth)) => {
+                    *depth -= 1;
+                    Token::RightBrace
+                }
+                None => Token::RightBrace,
+            },
+            ',' => Token::Comma,
+            '.' => {
+                if self.peek_char() != Some('.') { return Token::Dot; }
+                self.read_char();
 
-        match byt {
-            b' ' | b'\t' | b'\r' => self.next(),
-            b'\n' => {
-                self.line += 1;
-                self.next()
+                self.check_ahead('.', Token::DotDot, Token::Ellipsis)
             }
-            b'(' => Token::LeftParen,
-            b')' => Token::RightParen,
-            b'{' => Token::LeftBrace,
-            b'}' => Token::RightBrace,
-            b',' => Token::Comma,
-            b'.' => Token::Dot,
-            b'-' => Token::Minus,
-            b'+' => Token::Plus,
-            b':' => Token::Colon,
-            b';' => Token::SemiColon,
-            b'*' => Token::Star,
-            b'|' => {
-                if self.read_byte() != b'|' {
+            '-' => match self.check_ahead('>', Token::Minus, Token::ThinArrow) {
+                Token::Minus => match self.check_ahead('=', Token::Minus, Token::MinusEqual) {
+                    Token::Minus => self.check_ahead('-', Token::Minus, Token::MinusMinus),
+                    t => t,
+                },
+                t => t,
+            },
+            '+' => match self.check_ahead('=', Token::Plus, Token::PlusEqual) {
+                Token::Plus => self.check_ahead('+', Token::Plus, Token::PlusPlus),
+                t => t,
+            },
+            ':' => self.check_ahead(':', Token::Colon, Token::ColonColon),
+            ';' => Token::SemiColon,
+            '*' => self.check_ahead('=', Token::Star, Token::StarEqual),
+            '%' => self.check_ahead('=', Token::Percent, Token::PercentEqual),
+            '?' => {
+                match self.check_ahead('?', Token::Question, Token::QuestionQuestion) {
+                    Token::Question => self.check_ahead('.', Token::Question, Token::QuestionDot),
+                    t => t,
+                }
+            }
+            '|' => {
+                if self.read_char() != Some('|') {
                     return Token::Error("Invalid '|'")
                 }
 
                 Token::Or
             }
-            b'&' => self.check_ahead(b'&', Token::Ampersand, Token::And),
-            b'!' => self.check_ahead(b'=', Token::Bang, Token::BangEqual),
-            b'=' => {
+            '&' => self.check_ahead('&', Token::Ampersand, Token::And),
+            '!' => self.check_ahead('=', Token::Bang, Token::BangEqual),
+            '=' => {
                 // => and == support
-                match self.check_ahead(b'=', Token::Equal, Token::EqualEqual) {
+                match self.check_ahead('=', Token::Equal, Token::EqualEqual) {
                     t if t == Token::EqualEqual => { t }
-                    _ => self.check_ahead(b'>', Token::Equal, Token::Arrow)
+                    _ => self.check_ahead('>', Token::Equal, Token::Arrow)
                 }
             },
-            b'>' => self.check_ahead(b'=', Token::Greater, Token::GreaterEqual),
-            b'<' => self.check_ahead(b'=', Token::Less, Token::LessEqual),
-            b'/' => {
-                if *self.peek_byte() == b'/' {
-                    self.read_byte();
+            '>' => self.check_ahead('=', Token::Greater, Token::GreaterEqual),
+            '<' => self.check_ahead('=', Token::Less, Token::LessEqual),
+            '/' => {
+                if self.peek_char() == Some('/') {
+                    self.read_char();
                     self.comment(false)
-                } else if *self.peek_byte() == b'*' {
-                    self.read_byte();
+                } else if self.peek_char() == Some('*') {
+                    self.read_char();
                     self.comment(true)
                 } else {
-                    Token::Slash
+                    self.check_ahead('=', Token::Slash, Token::SlashEqual)
                 }
             }
-            b'0'..=b'9' => self.number(byt),
-            b'\'' | b'"' => self.string(byt),
-            b'A'..=b'Z' | b'a'..=b'z' | b'_' => self.keyword(byt),
-            b'\0' => Token::Eof,
+            '0'..='9' => self.number(ch),
+            '\'' | '"' => self.string(ch),
+            'r' => match self.peek_char() {
+                Some(quote @ ('\'' | '"')) => {
+                    self.read_char();
+                    self.raw_string(quote)
+                }
+                _ => self.keyword(ch),
+            },
+            /* Identifiers follow UAX #31 */
+            _ if ch == '_' || unicode_ident::is_xid_start(ch) => self.keyword(ch),
             _ => Token::Error("Invalid Token"),
         }
     }
 
-    fn peek_byte(&mut self) -> &u8 {
-        match self.source.peek() {
-            Some(Ok(r)) => r,
-            Some(_) => panic!("error on byte peek"),
-            None => &b'\0',
+    fn peek_char(&mut self) -> Option<char> {
+        if self.peeked.is_none() {
+            self.peeked = self.decode();
         }
+
+        self.peeked
     }
 
-    fn next_byte(&mut self) -> Option<u8> {
-        self.source.next().and_then(|r| Some(r.unwrap()))
+    /// Consume next char, None at the end of source or after it failed.
+    ///
+    fn read_char(&mut self) -> Option<char> {
+        let ch = match self.peeked.take() {
+            Some(ch) => ch,
+            None => self.decode()?,
+        };
+
+        self.position.offset += ch.len_utf8();
+
+        if ch == '\n' {
+            self.position.line += 1;
+            self.position.column = 1;
+        } else {
+            self.position.column += 1;
+        }
+
+        Some(ch)
+    }
+
+    /// Read the bytes of next char from source.
+    ///
+    fn decode(&mut self) -> Option<char> {
+        let first = self.read_byte()?;
+
+        let len = match first {
+            0x00..=0x7F => return Some(first as char),
+            0xC0..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            0xF0..=0xF7 => 4,
+            _ => return self.error("Invalid UTF-8."),
+        };
+
+        let mut bytes = [first, 0, 0, 0];
+
+        for byte in bytes[1..len].iter_mut() {
+            match self.read_byte() {
+                Some(b) => *byte = b,
+                None => return self.error("Invalid UTF-8."),
+            }
+        }
+
+        /* Also rejects overlong encodings and surrogates */
+        match std::str::from_utf8(&bytes[..len]) {
+            Ok(str) => str.chars().next(),
+            Err(_) => self.error("Invalid UTF-8."),
+        }
     }
 
-    fn read_byte(&mut self) -> u8 {
-        match self.source.next() {
-            Some(Ok(ch)) => ch,
-            Some(_) => panic!("error reading byte on 0 line {}", self.line),
-            None => b'\0',
+    fn read_byte(&mut self) -> Option<u8> {
+        if self.eof || self.failure.is_some() { return None; }
+
+        let Some(byte) = self.source.next() else {
+            self.eof = true;
+            return None;
+        };
+
+        match byte {
+            Ok(byte) => Some(byte),
+            Err(_) => {
+                self.error("Could not read source.");
+                None
+            }
         }
     }
 
-    fn check_ahead(&mut self, ahead: u8, short: Token, long: Token) -> Token {
-        if *self.peek_byte() == ahead {
-            self.next_byte();
+    fn check_ahead(&mut self, ahead: char, short: Token, long: Token) -> Token {
+        if self.peek_char() == Some(ahead) {
+            self.read_char();
             long
         } else {
             short
         }
     }
 
-    fn number(&mut self, num: u8) -> Token {
-        let n = self.peek_byte();
+    fn number(&mut self, num: char) -> Token {
+        let n = self.peek_char();
 
         match num {
-            _ if *n == b'b' && num == b'0' => self.number_binary(),
-            _ if *n == b'x' && num == b'0' => self.number_hex(),
+            _ if n == Some('b') && num == '0' => self.number_binary(),
+            _ if n == Some('x') && num == '0' => self.number_hex(),
             _ => {
-                let mut result = u64::try_from(num - b'0').unwrap();
+                let mut result = num.to_digit(10).unwrap() as u64;
 
-                loop {
-                    if !(self.peek_byte().clone() as char).is_ascii_digit() {
-                        break;
-                    }
-
-                    let n = self.next_byte().unwrap();
+                while let Some(n) = self.peek_char().filter(char::is_ascii_digit) {
+                    self.read_char();
 
                     result = result
                         .checked_mul(10)
                         .expect("number overflow")
-                        .checked_add((n - b'0') as u64)
+                        .checked_add(n.to_digit(10).unwrap() as u64)
                         .expect("cannot add {n} to {result}");
                 }
 
-                if *self.peek_byte() == b'.' {
-                    self.read_byte();
+                if self.peek_char() == Some('.') && !self.member {
+                    self.read_char();
 
                     return self.number_float(result);
                 }
@@ -203,13 +386,10 @@ impl<R: std::io::Read> Lexer<R> {
         let mut result = 0.0;
         let mut divisor = 1.0;
 
-        loop {
-            if !(self.peek_byte().clone() as char).is_ascii_digit() {
-                break;
-            }
-            let ch = self.read_byte();
+        while let Some(ch) = self.peek_char().filter(char::is_ascii_digit) {
+            self.read_char();
 
-            result = result + ((ch - b'0') as f64) / (10.0 * divisor);
+            result = result + (ch.to_digit(10).unwrap() as f64) / (10.0 * divisor);
             divisor += 1.0;
         }
 
@@ -217,28 +397,24 @@ impl<R: std::io::Read> Lexer<R> {
     }
 
     fn number_binary(&mut self) -> Token {
-        let mut bnr: i64 = (self.read_byte() - b'0') as i64;
+        let mut bnr: i64 = self.read_char().and_then(|ch| ch.to_digit(10)).unwrap_or(2) as i64;
         if bnr != 1 && bnr != 0 { panic!("invalid binary number.") }
 
         loop {
             // binary number can be represented as 8chars_8chars_8chars.. in a maximum of 8 chunks (asterisk integer type is maximum 64bits long);
             for _ in 0..8 {
-                // 2 is a escape for chars that are before 0 in ASCII table thus must be checked_sub.
-                let ch = self.peek_byte().checked_sub(b'0').unwrap_or(2);
+                // 2 is a escape for chars that are not digits.
+                let ch = self.peek_char().and_then(|ch| ch.to_digit(10)).unwrap_or(2);
                 if ch != 1 && ch != 0 { break; }
-                self.read_byte();
+                self.read_char();
 
                 bnr <<= 1;
                 bnr = bnr.checked_add(ch as i64).expect("binary overflow.");
             }
 
-            dbg!(&bnr);
-            dbg!(*self.peek_byte() as char);
-
-            if *self.peek_byte() != b'_' { break; }
-            self.read_byte();
+            if self.peek_char() != Some('_') { break; }
+            self.read_char();
         }
-        dbg!("====", &bnr);
 
         Token::Integer(bnr)
     }
@@ -246,8 +422,8 @@ impl<R: std::io::Read> Lexer<R> {
     fn number_hex(&mut self) -> Token {
         let mut hex: i64 = 0;
 
-        match self.read_byte() - b'0' {
-            b'A'..b'F' | b'0'..b'9' =>  {
+        match self.read_char() {
+            Some('A'..'F' | '0'..'9') =>  {
 
             }
             _ => panic!("invalid hex value.")
@@ -256,10 +432,10 @@ impl<R: std::io::Read> Lexer<R> {
         let mut dnm: u8 = 0;
 
         loop {
-            let ch = self.peek_byte();
+            let ch = self.peek_char();
 
             match ch {
-                b'A'..b'F' | b'0'..b'9' => {
+                Some('A'..'F' | '0'..'9') => {
 
                 }
                 _ => break
@@ -269,116 +445,186 @@ impl<R: std::io::Read> Lexer<R> {
         Token::Integer(hex)
     }
 
-    fn string(&mut self, t: u8) -> Token {
+    /// Scan string until it's closing quote or an interpolated expression (`${`).
+    ///
+    fn string(&mut self, quote: char) -> Token {
         let mut str: Vec<u8> = Vec::new();
 
         loop {
-            let mut ch = self.read_byte();
+            let Some(ch) = self.read_char() else { return Token::Error("Unterminated string.") };
 
             match ch {
-                // Skip escaped byte
-                b'\\' => {
-                    ch = self.read_byte();
+                '\\' => {
+                    if let Err(msg) = self.escape(&mut str) {
+                        return Token::Error(msg);
+                    }
                 }
-                _ if ch == t => { break }
-                _ => ()
+                '$' if self.peek_char() == Some('{') => {
+                    self.read_char();
+                    self.interpolations.push((quote, 0));
+
+                    return Token::Interpolation(str);
+                }
+                _ if ch == quote => break,
+                _ => push_char(&mut str, ch),
             }
+        }
+
+        Token::String(str)
+    }
+
+    /// Decode escape sequence following a backslash into str.
+    ///
+    fn escape(&mut self, str: &mut Vec<u8>) -> Result<(), &'static str> {
+        let ch = match self.read_char() {
+            Some('n') => '\n',
+            Some('t') => '\t',
+            Some('r') => '\r',
+            Some('0') => '\0',
+            Some(ch @ ('\\' | '\'' | '"' | '$')) => ch,
+            Some('u') => self.unicode_escape().ok_or("Invalid unicode escape, expect \\u{0} to \\u{10FFFF}.")?,
+            _ => return Err("Invalid escape sequence."),
+        };
+
+        push_char(str, ch);
+
+        Ok(())
+    }
 
-            str.push(ch)
+    /// Scan `{hex}` of an unicode escape.
+    ///
+    fn unicode_escape(&mut self) -> Option<char> {
+        if self.read_char()? != '{' { return None; }
+
+        let mut code: u32 = 0;
+        let mut digits = 0;
+
+        loop {
+            let digit = match self.read_char()? {
+                '}' if digits > 0 => break,
+                ch => ch.to_digit(16)?,
+            };
+
+            digits += 1;
+            if digits > 6 { return None; }
+
+            code = code * 16 + digit;
+        }
+
+        char::from_u32(code)
+    }
+
+    /// Scan string without escapes nor interpolations, e.g: r"C:\path".
+    ///
+    fn raw_string(&mut self, quote: char) -> Token {
+        let mut str: Vec<u8> = Vec::new();
+
+        loop {
+            match self.read_char() {
+                None => return Token::Error("Unterminated string."),
+                Some(ch) if ch == quote => break,
+                Some(ch) => push_char(&mut str, ch),
+            }
         }
 
         Token::String(str)
     }
 
-    fn keyword(&mut self, ch: u8) -> Token {
+    fn keyword(&mut self, ch: char) -> Token {
         let mut word = String::new();
-        word.push(ch as char);
+        word.push(ch);
 
         loop {
-            match *self.peek_byte() as char {
-                t if t.is_alphanumeric() || t == '_' => {
-                    self.read_byte();
+            match self.peek_char() {
+                Some(t) if unicode_ident::is_xid_continue(t) => {
+                    self.read_char();
                     word.push(t);
                 }
                 _ => break,
             }
         }
-
         match &word as &str {
             "and" => Token::And,
             "or" => Token::Or,
             "class" => Token::Class,
             "case" => Token::Case,
+            "catch" => Token::Catch,
             "const" => Token::Const,
             "continue" => Token::Continue,
             "default" => Token::Default,
             "else" => Token::Else,
             "false" => Token::False,
+            "finally" => Token::Finally,
             "for" => Token::For,
             "fn" => Token::Fun,
             "if" => Token::If,
             "mut" => Token::Modifier,
+            "nil" => Token::Nil,
             "print" => Token::Print,
             "return" => Token::Return,
             "struct" => Token::StructDef,
+            "impl" => Token::Impl,
+            "trait" => Token::Trait,
+            "type" => Token::TypeAlias,
             "switch" => Token::Switch,
             "super" => Token::Super,
             "this" => Token::This,
+            "throw" => Token::Throw,
             "true" => Token::True,
+            "try" => Token::Try,
             "let" => Token::Var,
             "while" => Token::While,
             "Int" => Token::TypeDef(Type::Int),
             "Float" => Token::TypeDef(Type::Float),
             "Bool" => Token::TypeDef(Type::Bool),
             "String" => Token::TypeDef(Type::String),
+            "Struct" => Token::TypeDef(Type::Struct),
+            "Result" => Token::TypeDef(Type::Result),
+            "Ok" => Token::Ok,
+            "Err" => Token::Err,
+            "Fn" => Token::TypeDef(Type::Fn(None)),
+            "Tuple" => Token::TypeDef(Type::Tuple(None)),
             _ => Token::Identifier(word),
         }
     }
 
     fn comment(&mut self, multi: bool) -> Token {
-        match multi {
-            true => {
-                while let Some(c) = self.next_byte() {
-                    if c == b'\n' { self.line += 1 };
-
-                    if c == b'*' {
-                        let d = self.read_byte();
-
-                        if d == b'/' {
-                            break;
-                        }
-                    }
-                }
-            }
-            false => {
-                while let Some(c) = self.next_byte() {
-                    if c == b'\n' {
-                        self.line += 1;
-                        break;
-                    }
+        loop {
+            match self.read_char() {
+                None => break,
+                Some('\n') if !multi => break,
+                Some('*') if multi && self.peek_char() == Some('/') => {
+                    self.read_char();
+                    break;
                 }
+                _ => (),
             }
         }
 
-        self.next()
+        self.scan()
     }
 
     /// FIXME
     pub fn curr_tok(&mut self) -> String {
         let mut word = String::new();
 
-        loop {
-            let ch = self.read_byte();
-            if ch != b' ' {
-                break;
-            }
-
+        while let Some(ch @ ' ') = self.read_char() {
             /* \x1B[4m{}\x1B[0m */
-            word.push_str(&format!("{}\u{0334}", ch as char) as &str);
+            word.push_str(&format!("{}\u{0334}", ch) as &str);
         }
 
         word
     }
 
-    fn error(&mut self) {}
-}
\ No newline at end of file
+    /// Stop scanning at current position, reporting msg as the next token.
+    ///
+    fn error(&mut self, msg: &'static str) -> Option<char> {
+        self.failure = Some((msg, self.position));
+
+        None
+    }
+}
+
+fn push_char(str: &mut Vec<u8>, ch: char) {
+    str.extend_from_slice(ch.encode_utf8(&mut [0; 4]).as_bytes());
+}
@@ -1,26 +1,29 @@
+pub mod consts;
 pub mod lexer;
 pub mod ruler;
 pub mod scope;
 
-use std::{cell::RefCell};
+use std::{cell::RefCell, collections::HashMap};
 #[allow(unused)]
 use std::{rc::Rc, thread::{self, current}, time::Duration};
 
-use lexer::{Lexer, Token};
+use lexer::{Lexer, Position, Token};
 use ruler::{get_rule, Precedence};
 
-use crate::primitives::{primitive::UpValue, structs::Struct, types::Dyn};
+use crate::primitives::{primitive::UpValue, structs::{Methods, Struct}, tuple::Tuple, types::{Dyn, Signature, Trait}};
+use crate::vm::globals::Slots;
+use crate::objects::string::IString;
 #[allow(unused)]
 use crate::{
-    parser::scope::Scope,
+    parser::scope::{Borrow, Place, Scope, Try},
     primitives::{
-        functions::{Function, FunctionType},
+        functions::{Function, FunctionType, Param},
         primitive::{Primitive},
         types::{Modifier, Type},
         value::Value,
     },
-    utils::print::disassemble_chunk,
-    vm::chunk::OpCode,
+    utils::{parse_type, print::disassemble_chunk},
+    vm::chunk::{OpCode, JUMP_SIZE},
 };
 
 #[derive(Debug)]
@@ -34,10 +37,32 @@ pub struct Parser<R: std::io::Read> {
     pub previous: Token,
     pub had_error: bool,
     pub scopes: Vec<Scope>,
+    /// Position of previous Token.
+    pub previous_start: Position,
+    /// Last expression compiled to a reference to a local variable, with code length after it.
+    pub borrowed: Option<(Borrow, usize)>,
+    /// Last variable read, with the fields accessed on it.
+    pub place: Option<Place>,
+    /// Next expression parsed is a whole statement (or a for increment clause).
+    pub statement: bool,
+    /// Expression being parsed is a whole statement, the only one which can assign as assignments leave no value.
+    pub assigns: bool,
+    /// Try statements enclosing the code being compiled, innermost last.
+    pub tries: Vec<Try>,
+    /// Type parameters annotations may refer to, declared by the function being compiled, an enclosing one or the struct of its impl block.
+    pub type_params: Vec<String>,
+    /// Aliases, traits and structs declared by the function being compiled, the ones of enclosing functions are found through up_context.
+    pub types: HashMap<String, Type>,
+    /// Type parameters of the structs declared by the function being compiled, for their impl blocks to use.
+    pub struct_params: HashMap<String, Vec<String>>,
+    /// Constants declared by the function being compiled outside of any scope, folded into the chunk wherever they are read.
+    pub consts: HashMap<String, Primitive>,
+    /// Global slots of the Vm the script is compiled for.
+    pub slots: Slots,
 }
 
 impl<R: std::io::Read> Parser<R> {
-    pub fn new(function: Function, function_type: FunctionType, lexer: Lexer<R>) -> Self {
+    pub fn new(function: Function, function_type: FunctionType, lexer: Lexer<R>, slots: Slots) -> Self {
         Parser {
             function,
             function_type,
@@ -48,6 +73,17 @@ impl<R: std::io::Read> Parser<R> {
             previous: Token::Nil,
             had_error: false,
             scopes: vec![],
+            previous_start: Position::default(),
+            borrowed: None,
+            place: None,
+            statement: false,
+            assigns: false,
+            tries: vec![],
+            type_params: vec![],
+            types: HashMap::new(),
+            struct_params: HashMap::new(),
+            consts: HashMap::new(),
+            slots,
         }
     }
 }
@@ -66,6 +102,14 @@ impl<R: std::io::Read> Parser<R> {
             self = self.var_declaration();
         } else if self.match_token(Token::StructDef) {
             self = self.define_struct();
+        } else if self.match_token(Token::Impl) {
+            self = self.impl_declaration();
+        } else if self.match_token(Token::Trait) {
+            self = self.trait_declaration();
+        } else if self.match_token(Token::TypeAlias) {
+            self = self.alias_declaration();
+        } else if self.match_token(Token::Const) {
+            self = self.const_declaration();
         } else if self.match_token(Token::LeftBrace) {
             self.begin_scope();
             self = self.block();
@@ -108,7 +152,7 @@ impl<R: std::io::Read> Parser<R> {
 
         /* Let function as value available on top of stack */
         match global_var {
-            Some(idx) => self.define_variable(idx, modifier, Type::Fn),
+            Some(idx) => self.define_variable(idx, modifier, Type::Fn(None)),
             None => self.mark_initialized(name, Type::Closure),
         }
 
@@ -125,11 +169,16 @@ impl<R: std::io::Read> Parser<R> {
             i_function,
             i_lexer,
             i_previous,
+            i_previous_start,
             i_current,
             mut _self
         ) = {
             let current = self.get_current();
             let previous = self.get_previous();
+            let previous_start = self.previous_start;
+            let slots = Rc::clone(&self.slots);
+            /* Type parameters of enclosing functions and impl blocks stay usable */
+            let outer_params = self.type_params.clone();
             /* New parser creation, it basically changes actual parser with a new one */
             let mut parser: Parser<R> = Parser {
                 function: Function::new(func_name),
@@ -142,43 +191,102 @@ impl<R: std::io::Read> Parser<R> {
                 previous,
                 had_error: false,
                 scopes: vec![],
+                previous_start,
+                borrowed: None,
+                place: None,
+                statement: false,
+                assigns: false,
+                tries: vec![],
+                type_params: vec![],
+                types: HashMap::new(),
+                struct_params: HashMap::new(),
+                consts: HashMap::new(),
+                slots,
             };
 
+            let mut signature = Signature::default();
+
+            /* Type parameters: fn first<T>(x: T) -> T {} */
+            let own_params = parser.parse_type_params();
+            parser.function.type_params = own_params.iter().map(|name| name.as_str().into()).collect();
+            parser.type_params = outer_params.into_iter().chain(own_params).collect();
+
             parser.begin_scope();
             parser.consume(Token::LeftParen, "Expect '(' after function name.");
             if !parser.check(Token::RightParen) {
                 let modifier = Modifier::Const;
                 loop {
                     parser.function.arity += 1;
+                    /* Variadic: (a, ...rest) */
+                    let variadic = parser.match_token(Token::Ellipsis);
+
                     let local_name = match parser.get_current() {
                         Token::Identifier(name) => name,
+                        Token::This => "this".to_string(),
                         _ => parser.error("Could not parse arguments."),
                     };
                     parser.advance();
                     parser.parse_variable(modifier, local_name.clone());
 
-                    /* Type defs: (a: x, b: y, c: z) */
-                    parser.consume(
-                        Token::Colon,
-                        "Expect: Type specification on function signature.",
-                    );
+                    /* Type defs: (a: x, b: y, c: z), variadic args are always collected into a Tuple */
+                    let t = if variadic {
+                        Type::Tuple(None)
+                    } else if parser.match_token(Token::Colon) {
+                        parser.parse_var_type()
+                    } else {
+                        Type::UnInit
+                    };
+
+                    /* Default values: (a: x = 1, b = 2) */
+                    let default = if !variadic && parser.match_token(Token::Equal) {
+                        let default = parser.parse_default_value();
+
+                        if !default.conforms_to(&t) {
+                            parser.error("Default value does not match parameter type.");
+                        }
+
+                        Some(default)
+                    } else {
+                        None
+                    };
+
+                    if default.is_none() && parser.function.params.iter().any(|p| p.default.is_some()) && !variadic {
+                        parser.error("Parameters without default value must come before defaulted ones.");
+                    }
 
-                    let t = parser.parse_var_type();
+                    signature.params.push(t.clone());
+                    parser.function.params.push(Param { name: local_name.as_str().into(), default });
                     parser.mark_initialized(local_name, t);
 
+                    if variadic {
+                        parser.function.variadic = true;
+                        break;
+                    }
+
                     if !parser.match_token(Token::Comma) {
                         break;
                     }
                 }
             }
             parser.consume(Token::RightParen, "Expect ')' after function parameters.");
+
+            /* Return type: fn f() -> x {} */
+            if parser.match_token(Token::ThinArrow) {
+                signature._return = parser.parse_var_type();
+            }
+
+            let signature = Rc::new(signature);
+            parser.function.signature = Rc::clone(&signature);
+
             parser.consume(Token::LeftBrace, "Expect '{' after function name.");
             parser = parser.block();
             /* End-of-scope are automatically handled by block() */
 
+            parser.function.upvalues = std::mem::take(&mut parser.upvalues);
+
             let function = Value {
                 value: Primitive::Function(Rc::new(parser.end_compiler())),
-                _type: Type::Fn,
+                _type: Type::Fn(Some(signature)),
                 modifier: Modifier::Const,
             };
 
@@ -186,6 +294,7 @@ impl<R: std::io::Read> Parser<R> {
                 function,
                 parser.lexer.take(),
                 parser.previous,
+                parser.previous_start,
                 parser.current,
                 parser.up_context.take().unwrap()
             )
@@ -194,6 +303,7 @@ impl<R: std::io::Read> Parser<R> {
         /* Re-assign ownership over Lexer and it's Tokens to parent Parser */
         _self.lexer = i_lexer;
         _self.previous = i_previous;
+        _self.previous_start = i_previous_start;
         _self.current = i_current;
 
         _self.emit_constant(i_function);
@@ -206,19 +316,51 @@ impl<R: std::io::Read> Parser<R> {
         *_self
     }
 
+    /// Parse parameter default value, which must be a literal as it's evaluated at compile time.
+    ///
+    fn parse_default_value(&mut self) -> Value {
+        let negative = self.match_token(Token::Minus);
+
+        let value = match (self.get_current(), negative) {
+            (Token::Integer(i), _) => Primitive::Int(if negative { -i } else { i }),
+            (Token::Float(f), _) => Primitive::Float(if negative { -f } else { f }),
+            (Token::String(s), false) => Primitive::String(String::from_utf8(s).unwrap().into()),
+            (Token::True, false) => Primitive::Bool(true),
+            (Token::False, false) => Primitive::Bool(false),
+            (Token::Nil, false) => Primitive::Nil,
+            _ => self.error("Default value must be a literal."),
+        };
+        self.advance();
+
+        Value {
+            _type: parse_type(&value),
+            value,
+            modifier: Modifier::Const,
+        }
+    }
+
     /// Set new variable with SetGlobal or push a value to stack throught GetGlobal.
     ///
     pub fn var_declaration(mut self) -> Self {
+        if self.match_token(Token::LeftParen) {
+            return self.destructuring_declaration();
+        }
+
         let modifier = self.parse_modifier();
         let var_name = match self.get_current() {
             Token::Identifier(s) => s,
             _ => self.error("Expect variable name."),
         };
+        self.advance();
+
+        /* Name was the struct of a pattern: let P { x, y } = p; */
+        if modifier == Modifier::Const && self.match_token(Token::LeftBrace) {
+            return self.struct_destructuring(var_name);
+        }
+
         let global = self.parse_variable(modifier, var_name.clone());
         let mut _type = None;
 
-        self.advance();
-
         // Checks if after consuming identifier '=' Token is present.
         if self.match_token(Token::Equal) {
             self = self.expression();
@@ -239,6 +381,7 @@ impl<R: std::io::Read> Parser<R> {
         self.consume(Token::SemiColon, "Expect ';' after variable declaration.");
 
         if global.is_none() {
+            self.hold_borrow(&var_name, self.previous_start);
             self.mark_initialized(var_name, _type.unwrap_or_default());
 
             return self;
@@ -249,6 +392,140 @@ impl<R: std::io::Read> Parser<R> {
         self
     }
 
+    /// Bind each item of a tuple to it's own variable, as in let (a, mut b): (Int, Int) = t;
+    ///
+    fn destructuring_declaration(mut self) -> Self {
+        let mut bindings: Vec<(Modifier, String)> = vec![];
+
+        loop {
+            let modifier = self.parse_modifier();
+            let var_name = match self.get_current() {
+                Token::Identifier(s) => s,
+                _ => self.error("Expect variable name."),
+            };
+            self.advance();
+
+            bindings.push((modifier, var_name));
+
+            /* Trailing comma: let (a,) = t; */
+            if !self.match_token(Token::Comma) || self.check(Token::RightParen) {
+                break;
+            }
+        }
+        self.consume(Token::RightParen, "Expect ')' after destructured variables.");
+
+        let types: Vec<Type> = match self.match_token(Token::Colon) {
+            true => match self.parse_var_type() {
+                Type::Tuple(Some(types)) if types.len() == bindings.len() => types.to_vec(),
+                t => self.error(&format!("Cannot destructure {} into {} variables.", t, bindings.len())),
+            },
+            false => vec![Type::UnInit; bindings.len()],
+        };
+
+        self.consume(Token::Equal, "Uninitialized variables are not allowed.");
+        self = self.expression();
+        self.consume(Token::SemiColon, "Expect ';' after variable declaration.");
+
+        self.emit_byte(OpCode::Unpack(bindings.len()));
+
+        self.define_bindings(bindings, types)
+    }
+
+    /// Bind fields of a struct instance to variables, as in let P { x, y: mut b, .. } = p;
+    ///
+    /// Every field must be bound, unless the pattern ends with `..`.
+    ///
+    fn struct_destructuring(mut self, struct_name: String) -> Self {
+        let mut bindings: Vec<(Modifier, String)> = vec![];
+        let mut fields: Vec<String> = vec![];
+        let mut rest = false;
+
+        while !self.check(Token::RightBrace) {
+            if self.match_token(Token::DotDot) {
+                rest = true;
+                break;
+            }
+
+            let mut modifier = self.parse_modifier();
+            let field = match self.get_current() {
+                Token::Identifier(s) => s,
+                _ => self.error("Expect field name."),
+            };
+            self.advance();
+
+            /* Renamed binding: P { x: a } */
+            let var_name = match self.match_token(Token::Colon) {
+                true => {
+                    modifier = self.parse_modifier();
+
+                    match self.get_current() {
+                        Token::Identifier(s) => { self.advance(); s }
+                        _ => self.error("Expect variable name."),
+                    }
+                }
+                false => field.clone(),
+            };
+
+            fields.push(field);
+            bindings.push((modifier, var_name));
+
+            if !self.match_token(Token::Comma) {
+                break;
+            }
+        }
+        self.consume(Token::RightBrace, "Expect '}' after destructured fields.");
+
+        self.consume(Token::Equal, "Uninitialized variables are not allowed.");
+        self = self.expression();
+        self.consume(Token::SemiColon, "Expect ';' after variable declaration.");
+
+        /* Struct the instance is checked against */
+        self.previous = Token::Identifier(struct_name);
+        let rule = get_rule::<R>(&self.previous).prefix;
+        self = rule(self, false);
+
+        let names_index = self.names_constant(fields);
+        self.emit_byte(OpCode::UnpackInstance(names_index, rest));
+
+        let types = vec![Type::UnInit; bindings.len()];
+
+        self.define_bindings(bindings, types)
+    }
+
+    /// Define variables for the destructured values on top of stack, in order.
+    ///
+    /// Locals take their slots as they are, while globals are defined from the last one.
+    ///
+    fn define_bindings(mut self, bindings: Vec<(Modifier, String)>, types: Vec<Type>) -> Self {
+        if self.scopes.is_empty() {
+            for ((modifier, var_name), t) in bindings.into_iter().zip(types).rev() {
+                let slot = self.global_slot(var_name).unwrap();
+                self.define_variable(slot, modifier, t);
+            }
+
+            return self;
+        }
+
+        /* Declared after the expression, so it still reads outer variables: let (a, b) = (b, a); */
+        for ((modifier, var_name), t) in bindings.into_iter().zip(types) {
+            self.add_local(modifier, var_name.clone());
+            self.mark_initialized(var_name, t);
+        }
+
+        self
+    }
+
+    /// Write names as a tuple constant (named arguments, struct patterns), returning it's index.
+    ///
+    pub fn names_constant(&mut self, names: Vec<String>) -> usize {
+        let names = names
+            .into_iter()
+            .map(|name| Value { value: Primitive::String(name.into()), _type: Type::String, modifier: Modifier::Const })
+            .collect::<Vec<Value>>();
+
+        self.function.chunk.write_constant(Primitive::Tuple(Tuple::new(names.into())))
+    }
+
     /// Match current Token for Modifier(Mut) / Identifier(Const).
     ///
     fn parse_modifier(&mut self) -> Modifier {
@@ -263,50 +540,147 @@ impl<R: std::io::Read> Parser<R> {
         }
     }
 
-    /// Set local/global variables to scopes by emitting new constant (if global), returning it's index.
+    /// Set local/global variables to scopes by resolving it's slot (if global), returning it.
     ///
     /// Local Variables are auto-declared so to speak, It follows a convention on var declaration
     /// and scope-flow, so there's no need to set them to constants vector, the Compiler (Parser) object already take care
     /// of which indexes behaves to which variables by scope_depth and local_count when local vars are set.
     ///
-    /// Return 0 when variable is local, which will be ignored by define_variable(), so it is not set to constants.
+    /// Return None when variable is local, as it does not need to be defined by define_variable().
     ///
     fn parse_variable(&mut self, modifier: Modifier, name: String) -> Option<usize> {
         // Check if var is global
         if self.scopes.len() == 0 {
-            return self.identifier_constant(name);
+            /* Reads would still resolve to the constant */
+            if self.resolve_const(&name).is_some() {
+                self.error(&format!("Cannot redeclare constant '{name}'."));
+            }
+
+            return self.global_slot(name);
         }
 
         self.add_local(modifier, name);
         return None;
     }
 
+    /// Parse type parameters names if any, as in <A, B>
+    ///
+    fn parse_type_params(&mut self) -> Vec<String> {
+        let mut names = vec![];
+
+        if !self.match_token(Token::Less) {
+            return names;
+        }
+
+        loop {
+            match self.get_current() {
+                Token::Identifier(name) if !names.contains(&name) => names.push(name),
+                Token::Identifier(name) => self.error(&format!("Type parameter '{name}' is already declared.")),
+                _ => self.error("Expect type parameter name."),
+            }
+            self.advance();
+
+            if !self.match_token(Token::Comma) { break; }
+        }
+        self.consume(Token::Greater, "Expect '>' after type parameters.");
+
+        names
+    }
+
     /// Try to extract current type from TypeDef Token recursivelly.
     ///
     /// Executed when explicit type definition is set with :
     ///
     pub fn parse_var_type(&mut self) -> Type {
-        match self.get_current() {
+        let t = match self.get_current() {
             Token::Ampersand => {
                 self.advance();
-                Type::Ref(Rc::new(self.parse_var_type()))
+
+                match self.match_token(Token::Modifier) {
+                    true => Type::MutRef(Rc::new(self.parse_var_type())),
+                    false => Type::Ref(Rc::new(self.parse_var_type())),
+                }
             }
             Token::TypeDef(t) => {
                 self.advance();
                 t
             }
+            /* Type parameter: fn f<T>(x: T) {} */
+            Token::Identifier(name) if self.type_params.contains(&name) => {
+                self.advance();
+                Type::Param(name.into())
+            }
+            /* Alias, trait or struct: type Id = Int; trait Shape {} */
+            Token::Identifier(name) => match self.resolve_type(&name) {
+                Some(t) => {
+                    self.advance();
+                    t
+                }
+                None => self.error("Invalid Var Type."),
+            },
+            Token::Fun => {
+                self.advance();
+                Type::Fn(Some(Rc::new(self.parse_signature())))
+            }
+            /* Tuple: (x, y), a single type is only a tuple with a trailing comma: (x,) */
+            Token::LeftParen => {
+                self.advance();
+
+                let mut types = vec![];
+                let mut trailing_comma = false;
+
+                while !self.check(Token::RightParen) {
+                    types.push(self.parse_var_type());
+
+                    trailing_comma = self.match_token(Token::Comma);
+                    if !trailing_comma { break; }
+                }
+                self.consume(Token::RightParen, "Expect ')' after tuple types.");
+
+                match types.len() == 1 && !trailing_comma {
+                    true => types.pop().unwrap(),
+                    false => Type::Tuple(Some(types.into())),
+                }
+            }
             _ => self.error("Invalid Var Type."),
+        };
+
+        /* Optional: T? */
+        if self.match_token(Token::Question) {
+            return Type::Optional(Rc::new(t));
+        }
+
+        t
+    }
+
+    /// Parse function type annotation parameters and return, as in fn(x, y) -> z
+    ///
+    fn parse_signature(&mut self) -> Signature {
+        let mut signature = Signature::default();
+
+        self.consume(Token::LeftParen, "Expect '(' after 'fn' on type annotation.");
+        if !self.check(Token::RightParen) {
+            loop {
+                signature.params.push(self.parse_var_type());
+
+                if !self.match_token(Token::Comma) {
+                    break;
+                }
+            }
+        }
+        self.consume(Token::RightParen, "Expect ')' after function type parameters.");
+
+        if self.match_token(Token::ThinArrow) {
+            signature._return = self.parse_var_type();
         }
+
+        signature
     }
 
-    /// Receive variable's name and emit it's Identifier as String to constants vector.
+    /// Resolve global variable's name to it's slot, so Vm does not need to hash the name on every access.
     ///
-    fn identifier_constant(&mut self, name: String) -> Option<usize> {
-        Some(
-            self.function
-                .chunk
-                .write_constant(Primitive::String(name.into())),
-        )
+    fn global_slot(&mut self, name: String) -> Option<usize> {
+        Some(self.slots.borrow_mut().resolve(&name))
     }
 
     /// Set previous Token as local variable, assign it to compiler.locals, increasing Compiler's local_count
@@ -335,18 +709,21 @@ impl<R: std::io::Read> Parser<R> {
             .get_local(&local_name)
             .unwrap();
 
+        let type_index = self.function.chunk.write_type(_type);
+
         // (idx on stack, Modifier, Type)
         self.emit_byte(OpCode::DefineLocal(
             local_index.borrow().0,
             local_index.borrow().1,
-            _type
+            type_index
         ));
     }
 
-    /// Emit DefineGlobal ByteCode with provided index. (global variables only)
+    /// Emit DefineGlobal ByteCode with provided slot. (global variables only)
     ///
-    pub fn define_variable(&mut self, name_index: usize, modifier: Modifier, _type: Type) {
-        self.emit_byte(OpCode::DefineGlobal(name_index, modifier, _type));
+    pub fn define_variable(&mut self, slot: usize, modifier: Modifier, _type: Type) {
+        let type_index = self.function.chunk.write_type(_type);
+        self.emit_byte(OpCode::DefineGlobal(slot, modifier, type_index));
     }
 
     /// Build struct blueprint by parsing name and it's types
@@ -358,9 +735,11 @@ impl<R: std::io::Read> Parser<R> {
         };
 
         let mut field_count = 0;
-        let mut field_indices = std::collections::HashMap::<String, (Type, usize)>::new();
+        let mut field_indices = std::collections::HashMap::<IString, (Type, usize)>::new();
+        let mut defaults = vec![];
 
         self.advance();
+        let type_params = self.parse_type_params();
         self.consume(Token::LeftBrace, "Expect '{'.");
 
         // If field type has dynamically resolved types
@@ -377,6 +756,10 @@ impl<R: std::io::Read> Parser<R> {
 
             let _type = match self.get_current() {
                 Token::TypeDef(t) => t,
+                /* Type parameter: struct Pair<A, B> { a: A, b: B } */
+                Token::Identifier(id) if type_params.contains(&id) => Type::Param(id.into()),
+                /* Structs are read when the struct is defined, as in other expressions */
+                Token::Identifier(id) if self.resolve_type(&id).is_some_and(|t| !matches!(t, Type::Dyn(_))) => self.resolve_type(&id).unwrap(),
                 Token::Identifier(id) => {
                     dyn_count += 1;
 
@@ -393,9 +776,30 @@ impl<R: std::io::Read> Parser<R> {
             // : or }
             self.advance();
 
+            let _type = match self.match_token(Token::Question) {
+                true => Type::Optional(Rc::new(_type)),
+                false => _type,
+            };
+
+            /* Default values: count: Int = 0, optional fields default to nil */
+            let default = if self.match_token(Token::Equal) {
+                let default = self.parse_default_value();
+
+                if !default.conforms_to(&_type) {
+                    self.error("Default value does not match field type.");
+                }
+
+                Some(default)
+            } else if let Type::Optional(_) = _type {
+                Some(Value { value: Primitive::Nil, _type: Type::Nil, modifier: Modifier::Const })
+            } else {
+                None
+            };
+            defaults.push(default);
+
             match tok {
                 Token::Identifier(id) => {
-                    field_indices.insert(id, (_type, field_count.clone()));
+                    field_indices.insert(id.into(), (_type, field_count.clone()));
                     field_count += 1;
                 }
                 _ => self.error("Invalid token when defining struct."),
@@ -412,11 +816,21 @@ impl<R: std::io::Read> Parser<R> {
             name: name.clone(),
             field_count,
             field_indices,
+            defaults,
+            methods: Methods::default(),
+            type_params: type_params.iter().map(|name| name.as_str().into()).collect(),
         };
+        self.struct_params.insert(name.clone(), type_params);
+
         let is_global = self.scopes.len() == 0;
         let global_idx = self.parse_variable(Modifier::Const, name.clone());
 
-        self.emit_constant(_struct.into());
+        let blueprint: Value = _struct.into();
+
+        /* Annotations naming the struct accept it's instances: let p: P? = nil; */
+        self.types.insert(name.clone(), Type::Dyn(Dyn(Rc::new(RefCell::new(blueprint.clone())))));
+
+        self.emit_constant(blueprint);
 
         if dyn_count > 0 {
             self.emit_byte(OpCode::ParseStructDyn(dyn_count));
@@ -431,6 +845,254 @@ impl<R: std::io::Read> Parser<R> {
         self
     }
 
+    /// Add methods to a struct: impl P { fn area(this) -> Float { ... } }
+    ///
+    /// Implementing a trait (impl Shape for P {}) checks the struct has every trait method once the block runs.
+    ///
+    pub fn impl_declaration(mut self) -> Self {
+        let mut struct_name = match self.get_current() {
+            Token::Identifier(s) => s,
+            _ => self.error("Expect struct name after 'impl'."),
+        };
+        self.advance();
+
+        let mut _trait = None;
+
+        if self.match_token(Token::For) {
+            _trait = match self.resolve_type(&struct_name) {
+                Some(Type::Trait(_trait)) => Some(_trait),
+                _ => self.error(&format!("Unknown trait '{struct_name}'.")),
+            };
+
+            struct_name = match self.get_current() {
+                Token::Identifier(s) => s,
+                _ => self.error("Expect struct name after 'for'."),
+            };
+            self.advance();
+        }
+
+        /* Struct stays on stack while it's methods are added */
+        self.previous = Token::Identifier(struct_name.clone());
+        let rule = get_rule::<R>(&self.previous).prefix;
+        self = rule(self, false);
+
+        self.consume(Token::LeftBrace, "Expect '{' after impl struct name.");
+
+        /* Methods may refer to the struct type parameters */
+        let outer_count = self.type_params.len();
+        let struct_params = self.resolve_struct_params(&struct_name).unwrap_or_default();
+        self.type_params.extend(struct_params);
+
+        while !self.check(Token::RightBrace) {
+            self.consume(Token::Fun, "Expect method declaration.");
+
+            let name = match self.get_current() {
+                Token::Identifier(s) => s,
+                _ => self.error("Expect method name."),
+            };
+            self.advance();
+
+            if let Some(_trait) = &_trait {
+                if !_trait.methods.iter().any(|(method, _)| method.as_str() == name) {
+                    self.error(&format!("Method '{}' is not a member of trait {}.", name, _trait.name));
+                }
+            }
+
+            self = self.function(FunctionType::Fn, format!("{struct_name}::{name}"));
+
+            let name_index = self.function.chunk.write_constant(Primitive::String(name.into()));
+            self.emit_byte(OpCode::Method(name_index));
+        }
+        self.consume(Token::RightBrace, "Expect '}' after impl methods.");
+        self.type_params.truncate(outer_count);
+
+        if let Some(_trait) = _trait {
+            let type_index = self.function.chunk.write_type(Type::Trait(_trait));
+            self.emit_byte(OpCode::Implement(type_index));
+        }
+
+        self.emit_byte(OpCode::Pop);
+
+        self
+    }
+
+    /// Declare the methods a struct must have to be used as the trait: trait Shape { fn area(this) -> Float; }
+    ///
+    /// Traits only exist while compiling, as annotations and impl blocks refer to them.
+    ///
+    pub fn trait_declaration(mut self) -> Self {
+        let name = match self.get_current() {
+            Token::Identifier(s) => s,
+            _ => self.error("Expect trait name."),
+        };
+        self.advance();
+        self.consume(Token::LeftBrace, "Expect '{' after trait name.");
+
+        let mut methods: Vec<(IString, Rc<Signature>)> = vec![];
+
+        while !self.check(Token::RightBrace) {
+            self.consume(Token::Fun, "Expect method declaration.");
+
+            let method = match self.get_current() {
+                Token::Identifier(s) => s,
+                _ => self.error("Expect method name."),
+            };
+            self.advance();
+
+            if methods.iter().any(|(m, _)| m.as_str() == method) {
+                self.error(&format!("Method '{method}' is already declared in {name}."));
+            }
+
+            let mut signature = Signature::default();
+
+            self.consume(Token::LeftParen, "Expect '(' after method name.");
+            if !self.check(Token::RightParen) {
+                loop {
+                    match self.get_current() {
+                        Token::Identifier(_) | Token::This => self.advance(),
+                        _ => self.error("Could not parse arguments."),
+                    }
+
+                    signature.params.push(match self.match_token(Token::Colon) {
+                        true => self.parse_var_type(),
+                        false => Type::UnInit,
+                    });
+
+                    if !self.match_token(Token::Comma) { break; }
+                }
+            }
+            self.consume(Token::RightParen, "Expect ')' after method parameters.");
+
+            if self.match_token(Token::ThinArrow) {
+                signature._return = self.parse_var_type();
+            }
+            self.consume(Token::SemiColon, "Expect ';' after method declaration.");
+
+            methods.push((method.into(), Rc::new(signature)));
+        }
+        self.consume(Token::RightBrace, "Expect '}' after trait methods.");
+
+        self.declare_type(name.clone(), Type::Trait(Rc::new(Trait { name, methods })));
+
+        self
+    }
+
+    /// Name a type so annotations can use it in it's place: type UserId = Int;
+    ///
+    pub fn alias_declaration(mut self) -> Self {
+        let name = match self.get_current() {
+            Token::Identifier(s) => s,
+            _ => self.error("Expect type alias name."),
+        };
+        self.advance();
+        self.consume(Token::Equal, "Expect '=' after type alias name.");
+
+        let t = self.parse_var_type();
+        self.consume(Token::SemiColon, "Expect ';' after type alias.");
+
+        self.declare_type(name, t);
+
+        self
+    }
+
+    fn declare_type(&mut self, name: String, t: Type) {
+        if self.types.contains_key(&name) {
+            self.error(&format!("Type '{name}' is already declared."));
+        }
+
+        self.types.insert(name, t);
+    }
+
+    /// Alias or trait declared by current function or by an enclosing one.
+    ///
+    pub fn resolve_type(&self, name: &str) -> Option<Type> {
+        match self.types.get(name) {
+            Some(t) => Some(t.clone()),
+            None => self.up_context.as_ref()?.resolve_type(name),
+        }
+    }
+
+    /// Type parameters of a struct declared by current function or by an enclosing one.
+    ///
+    pub fn resolve_struct_params(&self, name: &str) -> Option<Vec<String>> {
+        match self.struct_params.get(name) {
+            Some(params) => Some(params.clone()),
+            None => self.up_context.as_ref()?.resolve_struct_params(name),
+        }
+    }
+
+    /// Declare a constant: const MAX: Int = 10 * 1024;
+    ///
+    /// It's initializer is evaluated while compiling, reading the constant emits the resulting value.
+    ///
+    pub fn const_declaration(mut self) -> Self {
+        let name = match self.get_current() {
+            Token::Identifier(s) => s,
+            _ => self.error("Expect constant name."),
+        };
+        self.advance();
+
+        let declared = match self.scopes.last() {
+            Some(scope) => scope.consts.contains_key(&name),
+            None => self.consts.contains_key(&name),
+        };
+
+        if declared {
+            self.error(&format!("Constant '{name}' is already declared."));
+        }
+
+        /* Variables are resolved before constants, so the constant would never be read */
+        if self.resolves_variable(&name) {
+            self.error(&format!("Cannot declare constant '{name}', a variable with this name exists."));
+        }
+
+        let t = match self.match_token(Token::Colon) {
+            true => self.parse_var_type(),
+            false => Type::UnInit,
+        };
+
+        self.consume(Token::Equal, "Expect '=' after constant name.");
+        let value = self.constant_expression();
+        self.consume(Token::SemiColon, "Expect ';' after constant declaration.");
+
+        let value = Value { _type: parse_type(&value), value, modifier: Modifier::Const };
+
+        if !value.conforms_to(&t) {
+            self.error(&format!("Constant '{}' expects {} but got {}.", name, t, value._type));
+        }
+
+        match self.scopes.last_mut() {
+            Some(scope) => scope.consts.insert(name, value.value),
+            None => self.consts.insert(name, value.value),
+        };
+
+        self
+    }
+
+    /// Constant declared by an enclosing scope of current function or by an enclosing function.
+    ///
+    pub fn resolve_const(&self, name: &str) -> Option<Primitive> {
+        let scoped = self.scopes.iter().rev().find_map(|scope| scope.consts.get(name));
+
+        match scoped.or_else(|| self.consts.get(name)) {
+            Some(value) => Some(value.clone()),
+            None => self.up_context.as_ref()?.resolve_const(name),
+        }
+    }
+
+    /// Whether name is a local of current function or an enclosing one, or an already mentioned global.
+    ///
+    fn resolves_variable(&self, name: &str) -> bool {
+        if self.resolve_local(name).is_some() {
+            return true;
+        }
+
+        match &self.up_context {
+            Some(context) => context.resolves_variable(name),
+            None => self.slots.borrow().lookup(name).is_some(),
+        }
+    }
+
     fn get_current(&mut self) -> Token {
         std::mem::replace(&mut self.current, Token::Nil)
     }
@@ -457,7 +1119,7 @@ impl<R: std::io::Read> Parser<R> {
 
     /// Iterates over all parser scope's searching for local variable, returning it's (index, Mod)
     /// 
-    pub fn resolve_local(&self, var_name: &String) -> Option<Rc<RefCell<(usize, Modifier)>>> {
+    pub fn resolve_local(&self, var_name: &str) -> Option<Rc<RefCell<(usize, Modifier)>>> {
         let mut local = None;
 
         for scope in self.scopes.iter().rev() {
@@ -469,6 +1131,57 @@ impl<R: std::io::Read> Parser<R> {
         local
     }
 
+    /// Scopes count up to the one local variable is declared in.
+    ///
+    pub fn local_depth(&self, var_name: &str) -> Option<usize> {
+        self.scopes.iter().rposition(|scope| scope.get_local(var_name).is_some()).map(|idx| idx + 1)
+    }
+
+    /// Reference held by local variable, so reading the variable evaluates to it as well.
+    ///
+    pub fn held_borrow(&self, local_index: usize) -> Option<Borrow> {
+        self.scopes.iter().rev().find_map(|scope| scope.borrows.get(&local_index).cloned())
+    }
+
+    /// Reference to a local variable the expression just compiled evaluates to.
+    ///
+    /// Only tracks expressions ending with the reference itself, e.g: `&x` or a variable holding it.
+    ///
+    pub fn expression_borrow(&self) -> Option<Borrow> {
+        match &self.borrowed {
+            Some((borrow, end)) if *end == self.function.chunk.code.len() => Some(borrow.clone()),
+            _ => None,
+        }
+    }
+
+    /// Error if expression just compiled is a reference stored where it outlives the borrowed variable,
+    /// at `depth` scopes (0 for globals and values leaving the function).
+    ///
+    pub fn check_escape(&mut self, depth: usize, at: Position) {
+        let Some(borrow) = self.expression_borrow() else { return };
+
+        if depth < borrow.depth {
+            self.error(&format!(
+                "Reference to '{}' taken at {} escapes it's scope at {}.",
+                borrow.name, borrow.at, at
+            ));
+        }
+    }
+
+    /// Assign the reference expression just compiled evaluates to, if any, to local variable.
+    ///
+    pub fn hold_borrow(&mut self, var_name: &str, at: Position) {
+        let depth = self.local_depth(var_name).unwrap();
+        let local_index = self.scopes[depth - 1].get_local(var_name).unwrap().borrow().0;
+
+        self.check_escape(depth, at);
+
+        match self.expression_borrow() {
+            Some(borrow) => self.scopes[depth - 1].borrows.insert(local_index, borrow),
+            None => self.scopes[depth - 1].borrows.remove(&local_index),
+        };
+    }
+
     pub fn resolve_upvalue(&mut self, name: &String) -> Option<usize> {
         if self.up_context.is_none() { return None; };
 
@@ -479,16 +1192,11 @@ impl<R: std::io::Read> Parser<R> {
             .unwrap()
             .resolve_local(name);
 
-        if local.is_some() {
-            return Some(
-                    self
-                    .up_context
-                    .as_mut()
-                    .unwrap()
-                    .add_upvalue(local.unwrap().borrow().0, true)
-                );
+        if let Some(local) = local {
+            return Some(self.add_upvalue(local.borrow().0, true));
         };
 
+        /* Variable may be captured by enclosing function itself */
         let upvalue =
             self 
             .up_context
@@ -496,23 +1204,25 @@ impl<R: std::io::Read> Parser<R> {
             .unwrap()
             .resolve_upvalue(name);
 
-        if upvalue.is_some() {
-            return Some(self.add_upvalue(local.unwrap().borrow().0, false));
+        if let Some(upvalue) = upvalue {
+            return Some(self.add_upvalue(upvalue, false));
         }
 
         None
     }
 
+    /// Register captured variable on current function, returning it's index on function upvalues.
+    ///
     pub fn add_upvalue(&mut self, index: usize, is_local: bool) -> usize {
-        if self.upvalues.iter().find(
+        if let Some(position) = self.upvalues.iter().position(
             |up| up.index == index && up.is_local == is_local
-        ).is_some() { return index; }
+        ) { return position; }
 
         let upvalue = UpValue { index, is_local };
         self.upvalues.push(upvalue);
         self.function.upv_count += 1;
 
-        index
+        self.upvalues.len() - 1
     }
 
     /// Statement manager function
@@ -539,6 +1249,10 @@ impl<R: std::io::Read> Parser<R> {
             return self.while_statement();
         } else if self.match_token(Token::Switch) {
             return self.switch_statement();
+        } else if self.match_token(Token::Try) {
+            return self.try_statement();
+        } else if self.match_token(Token::Throw) {
+            return self.throw_statement();
         } else if self.check(Token::LeftBrace) {
             return self.declaration();
         } else {
@@ -603,7 +1317,7 @@ impl<R: std::io::Read> Parser<R> {
         /*
             This is the condition evaluation itself, this is where the loop begins, intructionally speaking xD
         */
-        let mut loop_start = self.function.chunk.code.len() - 1;
+        let mut loop_start = self.function.chunk.code.len();
         /*
             -1 is a fallback value, meaning the loop must not be patched, or better saying, the loop will not break.
         */
@@ -632,9 +1346,9 @@ impl<R: std::io::Read> Parser<R> {
             /* Set jump over body */
             let body_jump = self.emit_jump(OpCode::Jump(0));
             /* Execute increment - this is executed after body */
-            let increment_start = self.function.chunk.code.len() - 1;
+            let increment_start = self.function.chunk.code.len();
             /* Increment expression */
-            self = self.expression();
+            self = self.statement_expression();
 
             self.consume(Token::RightParen, "Expect ')' after for clauses.");
 
@@ -645,15 +1359,18 @@ impl<R: std::io::Read> Parser<R> {
                 Jump to the body.
                 After this jump, the self.emit_loop(loop_start) come back to evaluate the increment instruction.
             */
-            self.patch_jump(body_jump, OpCode::Jump(0));
+            self.patch_jump(body_jump);
         }
 
         self.consume(Token::LeftBrace, "Expect '{' start-of-block.");
+        /* Body locals are dropped on every iteration */
+        self.begin_scope();
         self = self.block();
+        self.end_scope();
         self.emit_loop(loop_start);
 
         if exit_jump != -1 {
-            self.patch_jump(exit_jump as usize, OpCode::JumpIfFalse(0));
+            self.patch_jump(exit_jump as usize);
             self.emit_byte(OpCode::Pop);
         }
 
@@ -688,16 +1405,16 @@ impl<R: std::io::Read> Parser<R> {
             Set correct calculated offset to earlier set then_jump.
             This is needed because jump doesn't know primarily how many instructions to jump
         */
-        _self.patch_jump(then_jump, OpCode::JumpIfFalse(0));
+        _self.patch_jump(then_jump);
         _self.emit_byte(OpCode::Pop);
 
         if _self.match_token(Token::Else) {
             let mut __self =Self::statement(_self);
-            __self.patch_jump(else_jump, OpCode::Jump(0));
+            __self.patch_jump(else_jump);
 
             return __self;
         }
-        _self.patch_jump(else_jump, OpCode::Jump(0));
+        _self.patch_jump(else_jump);
 
         _self
     }
@@ -708,19 +1425,51 @@ impl<R: std::io::Read> Parser<R> {
         }
 
         if self.match_token(Token::SemiColon) {
-            self.emit_return();
+            self.emit_byte(OpCode::Nil);
         } else {
+            let at = self.previous_start;
+
             self = self.expression();
+            self.check_escape(0, at);
             self.consume(Token::SemiColon, "Expect ; after return value.");
-            self.emit_byte(OpCode::Return);
         };
 
+        self.emit_try_return();
+
         self
     }
 
+    /// Return value on top of stack, or when inside a try statement, keep it in the statement hidden locals
+    /// and jump to it's finally block, which returns once it ran.
+    ///
+    fn emit_try_return(&mut self) {
+        let Some(_try) = self.tries.last() else {
+            self.emit_byte(OpCode::Return);
+            return;
+        };
+
+        let (depth, returning, value) = (_try.depth, _try.returning, _try.value);
+
+        /* Wrapped in a tuple, which keeps the value type while the local is untyped */
+        self.emit_byte(OpCode::Tuple(1));
+        self.emit_byte(OpCode::SetLocal(value, Modifier::Mut));
+        self.emit_byte(OpCode::True);
+        self.emit_byte(OpCode::SetLocal(returning, Modifier::Mut));
+
+        /* Locals declared inside try or catch, then the handler of either */
+        let local_count: usize = self.scopes[depth..].iter().map(|scope| scope.local_count).sum();
+        for _ in 0..local_count {
+            self.emit_byte(OpCode::Pop);
+        }
+        self.emit_byte(OpCode::PopHandler);
+
+        let jump = self.emit_jump(OpCode::Jump(0));
+        self.tries.last_mut().unwrap().jumps.push(jump);
+    }
+
     fn while_statement(mut self: Parser<R>) -> Self {
         /* The Bytecode index jump needs to go backward to restart loop */
-        let loop_start = self.function.chunk.code.len() - 1;
+        let loop_start = self.function.chunk.code.len();
 
         self.consume(Token::LeftParen, "Expect '(' after 'while'");
         self = self.expression();
@@ -731,7 +1480,7 @@ impl<R: std::io::Read> Parser<R> {
 
         self = self.statement();
         self.emit_loop(loop_start);
-        self.patch_jump(exit_jump, OpCode::JumpIfFalse(0));
+        self.patch_jump(exit_jump);
         self.emit_byte(OpCode::Pop);
         
         self
@@ -745,19 +1494,28 @@ impl<R: std::io::Read> Parser<R> {
         self.consume(Token::RightParen, "Expect ')' after expression.");
         self.consume(Token::LeftBrace, "Expect '{' start-of-block.");
 
+        /*
+            Switch value and branch result are kept as hidden locals, so case bodies can declare their own.
+            No DefineLocal is emitted as switch value may be shared with the switched variable.
+        */
+        let switch_value = String::from(" switch");
+        self.add_local(Modifier::Const, switch_value.clone());
+        let value_index = self.resolve_local(&switch_value).unwrap().borrow().0;
+
         self.consume(Token::Case, "Expected 'case' statement.");
         /* This gets switch value to be compared with branch value on every iteration */
-        self = self.expression();
+        let binding;
+        (self, binding) = self.case_condition(value_index);
+        self.add_local(Modifier::Const, String::from(" matched"));
         self.consume(Token::Arrow, "Expect '=>' after expression.");
-        self.emit_byte(OpCode::PartialEqual);
         let stmt_jump = self.emit_jump(OpCode::JumpIfFalse(0));
         /*
             Statements doesnt let dangling values on stack, so no pop is needed.
             Finally, the value available on top is going to be the expression() result one.
         */
         // Validate block();
-        self = self.statement();
-        self.patch_jump(stmt_jump, OpCode::JumpIfFalse(0));
+        self = self.case_body(value_index, binding);
+        self.patch_jump(stmt_jump);
 
         /*
             Executed by getting the original switch value, copying it and comparing it with the branch expression value.
@@ -770,16 +1528,16 @@ impl<R: std::io::Read> Parser<R> {
             self.emit_byte(OpCode::Pop);
 
             /* This gets switch value to be compared with branch value on every iteration */
-            self = self.expression();
+            let binding;
+            (self, binding) = self.case_condition(value_index);
             self.consume(Token::Arrow, "Expect '=>' after expression.");
-            self.emit_byte(OpCode::PartialEqual);
             let stmt_jump = self.emit_jump(OpCode::JumpIfFalse(0));
 
             // Validate block();
-            self = self.statement();
-            self.patch_jump(stmt_jump, OpCode::JumpIfFalse(0));
+            self = self.case_body(value_index, binding);
+            self.patch_jump(stmt_jump);
 
-            self.patch_jump(branch_jump, OpCode::JumpIfTrue(0));
+            self.patch_jump(branch_jump);
             /* On final of loop, the expression value of branch is still available, once the pop is on next iteration */
         }
 
@@ -791,23 +1549,197 @@ impl<R: std::io::Read> Parser<R> {
             self = self.statement();
         }
 
-        self.patch_jump(default_jump, OpCode::JumpIfTrue(0));
-        /* Pop default_jump conditional */
-        self.emit_byte(OpCode::Pop);
+        self.patch_jump(default_jump);
+
+        self.consume(Token::RightBrace, "Expect '}' on end-of-block.");
+        /* Pop branch conditional and original switch value */
+        self.end_scope();
+
+        self
+    }
+
+    /// Emit case check, leaving if it matched on top of stack.
+    ///
+    /// Ok(name) / Err(name) match against Result variant, returning name to be bound to the inner value.
+    ///
+    fn case_condition(mut self: Parser<R>, value_index: usize) -> (Parser<R>, Option<String>) {
+        if self.check(Token::Ok) || self.check(Token::Err) {
+            let ok = self.match_token(Token::Ok) || !self.match_token(Token::Err);
+            self.consume(Token::LeftParen, "Expect '(' after Result variant.");
+
+            if let Token::Identifier(name) = self.current.clone() {
+                self.advance();
+                self.consume(Token::RightParen, "Expect ')' after Result binding.");
+
+                self.emit_byte(OpCode::GetLocal(value_index));
+                self.emit_byte(OpCode::MatchResult(ok));
+
+                return (self, Some(name));
+            }
+
+            /* Not a binding, compare against Result value itself */
+            self = self.expression();
+            self.consume(Token::RightParen, "Expect ')' after Result value.");
+            self.emit_byte(if ok { OpCode::Ok } else { OpCode::Err });
+        } else {
+            self = self.expression();
+        }
+
+        self.emit_byte(OpCode::PartialEqual);
+
+        (self, None)
+    }
+
+    /// Compile case statement, binding Result inner value to it's name if a variant was matched.
+    ///
+    fn case_body(mut self: Parser<R>, value_index: usize, binding: Option<String>) -> Parser<R> {
+        let Some(name) = binding else {
+            return self.statement();
+        };
+
+        self.begin_scope();
+        self.emit_byte(OpCode::GetLocal(value_index));
+        self.emit_byte(OpCode::UnwrapResult);
+        self.add_local(Modifier::Const, name.clone());
+        self.mark_initialized(name, Type::UnInit);
+
+        self = self.statement();
+        self.end_scope();
+
+        self
+    }
+
+    /// Compile try { } catch (e) { } finally { }, where at least one of catch or finally must be present.
+    ///
+    /// Values thrown and not handled by catch are kept in a hidden local, so finally runs before they are re-thrown.
+    /// Returns inside try or catch are kept the same way, see `emit_try_return`.
+    ///
+    fn try_statement(mut self: Parser<R>) -> Parser<R> {
+        self.begin_scope();
+
+        /* Hidden local holding the pending error, nil when there's none */
+        let pending_index = self.hidden_local(" pending", OpCode::Nil, Type::Optional(Rc::new(Type::UnInit)));
+        let returning = self.hidden_local(" returning", OpCode::False, Type::Bool);
+        let value = self.hidden_local(" returned", OpCode::Nil, Type::Optional(Rc::new(Type::UnInit)));
+
+        self.tries.push(Try { depth: self.scopes.len(), returning, value, jumps: vec![] });
+
+        let try_handler = self.emit_jump(OpCode::PushHandler(0));
+
+        self.consume(Token::LeftBrace, "Expect '{' after 'try'.");
+        self.begin_scope();
+        self = self.block();
+        self.end_scope();
+
+        self.emit_byte(OpCode::PopHandler);
+        let try_jump = self.emit_jump(OpCode::Jump(0));
+
+        /* Thrown value is on top of stack from here */
+        self.patch_jump(try_handler);
+
+        let mut catch_jump = None;
+        let has_catch = self.match_token(Token::Catch);
+
+        if has_catch {
+            /* Errors thrown inside catch also wait for finally */
+            let catch_handler = self.emit_jump(OpCode::PushHandler(0));
+
+            self.consume(Token::LeftParen, "Expect '(' after 'catch'.");
+            let name = match self.get_current() {
+                Token::Identifier(s) => s,
+                _ => self.error("Expect catch binding name."),
+            };
+            self.advance();
+            self.consume(Token::RightParen, "Expect ')' after catch binding.");
+
+            self.begin_scope();
+            self.add_local(Modifier::Const, name.clone());
+            self.mark_initialized(name, Type::UnInit);
+
+            self.consume(Token::LeftBrace, "Expect '{' after catch binding.");
+            self = self.block();
+            self.end_scope();
+
+            self.emit_byte(OpCode::PopHandler);
+            catch_jump = Some(self.emit_jump(OpCode::Jump(0)));
+
+            /* Stack here is: catch binding, thrown value */
+            self.patch_jump(catch_handler);
+            self.emit_byte(OpCode::SetLocal(pending_index, Modifier::Mut));
+            self.emit_byte(OpCode::Pop);
+        } else {
+            self.emit_byte(OpCode::SetLocal(pending_index, Modifier::Mut));
+        }
+
+        self.patch_jump(try_jump);
+        if let Some(catch_jump) = catch_jump {
+            self.patch_jump(catch_jump);
+        }
+
+        /* Returns inside finally leave the function, or reach the enclosing try statement */
+        let _try = self.tries.pop().unwrap();
+        for jump in _try.jumps.iter() {
+            self.patch_jump(*jump);
+        }
+
+        if self.match_token(Token::Finally) {
+            self.consume(Token::LeftBrace, "Expect '{' after 'finally'.");
+            self.begin_scope();
+            self = self.block();
+            self.end_scope();
+        } else if !has_catch {
+            self.error("Expect 'catch' or 'finally' after try block.");
+        }
 
-        /* As original switch value are available, pop */
+        /* Re-throw pending error */
+        self.emit_byte(OpCode::GetLocal(pending_index));
+        let skip_throw = self.emit_jump(OpCode::JumpIfNil(0));
+        self.emit_byte(OpCode::Throw);
+        self.patch_jump(skip_throw);
         self.emit_byte(OpCode::Pop);
 
-        self.consume(Token::RightBrace, "Expect '}' on end-of-block.");
+        /* Resume pending return */
+        if !_try.jumps.is_empty() {
+            self.emit_byte(OpCode::GetLocal(returning));
+            let skip_return = self.emit_jump(OpCode::JumpIfFalse(0));
+            self.emit_byte(OpCode::Pop);
+            self.emit_byte(OpCode::GetLocal(value));
+            self.emit_constant(Value { value: Primitive::Int(0), _type: Type::Int, modifier: Modifier::Unassigned });
+            self.emit_byte(OpCode::Access);
+            self.emit_try_return();
+            self.patch_jump(skip_return);
+            self.emit_byte(OpCode::Pop);
+        }
+
         self.end_scope();
 
         self
     }
 
+    /// Declare a local the user can't name, initialized by given opcode, returning it's index.
+    ///
+    fn hidden_local(&mut self, name: &str, init: OpCode, t: Type) -> usize {
+        self.emit_byte(init);
+        self.add_local(Modifier::Mut, name.to_string());
+        self.mark_initialized(name.to_string(), t);
+
+        self.resolve_local(name).unwrap().borrow().0
+    }
+
+    /// Emit: OpCode::Throw
+    ///
+    fn throw_statement(mut self: Parser<R>) -> Parser<R> {
+        self = self.expression();
+        self.consume(Token::SemiColon, "Expect ';' after thrown value.");
+        self.emit_byte(OpCode::Throw);
+
+        self
+    }
+
     /// Evaluate expression and consume ';' token.
     ///
     pub fn expression_statement(mut self) -> Self {
-        self = self.expression();
+        self = self.statement_expression();
         self.consume(Token::SemiColon, "Expect ';' after expression.");
 
         self
@@ -837,6 +1769,46 @@ impl<R: std::io::Read> Parser<R> {
         true
     }
 
+    /// Match compound assignment Token (+=, -=, *=, /=, %=, ++, --) if target can be assigned, returning it's operator.
+    ///
+    pub fn match_compound_assign(&mut self, can_assign: bool) -> Option<Token> {
+        let operator = match self.current {
+            _ if !can_assign => return None,
+            Token::PlusEqual | Token::PlusPlus => Token::Plus,
+            Token::MinusEqual | Token::MinusMinus => Token::Minus,
+            Token::StarEqual => Token::Star,
+            Token::SlashEqual => Token::Slash,
+            Token::PercentEqual => Token::Percent,
+            _ => return None,
+        };
+
+        self.assignment();
+        self.advance();
+
+        Some(operator)
+    }
+
+    /// Right operand of the compound assignment just matched, which is 1 for increments and decrements: x++ -> x += 1
+    ///
+    pub fn compound_operand(mut self) -> Self {
+        match self.previous {
+            Token::PlusPlus | Token::MinusMinus => {
+                self.emit_constant(Value { value: Primitive::Int(1), _type: Type::Int, modifier: Modifier::Const });
+                self.end_increment();
+                self
+            }
+            _ => self.expression(),
+        }
+    }
+
+    /// Increments leave no value, so they can't be an operand: x++ + 1
+    ///
+    pub fn end_increment(&mut self) {
+        if get_rule::<R>(&self.current).precedence != Precedence::None {
+            self.error("Increment can't be used as an operand.");
+        }
+    }
+
     /// Compare current Token with param Token.
     ///
     pub fn check(&self, token: Token) -> bool {
@@ -847,6 +1819,7 @@ impl<R: std::io::Read> Parser<R> {
     ///
     pub fn advance(&mut self) {
         self.previous = self.get_current();
+        self.previous_start = self.lexer.as_ref().unwrap().start;
 
         self.current = self.lexer.as_mut().unwrap().next();
 
@@ -867,6 +1840,21 @@ impl<R: std::io::Read> Parser<R> {
         self.parse_precedence(Precedence::Assignment)
     }
 
+    /// Expression which value is not used, so it can be an assignment: x += 1;
+    ///
+    pub fn statement_expression(mut self) -> Parser<R> {
+        self.statement = true;
+        self.expression()
+    }
+
+    /// Assignments leave no value, so they can't be part of another expression: print x = 1;
+    ///
+    pub fn assignment(&mut self) {
+        if !self.assigns {
+            self.error("Assignment can only be used as a statement.");
+        }
+    }
+
     /// This is the Ruler core itself, it orchestrate the expressions' values / order.
     ///
     pub fn parse_precedence(mut self, precedence: Precedence) -> Parser<R> {
@@ -874,8 +1862,18 @@ impl<R: std::io::Read> Parser<R> {
         println!("\n parsing precedence for {:?}", &self.previous);
         self.advance();
 
+        self.parse_from_previous(precedence)
+    }
+
+    /// Same as parse_precedence, but the expression first Token was already consumed into previous.
+    ///
+    pub fn parse_from_previous(mut self, precedence: Precedence) -> Parser<R> {
         let prefix_rule = get_rule(&self.previous).prefix;
 
+        /* Only the outermost expression of a statement can assign, not the nested ones: x = f(y = 1); */
+        let statement = std::mem::take(&mut self.statement);
+        let outer = std::mem::replace(&mut self.assigns, statement);
+
         let can_assign = precedence <= Precedence::Assignment;
         let mut _self = prefix_rule(self, can_assign);
 
@@ -896,6 +1894,7 @@ impl<R: std::io::Read> Parser<R> {
             _self = infix_rule(_self, can_assign)
         }
 
+        _self.assigns = outer;
         _self
     }
 
@@ -928,39 +1927,53 @@ impl<R: std::io::Read> Parser<R> {
         const_index
     }
 
-    /// Emit jump instruction and return it's index on chunk.code
+    /// Emit string literal bytes as an interned String constant.
+    ///
+    /// Emit: OpCode::Constant
+    ///
+    pub fn emit_string(&mut self, str: Vec<u8>) {
+        let index = self
+            .function
+            .chunk
+            .write_constant(Primitive::String(String::from_utf8(str).unwrap().into()));
+
+        self.emit_byte(OpCode::Constant(index));
+    }
+
+    /// Emit jump instruction and return it's position on chunk.code
     ///
     pub fn emit_jump(&mut self, instruction: OpCode) -> usize {
+        let position = self.function.chunk.code.len();
+
         /* Instruction */
         self.emit_byte(instruction);
 
-        /* Return instruction count */
-        return self.function.chunk.code.len() - 1;
+        return position;
     }
 
-    /// Loop is a jump * -1, it goes backward to where the flag was set (loop_start which generally are self.chunk.code.len() - 1)
+    /// Loop is a jump * -1, it goes backward to where the flag was set (loop_start which generally are self.chunk.code.len())
     ///
     fn emit_loop(&mut self, loop_start: usize) {
-        self.emit_byte(OpCode::Loop(
-            self.function.chunk.code.len() - 1 - loop_start,
-        ));
+        /* Offset is taken from the end of Loop instruction itself */
+        let offset = self.function.chunk.code.len() + JUMP_SIZE - loop_start;
+
+        if offset > u16::MAX as usize {
+            self.error("Loop body too large.")
+        }
+
+        self.emit_byte(OpCode::Loop(offset));
     }
 
     /// Calculate jump after evaluate conditional branch and set it to jump instruction.
     ///
-    fn patch_jump(&mut self, offset: usize, instruction: OpCode) {
-        let jump = self.function.chunk.code.len() - offset;
+    fn patch_jump(&mut self, offset: usize) {
+        let jump = self.function.chunk.code.len() - offset - JUMP_SIZE;
 
-        if jump > usize::MAX {
+        if jump > u16::MAX as usize {
             self.error("Max jump bytes reached.")
         }
 
-        match instruction {
-            OpCode::JumpIfTrue(_) => self.function.chunk.code[offset] = OpCode::JumpIfTrue(jump),
-            OpCode::JumpIfFalse(_) => self.function.chunk.code[offset] = OpCode::JumpIfFalse(jump),
-            OpCode::Jump(_) => self.function.chunk.code[offset] = OpCode::Jump(jump),
-            _ => self.error("Invalid jump intruction."),
-        }
+        self.function.chunk.patch_jump(offset, jump as u16);
     }
 
     fn emit_return(&mut self) {
@@ -976,7 +1989,7 @@ impl<R: std::io::Read> Parser<R> {
         if !self.had_error {
             // STUB
             #[cfg(feature = "debug")]
-            disassemble_chunk(&self.function.chunk, self.function.name.to_string());
+            disassemble_chunk(&self.function.chunk, self.function.name.to_string(), &self.slots.borrow());
         }
 
         std::mem::replace(&mut self.function, Function::default())
@@ -986,13 +1999,12 @@ impl<R: std::io::Read> Parser<R> {
     ///
     pub fn error(&mut self, msg: &str) -> ! {
         let token = &self.current;
-        let mut curr_line = self.lexer.as_ref().unwrap().line;
-        if curr_line == 0 { curr_line = 1 };
+        let start = self.lexer.as_ref().unwrap().start;
 
         let complement = match token {
             Token::Eof => String::from(" at end."),
-            Token::Error(s) => format!("{} at line {}", s, curr_line),
-            _ => format!("at line {}", curr_line)
+            Token::Error(s) => format!("{} at {}", s, start),
+            _ => format!("at {}", start)
         };
 
         panic!(
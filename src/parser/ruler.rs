@@ -1,10 +1,13 @@
 use crate::parser::Parser;
+use crate::primitives::functions::FunctionType;
 use crate::primitives::primitive::{Primitive};
 use crate::primitives::types::{Modifier, Type};
 use crate::primitives::value::Value;
+use crate::utils::parse_type;
 use crate::vm::chunk::OpCode;
 
 use super::lexer::Token;
+use super::scope::{Borrow, Place};
 
 #[derive(Debug, PartialEq, PartialOrd)]
 /// Defines lower to higher operation precedence order.
@@ -12,6 +15,7 @@ use super::lexer::Token;
 pub enum Precedence {
     None,
     Assignment, // =
+    Coalesce,   // ??
     Or,         // or
     And,        // and
     Equality,   // == !=
@@ -27,7 +31,8 @@ impl Precedence {
     fn increment(&mut self) {
         *self = match self {
             Self::None => Self::None,
-            Self::Assignment => Self::Or,
+            Self::Assignment => Self::Coalesce,
+            Self::Coalesce => Self::Or,
             Self::Or => Self::And,
             Self::And => Self::Equality,
             Self::Equality => Self::Comparison,
@@ -60,9 +65,45 @@ impl<R: std::io::Read> ParseRule<R> {
 
     /// Handle "()" precedence operator consuming ")" on end.
     ///
+    /// A comma after the first expression makes it a tuple: (a, b), single item ones keep it trailing: (a,)
+    ///
     fn grouping(mut parser: Parser<R>, _can_assign: bool) -> Parser<R> {
+        /* Empty tuple: () */
+        if parser.match_token(Token::RightParen) {
+            parser.emit_byte(OpCode::Tuple(0));
+            return parser;
+        }
+
         parser = parser.expression();
-        parser.consume(Token::RightParen, "expected ')' after expression.");
+
+        if !parser.match_token(Token::Comma) {
+            parser.consume(Token::RightParen, "expected ')' after expression.");
+            return parser;
+        }
+
+        let mut size = 1;
+        /* Innermost borrowed local among items, so the tuple can't outlive it either */
+        let mut borrowed = parser.expression_borrow();
+
+        while !parser.check(Token::RightParen) {
+            parser = parser.expression();
+            size += 1;
+
+            if let Some(borrow) = parser.expression_borrow() {
+                if borrowed.as_ref().is_none_or(|b| borrow.depth > b.depth) {
+                    borrowed = Some(borrow);
+                }
+            }
+
+            if !parser.match_token(Token::Comma) { break; }
+        }
+        parser.consume(Token::RightParen, "Expect ')' after tuple items.");
+
+        parser.emit_byte(OpCode::Tuple(size));
+
+        if let Some(borrow) = borrowed {
+            parser.borrowed = Some((borrow, parser.function.chunk.code.len()));
+        }
 
         parser
     }
@@ -106,6 +147,66 @@ impl<R: std::io::Read> ParseRule<R> {
         parser
     }
 
+    /// Prefix increment and decrement: ++x, --p.f
+    ///
+    /// Like assignments they leave no value and can only be a statement, so it's the same as x++ or x += 1.
+    ///
+    fn increment(mut parser: Parser<R>, can_assign: bool) -> Parser<R> {
+        let operator = match parser.get_previous() {
+            Token::PlusPlus => Token::Plus,
+            _ => Token::Minus,
+        };
+
+        if !can_assign {
+            parser.error("Invalid assignment target.");
+        }
+        parser.assignment();
+
+        /* Target is compiled as read, then re-emitted to be written */
+        let target = parser.current.clone();
+        parser = parser.parse_precedence(Precedence::Call);
+
+        let Some(mut place) = parser.place.take().filter(|place| place.end == parser.function.chunk.code.len()) else {
+            match target {
+                Token::Identifier(name) if parser.resolve_const(&name).is_some() => {
+                    parser.error(&format!("Cannot assign to constant '{name}'."))
+                }
+                _ => parser.error("Invalid assignment target."),
+            }
+        };
+        let one = Value { value: Primitive::Int(1), _type: Type::Int, modifier: Modifier::Const };
+
+        let Some(field) = place.fields.pop() else {
+            parser.emit_constant(one);
+            Self::arithmetic(&mut parser, &operator);
+            parser.emit_byte(place.set_op);
+            parser.end_increment();
+
+            return parser;
+        };
+
+        /* Instance is read once, as in p.f += 1 */
+        parser.function.chunk.code.truncate(place.start);
+        for parent in place.fields.iter() {
+            parser.emit_byte(OpCode::Dup);
+            parser.emit_byte(OpCode::Constant(*parent));
+            parser.emit_byte(OpCode::Access);
+        }
+
+        parser.emit_byte(OpCode::Dup);
+        parser.emit_byte(OpCode::Constant(field));
+        parser.emit_byte(OpCode::Access);
+
+        parser.emit_constant(one);
+        Self::arithmetic(&mut parser, &operator);
+
+        parser.emit_byte(OpCode::SetField(field));
+        Self::close_place(&mut parser, place);
+        parser.end_increment();
+
+        parser
+    }
+
     /// Parse math operators recursivelly until all operations are evaluated in correct order.
     ///
     fn binary(mut parser: Parser<R>, _can_assign: bool) -> Parser<R> {
@@ -118,13 +219,9 @@ impl<R: std::io::Read> ParseRule<R> {
 
         if let Some(token) = Some(operator_type) {
             match token {
-                Token::Plus => parser.emit_byte(OpCode::Add),
-                Token::Minus => {
-                    parser.emit_byte(OpCode::Negate);
-                    parser.emit_byte(OpCode::Add)
+                Token::Plus | Token::Minus | Token::Star | Token::Slash | Token::Percent => {
+                    Self::arithmetic(&mut parser, token)
                 }
-                Token::Star => parser.emit_byte(OpCode::Multiply),
-                Token::Slash => parser.emit_byte(OpCode::Divide),
                 Token::BangEqual => {
                     parser.emit_byte(OpCode::Equal);
                     parser.emit_byte(OpCode::Not);
@@ -147,21 +244,36 @@ impl<R: std::io::Read> ParseRule<R> {
         parser
     }
 
+    /// Emit arithmetic operator, shared by binary expressions and compound assignments.
+    ///
+    fn arithmetic(parser: &mut Parser<R>, operator: &Token) {
+        match operator {
+            Token::Plus => parser.emit_byte(OpCode::Add),
+            Token::Minus => parser.emit_byte(OpCode::Subtract),
+            Token::Star => parser.emit_byte(OpCode::Multiply),
+            Token::Slash => parser.emit_byte(OpCode::Divide),
+            Token::Percent => parser.emit_byte(OpCode::Modulo),
+            _ => panic!("invalid arithmetic operator."),
+        }
+    }
+
     /// Emit bool values Bytecodes.
     ///
     fn literal(mut parser: Parser<R>, _can_assign: bool) -> Parser<R> {
         match &parser.previous {
             Token::True => parser.emit_byte(OpCode::True),
             Token::False => parser.emit_byte(OpCode::False),
+            Token::Nil => parser.emit_byte(OpCode::Nil),
             _ => panic!("invalid literal operation."),
         }
 
         parser
     }
 
-    // TODO Set string interning model
     /// Emit String to Contants.
     ///
+    /// Literals are interned, so equal strings share one object.
+    ///
     /// Emit: Constant
     ///
     fn string(mut parser: Parser<R>, _can_assign: bool) -> Parser<R> {
@@ -170,43 +282,92 @@ impl<R: std::io::Read> ParseRule<R> {
             _ => panic!("Invalid string value"),
         };
 
-        let index = parser
-            .function
-            .chunk
-            .write_constant(Primitive::String(String::from_utf8(str).unwrap()));
-        parser.emit_byte(OpCode::Constant(index));
+        parser.emit_string(str);
+
+        parser
+    }
+
+    /// Compile interpolated string as the concatenation of it's parts and stringified expressions.
+    ///
+    /// Emit: Constant, (expression, Stringify, Add, Constant, Add)+
+    ///
+    fn interpolation(mut parser: Parser<R>, _can_assign: bool) -> Parser<R> {
+        let Token::Interpolation(str) = parser.get_previous() else {
+            panic!("Invalid interpolation value")
+        };
+
+        parser.emit_string(str);
+
+        loop {
+            parser = parser.expression();
+            parser.emit_byte(OpCode::Stringify);
+            parser.emit_byte(OpCode::Add);
+
+            parser.advance();
+
+            let (str, ended) = match parser.get_previous() {
+                Token::Interpolation(str) => (str, false),
+                Token::String(str) => (str, true),
+                _ => parser.error("Expect '}' after interpolated expression."),
+            };
+
+            parser.emit_string(str);
+            parser.emit_byte(OpCode::Add);
+
+            if ended { break; }
+        }
 
         parser
     }
 
-    /// & -> Reference
+    /// & -> Reference, &mut -> Mutable reference
     /// -> Get current token (Value to-be-parsed)
     /// -> Emit bytecode which set referenced named variable to the stack
     /// -> Ref must reference the value in the stack itself
     ///
     fn reference(mut parser: Parser<R>, _can_assign: bool) -> Parser<R> {
+        let at = parser.previous_start;
+        let modifier = match parser.match_token(Token::Modifier) {
+            true => Modifier::Mut,
+            false => Modifier::Const,
+        };
+
         parser.advance();
         let name = match parser.get_previous() {
             Token::Identifier(s) => s,
-            _ => panic!("Invalid reference."),
+            _ => parser.error("Expect variable name after '&'."),
         };
 
-        match parser.scopes.len() {
-            /* Global */
-            0 => {
-                let var_index = parser.identifier_constant(name);
-                parser.emit_byte(OpCode::SetRefGlobal(var_index.unwrap()));
-            }
-            _ => {
-                let var_index = parser
-                    .scopes
-                    .last()
-                    .unwrap()
-                    .get_local(&name)
-                    .expect("Invalid variable name: {name}");
-
-                parser.emit_byte(OpCode::SetRefLocal(var_index.borrow().0));
-            }
+        /* Locals of every enclosing scope, otherwise a global */
+        if let Some(local) = parser.resolve_local(&name) {
+            let var_index = local.borrow().0;
+            let borrow = Borrow { depth: parser.local_depth(&name).unwrap(), name, at };
+
+            parser.emit_byte(OpCode::SetRefLocal(var_index, modifier));
+            parser.borrowed = Some((borrow, parser.function.chunk.code.len()));
+        } else if parser.resolve_upvalue(&name).is_some() {
+            parser.error("Cannot reference a captured variable.");
+        } else if parser.resolve_const(&name).is_some() {
+            parser.error(&format!("Cannot reference constant '{name}'."));
+        } else {
+            let slot = parser.global_slot(name);
+            parser.emit_byte(OpCode::SetRefGlobal(slot.unwrap(), modifier));
+        }
+
+        parser
+    }
+
+    /// * -> Dereference, writing through the reference if followed by =.
+    ///
+    fn deref(mut parser: Parser<R>, can_assign: bool) -> Parser<R> {
+        parser = parser.parse_precedence(Precedence::Unary);
+
+        if can_assign && parser.match_token(Token::Equal) {
+            parser.assignment();
+            parser = parser.expression();
+            parser.emit_byte(OpCode::SetDeref);
+        } else {
+            parser.emit_byte(OpCode::Deref);
         }
 
         parser
@@ -216,6 +377,13 @@ impl<R: std::io::Read> ParseRule<R> {
         Self::named_variable(parser, can_assign)
     }
 
+    /// Receiver of a method, which is it's first parameter: fn area(this) {}
+    ///
+    fn this(mut parser: Parser<R>, can_assign: bool) -> Parser<R> {
+        parser.previous = Token::Identifier("this".to_string());
+        Self::named_variable(parser, can_assign)
+    }
+
     /// Distinguish between re-assign and get variable already set value as well as local and global variables.
     ///
     /// Emit: (Local set / get) or (Global set / get).
@@ -227,6 +395,9 @@ impl<R: std::io::Read> ParseRule<R> {
             Token::Identifier(s) => s,
             _ => panic!("Could not get named_var"),
         };
+        let at = parser.previous_start;
+        /* Local variable holding a reference to another local */
+        let mut local_index = None;
 
         let scopes = &mut parser.scopes;
 
@@ -240,37 +411,106 @@ impl<R: std::io::Read> ParseRule<R> {
 
                 get_op = OpCode::GetLocal(local.borrow().0);
                 set_op = OpCode::SetLocal(local.borrow().0, local.borrow().1);
+                local_index = Some(local.borrow().0);
             } else if let Some(up_idx) = parser.resolve_upvalue(&var_name) {
                 get_op = OpCode::GetUpValue(up_idx);
                 set_op = OpCode::SetUpValue(up_idx);
+            } else if let Some(value) = parser.resolve_const(&var_name) {
+                return Self::constant(parser, var_name, value, can_assign);
             } else {
-                let var_index = parser.identifier_constant(var_name);
+                let slot = parser.global_slot(var_name.clone());
 
-                get_op = OpCode::GetGlobal(var_index.unwrap());
-                set_op = OpCode::SetGlobal(var_index.unwrap());
+                get_op = OpCode::GetGlobal(slot.unwrap());
+                set_op = OpCode::SetGlobal(slot.unwrap());
             }
         /* UpValues handling */
         } else if let Some(up_idx) = parser.resolve_upvalue(&var_name) {
             get_op = OpCode::GetUpValue(up_idx);
             set_op = OpCode::SetUpValue(up_idx);
+        } else if let Some(value) = parser.resolve_const(&var_name) {
+            return Self::constant(parser, var_name, value, can_assign);
         /* Global */
         } else {
-            let var_index = parser.identifier_constant(var_name).unwrap();
+            let slot = parser.global_slot(var_name.clone()).unwrap();
 
-            get_op = OpCode::GetGlobal(var_index);
-            set_op = OpCode::SetGlobal(var_index);
+            get_op = OpCode::GetGlobal(slot);
+            set_op = OpCode::SetGlobal(slot);
         }
 
         if can_assign && parser.match_token(Token::Equal) {
+            parser.assignment();
             parser = parser.expression();
+
+            match local_index {
+                Some(_) => parser.hold_borrow(&var_name, at),
+                None => parser.check_escape(0, at),
+            }
+
+            parser.emit_byte(set_op);
+        } else if let Some(operator) = parser.match_compound_assign(can_assign) {
+            /* x op= v -> x = x op v */
+            parser.emit_byte(get_op);
+            parser = parser.compound_operand();
+            Self::arithmetic(&mut parser, &operator);
+
+            if local_index.is_some() {
+                parser.hold_borrow(&var_name, at);
+            }
+
             parser.emit_byte(set_op);
+        } else if parser.match_token(Token::ColonColon) {
+            /* Explicit type arguments: first::<Int>(x), Pair::<Int, String> { .. } */
+            parser.emit_byte(get_op);
+            parser = Self::type_args(parser);
         } else {
             parser.emit_byte(get_op);
+            let end = parser.function.chunk.code.len();
+            parser.place = Some(Place { set_op, start: end, fields: vec![], end });
+
+            if let Some(borrow) = local_index.and_then(|idx| parser.held_borrow(idx)) {
+                parser.borrowed = Some((borrow, parser.function.chunk.code.len()));
+            }
         }
 
         parser
     }
 
+    /// Parse type arguments of a generic call or struct literal, which must follow them.
+    ///
+    fn type_args(mut parser: Parser<R>) -> Parser<R> {
+        parser.consume(Token::Less, "Expect '<' after '::'.");
+
+        let mut types = vec![];
+        loop {
+            types.push(parser.parse_var_type());
+
+            if !parser.match_token(Token::Comma) { break; }
+        }
+        parser.consume(Token::Greater, "Expect '>' after type arguments.");
+
+        let type_index = parser.function.chunk.write_type(Type::Tuple(Some(types.into())));
+
+        if parser.match_token(Token::LeftParen) {
+            return Self::call_with(parser, Some(type_index));
+        }
+
+        parser.consume(Token::LeftBrace, "Expect '(' or '{' after type arguments.");
+
+        Self::instance_with(parser, Some(type_index))
+    }
+
+    /// Emit the folded value of a constant, which can't be assigned.
+    ///
+    fn constant(mut parser: Parser<R>, name: String, value: Primitive, can_assign: bool) -> Parser<R> {
+        if (can_assign && parser.check(Token::Equal)) || parser.match_compound_assign(can_assign).is_some() {
+            parser.error(&format!("Cannot assign to constant '{name}'."));
+        }
+
+        parser.emit_constant(Value { _type: parse_type(&value), value, modifier: Modifier::Const });
+
+        parser
+    }
+
     /// Jump if first condition of expression is false, verifying the second for a possible jump.
     ///
     fn and_(mut parser: Parser<R>, _can_assign: bool) -> Parser<R> {
@@ -279,7 +519,7 @@ impl<R: std::io::Read> ParseRule<R> {
         parser.emit_byte(OpCode::Pop);
         parser = parser.parse_precedence(Precedence::And);
 
-        parser.patch_jump(end_jump, OpCode::JumpIfFalse(0));
+        parser.patch_jump(end_jump);
 
         parser
     }
@@ -290,30 +530,123 @@ impl<R: std::io::Read> ParseRule<R> {
         let else_jump = parser.emit_jump(OpCode::JumpIfFalse(0));
         let end_jump = parser.emit_jump(OpCode::Jump(0));
 
-        parser.patch_jump(else_jump, OpCode::JumpIfFalse(0));
+        parser.patch_jump(else_jump);
         parser.emit_byte(OpCode::Pop);
 
         parser = parser.parse_precedence(Precedence::Or);
-        parser.patch_jump(end_jump, OpCode::Jump(0));
+        parser.patch_jump(end_jump);
+
+        parser
+    }
+
+    /// Null-coalescing: keep left value if it's not nil, otherwise evaluate the right one.
+    ///
+    fn coalesce(mut parser: Parser<R>, _can_assign: bool) -> Parser<R> {
+        let end_jump = parser.emit_jump(OpCode::JumpIfNotNil(0));
+
+        parser.emit_byte(OpCode::Pop);
+        parser = parser.parse_precedence(Precedence::Or);
+
+        parser.patch_jump(end_jump);
+
+        parser
+    }
+
+    /// Build Ok(v) / Err(e) values.
+    ///
+    fn result(mut parser: Parser<R>, _can_assign: bool) -> Parser<R> {
+        let variant = match parser.previous {
+            Token::Ok => OpCode::Ok,
+            _ => OpCode::Err,
+        };
+
+        parser.consume(Token::LeftParen, "Expect '(' after Result variant.");
+        parser = parser.expression();
+        parser.consume(Token::RightParen, "Expect ')' after Result value.");
+
+        parser.emit_byte(variant);
+
+        parser
+    }
+
+    /// Postfix ?: unwrap Ok value or return Err from enclosing function.
+    ///
+    fn propagate(mut parser: Parser<R>, _can_assign: bool) -> Parser<R> {
+        if parser.function_type == FunctionType::Script {
+            parser.error("Can't use '?' on top-level code.");
+        }
+
+        parser.emit_byte(OpCode::Propagate);
+
+        parser
+    }
+
+    /// Optional field access, skip the access leaving nil on stack if the accessed value is nil.
+    ///
+    fn optional_dot(mut parser: Parser<R>, can_assign: bool) -> Parser<R> {
+        let nil_jump = parser.emit_jump(OpCode::JumpIfNil(0));
+
+        parser = Self::dot(parser, can_assign);
+        parser.patch_jump(nil_jump);
 
         parser
     }
 
     /// Get argument count by evaluating expression on function arguments.
     ///
+    /// Emit: Call or CallNamed if any argument is named.
+    ///
     fn call(parser: Parser<R>, _can_assign: bool) -> Parser<R> {
-        let (arg_count, mut _parser) = Self::arg_list(parser);
-        _parser.emit_byte(OpCode::Call(arg_count));
+        Self::call_with(parser, None)
+    }
+
+    /// Emit call, preceded by TypeArgs if it has explicit type arguments.
+    ///
+    fn call_with(parser: Parser<R>, type_args: Option<usize>) -> Parser<R> {
+        let (arg_count, names, mut _parser) = Self::arg_list(parser);
+
+        if let Some(type_index) = type_args {
+            _parser.emit_byte(OpCode::TypeArgs(type_index));
+        }
+
+        if names.is_empty() {
+            _parser.emit_byte(OpCode::Call(arg_count));
+        } else {
+            let names_index = _parser.names_constant(names);
+            _parser.emit_byte(OpCode::CallNamed(arg_count, names_index));
+        }
 
         _parser
     }
 
-    fn arg_list(mut parser: Parser<R>) -> (usize, Parser<R>) {
+    /// Parse positional arguments followed by named ones (name: value), returning the names of the latter.
+    ///
+    fn arg_list(mut parser: Parser<R>) -> (usize, Vec<String>, Parser<R>) {
         let mut arg_count = 0;
+        let mut names: Vec<String> = vec![];
 
         if !parser.check(Token::RightParen) {
             loop {
-                parser = parser.expression();
+                let named_count = names.len();
+
+                if let Token::Identifier(name) = parser.current.clone() {
+                    parser.advance();
+
+                    if parser.match_token(Token::Colon) {
+                        names.push(name);
+                        parser = parser.expression();
+                    } else {
+                        /* Not a named argument, identifier starts a common expression */
+                        parser = parser.parse_from_previous(Precedence::Assignment);
+                    }
+                } else {
+                    parser = parser.expression();
+                }
+
+                if named_count > 0 && named_count == names.len() {
+                    parser.error("Positional arguments must come before named ones.");
+                }
+
                 arg_count += 1;
 
                 if !parser.match_token(Token::Comma) {
@@ -323,11 +656,20 @@ impl<R: std::io::Read> ParseRule<R> {
         }
         parser.consume(Token::RightParen, "Expect ')' after function arguments.");
 
-        (arg_count, parser)
+        (arg_count, names, parser)
     }
 
-    fn _struct(mut parser: Parser<R>, _can_assign: bool) -> Parser<R> {
+    /// Parse instance fields (name: value), optionally ending with the instance left out fields are taken from: P { x: 1, ..p }
+    ///
+    fn _struct(parser: Parser<R>, _can_assign: bool) -> Parser<R> {
+        Self::instance_with(parser, None)
+    }
+
+    /// Emit instance creation, preceded by TypeArgs if it has explicit type arguments.
+    ///
+    fn instance_with(mut parser: Parser<R>, type_args: Option<usize>) -> Parser<R> {
         let mut arg_count = 0;
+        let mut spread = false;
 
         if !parser.check(Token::RightBrace) {
             loop {
@@ -337,10 +679,18 @@ impl<R: std::io::Read> ParseRule<R> {
                         parser.advance();
                         parser.consume(Token::Colon, "Expect ':' after field name definition.");
                         // Set and emit tuple (field_name, value) into stack;
+                        parser.emit_constant(Value { value: Primitive::String(name.into()), _type: Type::String, modifier: Modifier::Const });
                         parser = parser.expression();
-                        parser.emit_constant(Value { value: Primitive::String(name), _type: Type::String, modifier: Modifier::Const });
                         parser.emit_byte(OpCode::Tuple(2));
                     }
+                    /* Base instance is always the last one */
+                    Token::DotDot => {
+                        parser.advance();
+                        parser = parser.expression();
+                        spread = true;
+
+                        break;
+                    }
                     _ => parser.error("Expect field name.")
                 };
 
@@ -351,7 +701,14 @@ impl<R: std::io::Read> ParseRule<R> {
         }
         parser.consume(Token::RightBrace, "Expect '}' on object declaration.");
 
-        parser.emit_byte(OpCode::CreateInstance(arg_count));
+        if let Some(type_index) = type_args {
+            parser.emit_byte(OpCode::TypeArgs(type_index));
+        }
+
+        match spread {
+            true => parser.emit_byte(OpCode::UpdateInstance(arg_count)),
+            false => parser.emit_byte(OpCode::CreateInstance(arg_count)),
+        }
 
         parser
     }
@@ -363,20 +720,85 @@ impl<R: std::io::Read> ParseRule<R> {
         parser
     }
 
-    /// Assume that variable value is on stack, accessing it's field
-    /// 
-    fn dot(mut parser: Parser<R>, _can_assign: bool) -> Parser<R> {
-        let field_name = match parser.get_current() {
-            Token::Identifier(id) => id,
+    /// Assume that variable value is on stack, accessing it's field, or it's item for tuples (t.0)
+    ///
+    /// Fields of an instance read from a variable can be assigned (x.f = v, x.f op= v),
+    /// the updated instance is written back into the variable.
+    ///
+    fn dot(mut parser: Parser<R>, can_assign: bool) -> Parser<R> {
+        let name = match parser.get_current() {
+            Token::Identifier(id) => Value { value: Primitive::String(id.into()), _type: Type::String, modifier: Modifier::Unassigned },
+            Token::Integer(idx) => Value { value: Primitive::Int(idx), _type: Type::Int, modifier: Modifier::Unassigned },
             _ => panic!("Expect field name after '.'")
         };
 
-        parser.emit_constant(Value { value: Primitive::String(field_name), _type: Type::String, modifier: Modifier::Unassigned });
-        parser.emit_byte(OpCode::Access);
+        /* Variable instance was just read from, directly or through other fields */
+        let place = parser.place.take().filter(|place| place.end == parser.function.chunk.code.len());
+
         parser.advance();
 
+        if can_assign && parser.match_token(Token::Equal) {
+            let Some(place) = place else { parser.error("Invalid assignment target.") };
+            parser.assignment();
+            Self::reopen_place(&mut parser, &place);
+
+            parser = parser.expression();
+
+            let name_index = parser.function.chunk.write_constant(name.value);
+            parser.emit_byte(OpCode::SetField(name_index));
+            Self::close_place(&mut parser, place);
+        } else if let Some(operator) = parser.match_compound_assign(can_assign) {
+            let Some(place) = place else { parser.error("Invalid assignment target.") };
+            Self::reopen_place(&mut parser, &place);
+
+            /* Instance is read once: x.f op= v -> x.f = x.f op v */
+            parser.emit_byte(OpCode::Dup);
+            let name_index = parser.emit_constant(name);
+            parser.emit_byte(OpCode::Access);
+
+            parser = parser.compound_operand();
+            Self::arithmetic(&mut parser, &operator);
+
+            parser.emit_byte(OpCode::SetField(name_index));
+            Self::close_place(&mut parser, place);
+        } else {
+            let name_index = parser.emit_constant(name);
+            parser.emit_byte(OpCode::Access);
+
+            /* Nested fields stay assignable: p.q.v = x */
+            if let Some(mut place) = place {
+                place.fields.push(name_index);
+                place.end = parser.function.chunk.code.len();
+                parser.place = Some(place);
+            }
+        }
+
         parser
     }
+
+    /// Re-emit the field accesses of a place keeping each instance on the stack: p.q -> p, p.q
+    ///
+    fn reopen_place(parser: &mut Parser<R>, place: &Place) {
+        if place.fields.is_empty() { return; }
+
+        parser.function.chunk.code.truncate(place.start);
+
+        for field in place.fields.iter() {
+            parser.emit_byte(OpCode::Dup);
+            parser.emit_byte(OpCode::Constant(*field));
+            parser.emit_byte(OpCode::Access);
+        }
+    }
+
+    /// Set each updated instance back on it's parent, innermost first, then store the root into the variable.
+    ///
+    fn close_place(parser: &mut Parser<R>, place: Place) {
+        for field in place.fields.into_iter().rev() {
+            parser.emit_byte(OpCode::SetField(field));
+        }
+
+        parser.emit_byte(place.set_op);
+    }
 }
 
 /// Define which tokens will call which functions on prefix or infix while it's precedence is being parsed.
@@ -396,7 +818,7 @@ pub fn get_rule<R: std::io::Read>(token_code: &crate::parser::Token) -> ParseRul
         Token::LeftBrace => ParseRule {
             prefix: ParseRule::none,
             infix: ParseRule::_struct,
-            precedence: Precedence::Assignment,
+            precedence: Precedence::Call,
         },
         Token::RightBrace => ParseRule {
             prefix: ParseRule::none,
@@ -444,15 +866,79 @@ pub fn get_rule<R: std::io::Read>(token_code: &crate::parser::Token) -> ParseRul
             precedence: Precedence::None,
         },
         Token::Star => ParseRule {
+            prefix: ParseRule::deref,
+            infix: ParseRule::binary,
+            precedence: Precedence::Factor,
+        },
+        Token::Percent => ParseRule {
             prefix: ParseRule::none,
             infix: ParseRule::binary,
             precedence: Precedence::Factor,
         },
+        Token::PlusEqual
+        | Token::MinusEqual
+        | Token::StarEqual
+        | Token::SlashEqual
+        | Token::PercentEqual => ParseRule {
+            prefix: ParseRule::none,
+            infix: ParseRule::none,
+            precedence: Precedence::None,
+        },
+        Token::PlusPlus | Token::MinusMinus => ParseRule {
+            prefix: ParseRule::increment,
+            infix: ParseRule::none,
+            precedence: Precedence::None,
+        },
         Token::Arrow => ParseRule {
             prefix: ParseRule::none,
             infix: ParseRule::none,
             precedence: Precedence::None,
         },
+        Token::Question => ParseRule {
+            prefix: ParseRule::none,
+            infix: ParseRule::propagate,
+            precedence: Precedence::Call,
+        },
+        Token::Ok => ParseRule {
+            prefix: ParseRule::result,
+            infix: ParseRule::none,
+            precedence: Precedence::None,
+        },
+        Token::Err => ParseRule {
+            prefix: ParseRule::result,
+            infix: ParseRule::none,
+            precedence: Precedence::None,
+        },
+        Token::QuestionQuestion => ParseRule {
+            prefix: ParseRule::none,
+            infix: ParseRule::coalesce,
+            precedence: Precedence::Coalesce,
+        },
+        Token::QuestionDot => ParseRule {
+            prefix: ParseRule::none,
+            infix: ParseRule::optional_dot,
+            precedence: Precedence::Call,
+        },
+        Token::DotDot => ParseRule {
+            prefix: ParseRule::none,
+            infix: ParseRule::none,
+            precedence: Precedence::None,
+        },
+        Token::Ellipsis => ParseRule {
+            prefix: ParseRule::none,
+            infix: ParseRule::none,
+            precedence: Precedence::None,
+        },
+        Token::ThinArrow => ParseRule {
+            prefix: ParseRule::none,
+            infix: ParseRule::none,
+            precedence: Precedence::None,
+        },
+        Token::ColonColon => ParseRule {
+            prefix: ParseRule::none,
+            infix: ParseRule::none,
+            precedence: Precedence::None,
+        },
         Token::Bang => ParseRule {
             prefix: ParseRule::unary,
             infix: ParseRule::none,
@@ -510,6 +996,11 @@ pub fn get_rule<R: std::io::Read>(token_code: &crate::parser::Token) -> ParseRul
             infix: ParseRule::none,
             precedence: Precedence::None,
         },
+        Token::Interpolation(_) => ParseRule {
+            prefix: ParseRule::interpolation,
+            infix: ParseRule::none,
+            precedence: Precedence::None,
+        },
         Token::Integer(_) => ParseRule {
             prefix: ParseRule::number,
             infix: ParseRule::none,
@@ -566,7 +1057,7 @@ pub fn get_rule<R: std::io::Read>(token_code: &crate::parser::Token) -> ParseRul
             precedence: Precedence::None,
         },
         Token::Nil => ParseRule {
-            prefix: ParseRule::none,
+            prefix: ParseRule::literal,
             infix: ParseRule::none,
 
             precedence: Precedence::None,
@@ -586,6 +1077,21 @@ pub fn get_rule<R: std::io::Read>(token_code: &crate::parser::Token) -> ParseRul
             infix: ParseRule::none,
             precedence: Precedence::None,
         },
+        Token::Impl => ParseRule {
+            prefix: ParseRule::none,
+            infix: ParseRule::none,
+            precedence: Precedence::None,
+        },
+        Token::Trait => ParseRule {
+            prefix: ParseRule::none,
+            infix: ParseRule::none,
+            precedence: Precedence::None,
+        },
+        Token::TypeAlias => ParseRule {
+            prefix: ParseRule::none,
+            infix: ParseRule::none,
+            precedence: Precedence::None,
+        },
         Token::Switch => ParseRule {
             prefix: ParseRule::none,
             infix: ParseRule::none,
@@ -596,6 +1102,26 @@ pub fn get_rule<R: std::io::Read>(token_code: &crate::parser::Token) -> ParseRul
             infix: ParseRule::none,
             precedence: Precedence::None,
         },
+        Token::Throw => ParseRule {
+            prefix: ParseRule::none,
+            infix: ParseRule::none,
+            precedence: Precedence::None,
+        },
+        Token::Try => ParseRule {
+            prefix: ParseRule::none,
+            infix: ParseRule::none,
+            precedence: Precedence::None,
+        },
+        Token::Catch => ParseRule {
+            prefix: ParseRule::none,
+            infix: ParseRule::none,
+            precedence: Precedence::None,
+        },
+        Token::Finally => ParseRule {
+            prefix: ParseRule::none,
+            infix: ParseRule::none,
+            precedence: Precedence::None,
+        },
 
         Token::Continue => ParseRule {
             prefix: ParseRule::none,
@@ -614,7 +1140,7 @@ pub fn get_rule<R: std::io::Read>(token_code: &crate::parser::Token) -> ParseRul
             precedence: Precedence::None,
         },
         Token::This => ParseRule {
-            prefix: ParseRule::none,
+            prefix: ParseRule::this,
             infix: ParseRule::none,
             precedence: Precedence::None,
         },
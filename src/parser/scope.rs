@@ -1,6 +1,44 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
-use crate::{objects::hash_table::HashTable, primitives::types::Modifier};
+use crate::{objects::{hash_table::HashTable, string::{intern, IString}}, parser::lexer::Position, primitives::{primitive::Primitive, types::Modifier}, vm::chunk::OpCode};
+
+/// Reference to a local variable, tracked at compile time so it can't outlive the variable's scope.
+///
+#[derive(Debug, Clone)]
+pub struct Borrow {
+    pub name: String,
+    /// Scopes count up to the one borrowed variable is declared in.
+    pub depth: usize,
+    /// Position of the `&`.
+    pub at: Position,
+}
+
+/// Variable an expression was read from, along with the fields accessed on it, so they can be assigned back.
+///
+#[derive(Debug, Clone)]
+pub struct Place {
+    pub set_op: OpCode,
+    /// Code length after the variable read.
+    pub start: usize,
+    /// Name constants of the fields accessed since, outermost first: p.q.v -> [q] when assigning v.
+    pub fields: Vec<usize>,
+    /// Code length after the last access.
+    pub end: usize,
+}
+
+/// Try statement being compiled, so returns inside it run it's finally block first.
+///
+#[derive(Debug)]
+pub struct Try {
+    /// Scopes count up to the one holding the statement hidden locals.
+    pub depth: usize,
+    /// Hidden local set to true by a return waiting on finally.
+    pub returning: usize,
+    /// Hidden local holding the value being returned.
+    pub value: usize,
+    /// Jumps of returns, patched to the finally block.
+    pub jumps: Vec<usize>,
+}
 
 /// General scope handler.
 ///
@@ -9,8 +47,12 @@ pub struct Scope {
     /// Represents all local variables, resolved dynamically at runtime, without a Constant Bytecode.
     ///
     /// (Var position on locals [consequently on Stack], Modifier)
-    pub locals: HashTable<String, (usize, Modifier)>,
+    pub locals: HashTable<IString, (usize, Modifier)>,
     pub local_count: usize,
+    /// References held by this scope's locals, by local index.
+    pub borrows: HashMap<usize, Borrow>,
+    /// Constants declared in this scope, readable until it ends.
+    pub consts: HashMap<String, Primitive>,
 }
 
 /// Represent a block scope
@@ -19,14 +61,14 @@ impl Scope {
     /// Add new Local by hashing and inserting it
     ///
     pub fn add_local(&mut self, lexeme: String, modifier: Modifier, total_locals: usize) {
-        self.locals.insert(&lexeme, (total_locals, modifier));
+        self.locals.insert(&intern(&lexeme), (total_locals, modifier));
         self.local_count += 1;
     }
 
     /// Return Local index to be used by stack if it exists
     ///
-    pub fn get_local(&self, lexeme: &String) -> Option<Rc<RefCell<(usize, Modifier)>>> {
-        self.locals.get(lexeme)
+    pub fn get_local(&self, lexeme: &str) -> Option<Rc<RefCell<(usize, Modifier)>>> {
+        self.locals.get(&intern(lexeme))
     }
 }
 
@@ -35,6 +77,8 @@ impl<'a> Default for Scope {
         Scope {
             locals: HashTable::default(),
             local_count: 0,
+            borrows: HashMap::new(),
+            consts: HashMap::new(),
         }
     }
 }
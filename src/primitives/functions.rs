@@ -1,6 +1,6 @@
 use std::{cell::RefCell, rc::Rc};
 
-use crate::{primitives::value::Value, vm::chunk::Chunk};
+use crate::{objects::string::IString, errors::vm::{InterpretResult, VmError}, primitives::{native::take_error, primitive::UpValue, types::{Signature, TypeArgs}, value::Value}, vm::chunk::Chunk};
 
 #[derive(Debug, Clone, Default)]
 pub struct Function {
@@ -9,6 +9,22 @@ pub struct Function {
     pub name: String,
     // Upvalue count
     pub upv_count: usize,
+    // Variables captured from enclosing function when a closure is created
+    pub upvalues: Vec<UpValue>,
+    // Parameters and return types
+    pub signature: Rc<Signature>,
+    // Parameters names and default values, in declaration order
+    pub params: Vec<Param>,
+    // If last parameter collects the remaining arguments (...args)
+    pub variadic: bool,
+    // Names of the type parameters: fn first<T>(x: T) -> T
+    pub type_params: Vec<IString>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Param {
+    pub name: IString,
+    pub default: Option<Value>,
 }
 
 #[derive(Debug, PartialEq)]
@@ -25,9 +41,40 @@ impl Function {
             arity: 0,
             chunk: Chunk::default(),
             upv_count: 0,
+            upvalues: vec![],
+            signature: Rc::default(),
+            params: vec![],
+            variadic: false,
+            type_params: vec![],
             name,
         }
     }
+
+    /// Count of parameters which must be provided on call.
+    ///
+    pub fn required(&self) -> usize {
+        self.params
+            .iter()
+            .take(self.fixed())
+            .filter(|p| p.default.is_none())
+            .count()
+    }
+
+    /// Count of parameters which are not variadic.
+    ///
+    pub fn fixed(&self) -> usize {
+        self.params.len() - self.variadic as usize
+    }
+
+    /// Human readable accepted argument count, as in '1 to 2' or 'at least 1'.
+    ///
+    pub fn arity_range(&self) -> String {
+        match (self.required(), self.fixed()) {
+            (req, _) if self.variadic => format!("at least {req}"),
+            (req, fixed) if req == fixed => format!("{req}"),
+            (req, fixed) => format!("{req} to {fixed}"),
+        }
+    }
 }
 
 impl PartialEq for Function {
@@ -39,20 +86,70 @@ impl PartialEq for Function {
 #[derive(Clone, Debug, PartialEq)]
 pub struct Closure {
     pub _fn: Rc<Function>,
+    /* Captured variables cells, shared with the enclosing function */
+    pub upvalues: Vec<Rc<RefCell<Value>>>,
+    /* Type parameters the enclosing function was called with */
+    pub type_args: TypeArgs,
 }
 
+pub type NativeCall = fn(&[Value]) -> Value;
+
+/// Bytes a native call would allocate for it's arguments, see `NativeFn::allocates`.
+///
+pub type NativeSize = fn(&[Value]) -> usize;
+
+/// Method read from an instance (p.area), called with the instance as it's first argument.
+///
 #[derive(Debug, Clone, PartialEq)]
+pub struct Method {
+    pub receiver: Value,
+    pub function: Value,
+}
+
+/// Natives the Vm runs itself when their arguments have methods to call back, see `Vm::defer_native`.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Deferred {
+    /// `hash(v)` calls v's `hash` method.
+    Hash,
+    /// `join(items, separator)` calls the `to_string` methods of the items.
+    Join,
+}
+
+#[derive(Debug, Clone)]
 pub struct NativeFn {
+    pub name: &'static str,
     pub arity: usize,
-    pub _fn: fn(&[Rc<RefCell<Value>>]) -> Value,
+    pub _fn: NativeCall,
+    /// Set on the std lib natives calling instance methods back.
+    pub deferred: Option<Deferred>,
+    /// Set on natives building strings or tuples out of their arguments,
+    /// so the Vm can check the bytes against it's heap limit before they are allocated.
+    pub allocates: Option<NativeSize>,
+}
+
+/// Natives are the same if they call the same function, deferred and allocates follow from it.
+///
+impl PartialEq for NativeFn {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.arity == other.arity && std::ptr::fn_addr_eq(self._fn, other._fn)
+    }
 }
 
 impl NativeFn {
-    pub fn call(&mut self, args: &[Rc<RefCell<Value>>]) -> Value {
+    pub fn call(&mut self, args: &[Value]) -> Result<Value, VmError> {
         if args.len() != self.arity {
-            panic!("Expect {} but got {} arguments.", self.arity, args.len())
+            return Err(VmError::new(
+                format!("Function '{}' expects {} arguments but got {}.", self.name, self.arity, args.len()),
+                InterpretResult::RuntimeError,
+            ));
         }
 
-        (self._fn)(args)
+        let result = (self._fn)(args);
+
+        match take_error() {
+            Some(msg) => Err(VmError::new(msg, InterpretResult::RuntimeError)),
+            None => Ok(result),
+        }
     }
 }
\ No newline at end of file
@@ -4,4 +4,5 @@ pub mod tuple;
 pub mod primitive;
 pub mod types;
 pub mod value;
-pub mod functions;
\ No newline at end of file
+pub mod functions;
+pub mod strings;
@@ -1,4 +1,6 @@
-use std::{cell::RefCell, rc::Rc, time::Instant};
+use std::{cell::RefCell, hash::{Hash, Hasher}, time::Instant};
+
+use crate::{utils::hasher::FNV1aHasher, vm::gc::request_collection};
 
 use super::{
     primitive::Primitive,
@@ -6,7 +8,26 @@ use super::{
     value::Value,
 };
 
-pub fn duration(_args: &[Rc<RefCell<Value>>]) -> Value {
+thread_local! {
+    /* Set by natives failing, as they have no access to the Vm running them */
+    static ERROR: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Fail current native call with a runtime error, returning the placeholder value the native returns.
+///
+pub fn raise(msg: String) -> Value {
+    ERROR.set(Some(msg));
+
+    Value::default()
+}
+
+/// Take the error raised by last native call.
+///
+pub fn take_error() -> Option<String> {
+    ERROR.take()
+}
+
+pub fn duration(_args: &[Value]) -> Value {
     Value {
         value: Primitive::Int(
             Instant::now()
@@ -20,25 +41,56 @@ pub fn duration(_args: &[Rc<RefCell<Value>>]) -> Value {
     }
 }
 
-pub fn _typeof(args: &[Rc<RefCell<Value>>]) -> Value {
-    let obj = args[0].borrow();
+pub fn _typeof(args: &[Value]) -> Value {
+    let obj = &args[0];
 
     let t = match obj.value {
-        Primitive::String(_) => "String",
-        Primitive::Bool(_) => "Boolean",
-        Primitive::Int(_) => "Integer",
-        Primitive::Float(_) => "Float",
-        Primitive::Function(_) => "Function",
-        Primitive::NativeFunction(_) => "NativeFunction",
-        Primitive::Ref(_) => "Reference",
-        Primitive::Void(_) => "Void",
-        Primitive::Closure { .. } => "Closure",
-        _ => "Invalid Type",
+        Primitive::String(_) => "String".to_string(),
+        Primitive::Bool(_) => "Boolean".to_string(),
+        Primitive::Int(_) => "Integer".to_string(),
+        Primitive::Float(_) => "Float".to_string(),
+        // Callables report their full signature, as in fn(Int) -> Bool
+        Primitive::Function(ref f) => f.signature.to_string(),
+        Primitive::NativeFunction(_) => "NativeFunction".to_string(),
+        Primitive::Ref(_) => "Reference".to_string(),
+        Primitive::Nil => "Nil".to_string(),
+        Primitive::Result(_) => "Result".to_string(),
+        Primitive::Void(_) => "Void".to_string(),
+        Primitive::Closure(ref c) => c._fn.signature.to_string(),
+        Primitive::Method(_) => "Method".to_string(),
+        // Tuples report their element types, as in (Int, String)
+        Primitive::Tuple(_) => obj._type.to_string(),
+        Primitive::Instance(ref instance) => instance._struct.name.clone(),
+        Primitive::Struct(_) => "Struct".to_string(),
+        Primitive::Cell(ref cell) => return _typeof(&[cell.borrow().clone()]),
     };
 
     Value {
-        value: Primitive::String(t.to_string()),
+        value: Primitive::String(t.into()),
         _type: Type::String,
         modifier: Modifier::Const,
     }
 }
+
+/// Request a garbage collection, run by the Vm as soon as this call returns.
+///
+pub fn gc(_args: &[Value]) -> Value {
+    request_collection();
+
+    Value::default()
+}
+
+/// Derived hash of a value, as used for table keys.
+///
+/// Instances defining a `hash` method are hashed by it instead, the Vm calls it in place of this native.
+///
+pub fn hash(args: &[Value]) -> Value {
+    let mut hasher = FNV1aHasher::new();
+    args[0].hash(&mut hasher);
+
+    Value {
+        value: Primitive::Int(hasher.finish() as i64),
+        _type: Type::Int,
+        modifier: Modifier::Const,
+    }
+}
@@ -1,10 +1,13 @@
 use std::{
     cell::RefCell,
     fmt::{self, Display},
+    hash::{Hash, Hasher},
+    mem::discriminant,
     rc::Rc,
 };
 
-use crate::primitives::{functions::{Closure, Function, NativeFn}, structs::{Instance, Struct}, tuple::Tuple};
+use crate::objects::string::IString;
+use crate::primitives::{functions::{Closure, Function, Method, NativeFn}, structs::{Instance, Struct}, tuple::Tuple};
 
 use super::value::Value;
 
@@ -13,19 +16,25 @@ pub enum Primitive {
     Float(f64),
     Int(i64),
     Bool(bool),
-    String(String),
-    Struct(Struct),
-    Instance(Instance),
+    String(IString),
+    Struct(Rc<Struct>),
+    Instance(Rc<Instance>),
     Tuple(Tuple),
     NativeFunction(NativeFn),
     Function(Rc<Function>),
-    Closure(Closure),
+    Closure(Rc<Closure>),
+    Method(Rc<Method>),
     Ref(Rc<RefCell<Value>>),
+    // Boxed local variable slot, shared with references and closures capturing it
+    Cell(Rc<RefCell<Value>>),
+    // Ok(v) / Err(e) values
+    Result(Result<Box<Value>, Box<Value>>),
+    Nil,
     Void(()),
 }
 
-impl<'a> From<&'a Primitive> for &'a String {
-    fn from(_p: &'a Primitive) -> &'a String {
+impl<'a> From<&'a Primitive> for &'a str {
+    fn from(_p: &'a Primitive) -> &'a str {
         match _p {
             Primitive::String(s) => s,
             _ => panic!("Invalid Cast to &Primitive for String")
@@ -40,11 +49,16 @@ impl Display for Primitive {
             Primitive::Int(i) => write!(fmt, "{i}"),
             Primitive::Bool(b) => write!(fmt, "{b}"),
             Primitive::String(str) => write!(fmt, "{}", str),
+            Primitive::Nil => write!(fmt, "nil"),
+            Primitive::Result(Ok(v)) => write!(fmt, "Ok({})", v.value),
+            Primitive::Result(Err(e)) => write!(fmt, "Err({})", e.value),
             Primitive::Void(t) => write!(fmt, "{t:?}"),
             Primitive::Ref(value_ptr) => write!(fmt, "&{}", value_ptr.borrow().value),
+            Primitive::Cell(cell) => write!(fmt, "{}", cell.borrow().value),
             Primitive::Function(f) => write!(fmt, "&fn<{}, {}>", f.arity, f.name),
             Primitive::NativeFunction(f) => write!(fmt, "&native_fn<{:?}>", f),
             Primitive::Closure(c) => write!(fmt, "&closure<{:?}, {}>", c._fn.arity, c._fn.name),
+            Primitive::Method(m) => write!(fmt, "&method<{}>", m.function.value),
             Primitive::Struct(_struct) => {
                 write!(fmt, "{} {{ ", _struct.name)?;
 
@@ -54,13 +68,64 @@ impl Display for Primitive {
 
                 write!(fmt, "}}")
             },
-            Primitive::Instance(inst) => write!(fmt, "instance_of({})", inst._struct.borrow().value),
-            Primitive::Tuple(t) => write!(fmt, "{:?}", t.items)
+            Primitive::Instance(inst) => write!(fmt, "{inst}"),
+            Primitive::Tuple(t) => {
+                write!(fmt, "(")?;
+
+                for (i, item) in t.items.iter().enumerate() {
+                    if i > 0 { write!(fmt, ", ")?; }
+                    write!(fmt, "{}", item.value)?;
+                }
+
+                if t.items.len() == 1 { write!(fmt, ",")?; }
+
+                write!(fmt, ")")
+            }
+        }
+    }
+}
+
+/// Derived hashing, agreeing with `==`: values equal to each other hash the same.
+///
+/// Instances and tuples hash their fields, callables and references only their kind.
+///
+/// Instance `eq` and `hash` methods need the Vm to run, so tables keyed by values on the Rust side
+/// (e.g: `HashTable<Value, _>`) only use this and the derived `==`: instances a script considers
+/// equal through it's own `eq` may be different keys.
+///
+impl Hash for Primitive {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        discriminant(self).hash(state);
+
+        match self {
+            /* 0.0 == -0.0 */
+            Primitive::Float(f) if *f == 0.0 => 0.0f64.to_bits().hash(state),
+            Primitive::Float(f) => f.to_bits().hash(state),
+            Primitive::Int(i) => i.hash(state),
+            Primitive::Bool(b) => b.hash(state),
+            Primitive::String(s) => s.hash(state),
+            Primitive::Struct(_struct) => _struct.name.hash(state),
+            Primitive::Instance(instance) => {
+                instance._struct.name.hash(state);
+                instance.values.hash(state);
+            }
+            Primitive::Tuple(tuple) => tuple.items.hash(state),
+            Primitive::Result(result) => {
+                result.is_ok().hash(state);
+
+                match result {
+                    Ok(v) | Err(v) => v.hash(state),
+                }
+            }
+            _ => (),
         }
     }
 }
 
-#[derive(Debug)]
+/// Compile-time reference to a captured variable: a local slot of the enclosing function
+/// or an upvalue already captured by it.
+///
+#[derive(Debug, Clone, PartialEq)]
 pub struct UpValue {
     pub index: usize,
     pub is_local: bool,
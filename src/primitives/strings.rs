@@ -0,0 +1,290 @@
+use crate::objects::string::{IString, StringObj};
+
+use super::{
+    functions::{NativeCall, NativeSize},
+    native::raise,
+    primitive::Primitive,
+    tuple::Tuple,
+    types::{Modifier, Type},
+    value::Value,
+};
+
+/// String natives, indexes count chars and not bytes.
+///
+pub const NATIVES: [(&str, usize, NativeCall); 16] = [
+    ("len", 1, len),
+    ("char_at", 2, char_at),
+    ("substring", 3, substring),
+    ("slice", 3, slice),
+    ("find", 2, find),
+    ("contains", 2, contains),
+    ("starts_with", 2, starts_with),
+    ("ends_with", 2, ends_with),
+    ("split", 2, split),
+    ("trim", 1, trim),
+    ("to_upper", 1, to_upper),
+    ("to_lower", 1, to_lower),
+    ("replace", 3, replace),
+    ("repeat", 2, repeat),
+    ("parse_int", 1, parse_int),
+    ("parse_float", 1, parse_float),
+];
+
+/// Bytes allocated by the natives building strings or tuples in proportion to their arguments, None for the others.
+///
+pub fn allocates(native: &str) -> Option<NativeSize> {
+    match native {
+        "split" => Some(split_size),
+        "join" => Some(join_size),
+        "replace" => Some(replace_size),
+        "repeat" => Some(repeat_size),
+        _ => None,
+    }
+}
+
+fn string(s: impl Into<String>) -> Value {
+    Value { value: Primitive::String(IString::new(s.into())), _type: Type::String, modifier: Modifier::Const }
+}
+
+fn int(i: i64) -> Value {
+    Value { value: Primitive::Int(i), _type: Type::Int, modifier: Modifier::Const }
+}
+
+fn bool(b: bool) -> Value {
+    Value { value: Primitive::Bool(b), _type: Type::Bool, modifier: Modifier::Const }
+}
+
+/// Raise a runtime error for argument of unexpected type.
+///
+fn expected(native: &str, t: &str, arg: &Value) -> Value {
+    raise(format!("Function '{native}' expects {t} but got {}.", arg._type))
+}
+
+fn char_count(s: &str) -> i64 {
+    s.chars().count() as i64
+}
+
+/// Byte index of the char at idx, which may be one past the last char.
+///
+fn byte_idx(s: &str, idx: i64) -> usize {
+    s.char_indices().map(|(i, _)| i).nth(idx as usize).unwrap_or(s.len())
+}
+
+fn len(args: &[Value]) -> Value {
+    let Primitive::String(s) = &args[0].value else { return expected("len", "String", &args[0]) };
+
+    int(char_count(s))
+}
+
+fn char_at(args: &[Value]) -> Value {
+    let Primitive::String(s) = &args[0].value else { return expected("char_at", "String", &args[0]) };
+    let Primitive::Int(idx) = &args[1].value else { return expected("char_at", "Int", &args[1]) };
+
+    match usize::try_from(*idx).ok().and_then(|i| s.chars().nth(i)) {
+        Some(ch) => string(ch),
+        None => raise(format!("Index {idx} out of range for string of length {}.", char_count(s))),
+    }
+}
+
+/// Chars from start up to end, both must be in range.
+///
+fn substring(args: &[Value]) -> Value {
+    let Primitive::String(s) = &args[0].value else { return expected("substring", "String", &args[0]) };
+    let Primitive::Int(start) = &args[1].value else { return expected("substring", "Int", &args[1]) };
+    let Primitive::Int(end) = &args[2].value else { return expected("substring", "Int", &args[2]) };
+
+    let count = char_count(s);
+    if *start < 0 || start > end || *end > count {
+        return raise(format!("Range {start}..{end} out of bounds for string of length {count}."));
+    }
+
+    string(&s[byte_idx(s, *start)..byte_idx(s, *end)])
+}
+
+/// Chars from start up to end, negative indexes count from the end and out of range ones are clamped.
+///
+fn slice(args: &[Value]) -> Value {
+    let Primitive::String(s) = &args[0].value else { return expected("slice", "String", &args[0]) };
+    let Primitive::Int(start) = &args[1].value else { return expected("slice", "Int", &args[1]) };
+    let Primitive::Int(end) = &args[2].value else { return expected("slice", "Int", &args[2]) };
+
+    let count = char_count(s);
+    let clamp = |i: i64| if i < 0 { (count + i).max(0) } else { i.min(count) };
+    let (start, end) = (clamp(*start), clamp(*end));
+
+    if start >= end {
+        return string("");
+    }
+
+    string(&s[byte_idx(s, start)..byte_idx(s, end)])
+}
+
+/// Index of first occurrence of pattern, nil if there is none.
+///
+fn find(args: &[Value]) -> Value {
+    let Primitive::String(s) = &args[0].value else { return expected("find", "String", &args[0]) };
+    let Primitive::String(pattern) = &args[1].value else { return expected("find", "String", &args[1]) };
+
+    match s.find(pattern.as_str()) {
+        Some(i) => int(char_count(&s[..i])),
+        None => Value { value: Primitive::Nil, _type: Type::Nil, modifier: Modifier::Const },
+    }
+}
+
+fn contains(args: &[Value]) -> Value {
+    let Primitive::String(s) = &args[0].value else { return expected("contains", "String", &args[0]) };
+    let Primitive::String(pattern) = &args[1].value else { return expected("contains", "String", &args[1]) };
+
+    bool(s.contains(pattern.as_str()))
+}
+
+fn starts_with(args: &[Value]) -> Value {
+    let Primitive::String(s) = &args[0].value else { return expected("starts_with", "String", &args[0]) };
+    let Primitive::String(prefix) = &args[1].value else { return expected("starts_with", "String", &args[1]) };
+
+    bool(s.starts_with(prefix.as_str()))
+}
+
+fn ends_with(args: &[Value]) -> Value {
+    let Primitive::String(s) = &args[0].value else { return expected("ends_with", "String", &args[0]) };
+    let Primitive::String(suffix) = &args[1].value else { return expected("ends_with", "String", &args[1]) };
+
+    bool(s.ends_with(suffix.as_str()))
+}
+
+/// Tuple of the parts between separators, an empty separator splits every char.
+///
+fn split(args: &[Value]) -> Value {
+    let Primitive::String(s) = &args[0].value else { return expected("split", "String", &args[0]) };
+    let Primitive::String(separator) = &args[1].value else { return expected("split", "String", &args[1]) };
+
+    let items: Vec<Value> = match separator.is_empty() {
+        true => s.chars().map(string).collect(),
+        false => s.split(separator.as_str()).map(string).collect(),
+    };
+
+    Tuple::new(items.into()).into()
+}
+
+fn split_size(args: &[Value]) -> usize {
+    let (Primitive::String(s), Primitive::String(separator)) = (&args[0].value, &args[1].value) else { return 0 };
+
+    let parts = match separator.is_empty() {
+        true => s.chars().count(),
+        false => s.matches(separator.as_str()).count() + 1,
+    };
+
+    parts.saturating_mul(size_of::<Value>() + size_of::<StringObj>()).saturating_add(s.len())
+}
+
+/// Tuple items printed and joined by separator.
+///
+/// Registered apart from `NATIVES`, as the Vm calls the `to_string` methods of the items in place of it.
+///
+pub fn join(args: &[Value]) -> Value {
+    let Primitive::Tuple(parts) = &args[0].value else { return expected("join", "Tuple", &args[0]) };
+    let Primitive::String(separator) = &args[1].value else { return expected("join", "String", &args[1]) };
+
+    let parts: Vec<String> = parts.items.iter().map(|item| item.to_string()).collect();
+
+    string(parts.join(separator))
+}
+
+fn join_size(args: &[Value]) -> usize {
+    let (Primitive::Tuple(parts), Primitive::String(separator)) = (&args[0].value, &args[1].value) else { return 0 };
+
+    let separators = separator.len().saturating_mul(parts.items.len().saturating_sub(1));
+
+    parts.items.iter().fold(separators, |bytes, item| bytes.saturating_add(printed_len(item)))
+}
+
+/// Length of value printed, without printing strings and tuples which may be large.
+///
+fn printed_len(value: &Value) -> usize {
+    match &value.value {
+        Primitive::String(s) => s.len(),
+        /* (a, b) */
+        Primitive::Tuple(tuple) => tuple.items.iter().fold(2 + 2 * tuple.items.len(), |bytes, item| bytes.saturating_add(printed_len(item))),
+        _ => value.to_string().len(),
+    }
+}
+
+fn trim(args: &[Value]) -> Value {
+    let Primitive::String(s) = &args[0].value else { return expected("trim", "String", &args[0]) };
+
+    string(s.trim())
+}
+
+fn to_upper(args: &[Value]) -> Value {
+    let Primitive::String(s) = &args[0].value else { return expected("to_upper", "String", &args[0]) };
+
+    string(s.to_uppercase())
+}
+
+fn to_lower(args: &[Value]) -> Value {
+    let Primitive::String(s) = &args[0].value else { return expected("to_lower", "String", &args[0]) };
+
+    string(s.to_lowercase())
+}
+
+/// Replace every occurrence of pattern.
+///
+fn replace(args: &[Value]) -> Value {
+    let Primitive::String(s) = &args[0].value else { return expected("replace", "String", &args[0]) };
+    let Primitive::String(pattern) = &args[1].value else { return expected("replace", "String", &args[1]) };
+    let Primitive::String(with) = &args[2].value else { return expected("replace", "String", &args[2]) };
+
+    string(s.replace(pattern.as_str(), with))
+}
+
+fn replace_size(args: &[Value]) -> usize {
+    let (Primitive::String(s), Primitive::String(pattern), Primitive::String(with)) = (&args[0].value, &args[1].value, &args[2].value) else { return 0 };
+
+    let count = s.matches(pattern.as_str()).count();
+
+    (s.len() - count * pattern.len()).saturating_add(count.saturating_mul(with.len()))
+}
+
+/// Largest string `repeat` builds, so scripts can't exhaust the host memory at once, even with no heap limit set.
+///
+const MAX_REPEAT_BYTES: usize = 1 << 30;
+
+fn repeat_size(args: &[Value]) -> usize {
+    let (Primitive::String(s), Primitive::Int(times)) = (&args[0].value, &args[1].value) else { return 0 };
+
+    s.len().saturating_mul(usize::try_from(*times).unwrap_or(0))
+}
+
+fn repeat(args: &[Value]) -> Value {
+    let Primitive::String(s) = &args[0].value else { return expected("repeat", "String", &args[0]) };
+    let Primitive::Int(times) = &args[1].value else { return expected("repeat", "Int", &args[1]) };
+
+    let bytes = usize::try_from(*times).ok().and_then(|times| s.len().checked_mul(times));
+
+    match bytes {
+        Some(bytes) if bytes <= MAX_REPEAT_BYTES => string(s.repeat(*times as usize)),
+        _ => raise(format!("Cannot repeat string {times} times.")),
+    }
+}
+
+/// Ok(Int), or Err with a message if s is not an integer.
+///
+fn parse_int(args: &[Value]) -> Value {
+    let Primitive::String(s) = &args[0].value else { return expected("parse_int", "String", &args[0]) };
+
+    match s.parse::<i64>() {
+        Ok(i) => Value::ok(int(i)),
+        Err(_) => Value::err(string(format!("Invalid integer '{s}'."))),
+    }
+}
+
+/// Ok(Float), or Err with a message if s is not a number.
+///
+fn parse_float(args: &[Value]) -> Value {
+    let Primitive::String(s) = &args[0].value else { return expected("parse_float", "String", &args[0]) };
+
+    match s.parse::<f64>() {
+        Ok(f) => Value::ok(Value { value: Primitive::Float(f), _type: Type::Float, modifier: Modifier::Const }),
+        Err(_) => Value::err(string(format!("Invalid float '{s}'."))),
+    }
+}
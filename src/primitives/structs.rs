@@ -1,12 +1,52 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{cell::RefCell, collections::HashMap, fmt::{self, Display}, rc::Rc};
 
-use crate::primitives::{types::{Modifier, Type}, value::Value};
+use crate::objects::string::IString;
+use crate::primitives::{types::{Modifier, Type, TypeArgs}, value::Value};
 
 #[derive(Debug, PartialEq)]
 pub struct Struct {
     pub name: String,
-    pub field_indices: HashMap<String, (Type, usize)>,
+    pub field_indices: HashMap<IString, (Type, usize)>,
     pub field_count: usize,
+    /// Values of fields left out of instances, by field index.
+    pub defaults: Vec<Option<Value>>,
+    pub methods: Methods,
+    /// Names of the type parameters fields may be typed with: struct Pair<A, B> {}
+    pub type_params: Vec<IString>,
+}
+
+/// Functions defined by impl blocks, shared by every copy of the struct so instances created
+/// before the impl block see them as well.
+///
+#[derive(Debug, Clone, Default)]
+pub struct Methods(pub Rc<RefCell<HashMap<IString, Value>>>);
+
+/* Struct equality is structural, while it's methods are the same only if defined by the same impl blocks */
+impl PartialEq for Methods {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Struct {
+    pub fn method(&self, name: &str) -> Option<Value> {
+        self.methods.0.borrow().get(&IString::from(name)).cloned()
+    }
+
+    /// Fields names, in declaration order.
+    ///
+    pub fn field_names(&self) -> Vec<IString> {
+        let mut fields: Vec<(&IString, usize)> = self.field_indices.iter().map(|(name, (_, idx))| (name, *idx)).collect();
+        fields.sort_by_key(|(_, idx)| *idx);
+
+        fields.into_iter().map(|(name, _)| name.clone()).collect()
+    }
+
+    /// Type parameters bound to the given types, in declaration order.
+    ///
+    pub fn bindings(&self, type_args: &[Type]) -> TypeArgs {
+        self.type_params.iter().cloned().zip(type_args.iter().cloned()).collect()
+    }
 }
 
 // Suitable for clone inspec
@@ -16,6 +56,9 @@ impl Clone for Struct {
             name: self.name.clone(),
             field_indices: self.field_indices.clone(),
             field_count: self.field_count,
+            defaults: self.defaults.clone(),
+            methods: self.methods.clone(),
+            type_params: self.type_params.clone(),
         }
     }
 }
@@ -23,7 +66,7 @@ impl Clone for Struct {
 impl Into<Value> for Struct {
     fn into(self) -> Value {
         Value {
-            value: crate::primitives::primitive::Primitive::Struct(self),
+            value: crate::primitives::primitive::Primitive::Struct(Rc::new(self)),
             _type: Type::Struct,
             modifier: Modifier::Const
         }
@@ -32,16 +75,51 @@ impl Into<Value> for Struct {
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Instance {
-    pub _struct: Rc<RefCell<Value>>,
-    pub values: Vec<Value>
+    pub _struct: Rc<Struct>,
+    pub values: Vec<Value>,
+    /// Types bound to the struct type parameters, in declaration order.
+    pub type_args: Vec<Type>,
+}
+
+impl Instance {
+    pub fn new(_struct: Rc<Struct>, values: Vec<Value>, type_args: Vec<Type>) -> Self {
+        Instance { _struct, values, type_args }
+    }
+
+    /// Types bound to the struct type parameters, leaving out the ones nothing bound.
+    ///
+    pub fn bindings(&self) -> TypeArgs {
+        let mut bindings = self._struct.bindings(&self.type_args);
+        bindings.retain(|_, t| *t != Type::UnInit);
+
+        bindings
+    }
 }
 
 impl Into<Value> for Instance {
     fn into(self) -> Value {
         Value {
-            value: crate::primitives::primitive::Primitive::Instance(self),
+            value: crate::primitives::primitive::Primitive::Instance(Rc::new(self)),
             _type: Type::Struct,
             modifier: Modifier::Const
         }
     }
-}
\ No newline at end of file
+}
+
+/// Default printing, as in Point { x: 1, y: 2 }; overridden by a `to_string` method.
+///
+impl Display for Instance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {{", self._struct.name)?;
+
+        for (i, name) in self._struct.field_names().iter().enumerate() {
+            if i > 0 { write!(f, ",")?; }
+            write!(f, " {}: {}", name, self.values[i].value)?;
+        }
+
+        match self.values.is_empty() {
+            true => write!(f, "}}"),
+            false => write!(f, " }}"),
+        }
+    }
+}
@@ -1,6 +1,28 @@
-use crate::primitives::value::Value;
+use std::rc::Rc;
+
+use crate::primitives::{primitive::Primitive, types::{Modifier, Type}, value::Value};
 
 #[derive(Clone, PartialEq, Debug)]
 pub struct Tuple {
-    pub items: Box<[Value]>
-}
\ No newline at end of file
+    pub items: Rc<[Value]>,
+}
+
+impl Tuple {
+    pub fn new(items: Rc<[Value]>) -> Self {
+        Tuple { items }
+    }
+}
+
+/// Tuple value typed after it's items, as in (Int, String).
+///
+impl From<Tuple> for Value {
+    fn from(tuple: Tuple) -> Self {
+        let types: Rc<[Type]> = tuple.items.iter().map(|item| item._type.clone()).collect();
+
+        Value {
+            value: Primitive::Tuple(tuple),
+            _type: Type::Tuple(Some(types)),
+            modifier: Modifier::Const,
+        }
+    }
+}
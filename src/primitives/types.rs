@@ -1,6 +1,11 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, collections::HashMap, fmt::{self, Debug, Display}, rc::Rc};
 
-use crate::primitives::value::Value;
+use crate::objects::string::IString;
+use crate::primitives::{primitive::Primitive, structs::Struct, value::Value};
+
+/// Concrete types bound to the type parameters of a generic instance or call.
+///
+pub type TypeArgs = HashMap<IString, Type>;
 
 /* Primitives are variable assigned data, Type is the contract for this data to be valid throught the runtime */
 #[derive(Default, Debug, Clone, PartialEq)]
@@ -10,18 +15,237 @@ pub enum Type {
     Bool,
     String,
     Struct,
-    Tuple,
-    Fn,
+    // Element types of (T1, T2), None stands for the bare `Tuple` annotation, which accepts any tuple
+    Tuple(Option<Rc<[Type]>>),
+    // None stands for the bare `Fn` annotation, which accepts any function
+    Fn(Option<Rc<Signature>>),
     // Dyn is resolved dynamically entirelly at VM's bytecode execution phase
     Dyn(Dyn),
     NativeFn,
     Closure,
     Ref(Rc<Type>),
+    // &mut T, reference which can be written through
+    MutRef(Rc<Type>),
+    // T?, binding which accepts T or nil
+    Optional(Rc<Type>),
+    Result,
+    // Instances of any struct with the trait methods: trait Shape { fn area(this) -> Float; }
+    Trait(Rc<Trait>),
+    // Type parameter of a generic struct or function, bound to a concrete type on each instance or call
+    Param(IString),
+    Nil,
     Void,
     #[default]
     UnInit,
 }
 
+impl Type {
+    /// Check if a value of type `other` can be assigned to a binding annotated as self.
+    ///
+    /// UnInit works as a wildcard, as it is the type of not annotated bindings.
+    ///
+    pub fn accepts(&self, other: &Type) -> bool {
+        match (self, other) {
+            (Type::UnInit, _) => true,
+            /* Unbound type parameters accept anything, bound ones are resolved before checking */
+            (Type::Param(_), _) => true,
+            (Type::Optional(_), Type::Nil) => true,
+            (Type::Optional(t), Type::Optional(o)) => t.accepts(o),
+            (Type::Optional(t), o) => t.accepts(o),
+            (Type::Fn(None), Type::Fn(_)) => true,
+            (Type::Tuple(None), Type::Tuple(_)) => true,
+            (Type::Tuple(Some(expected)), Type::Tuple(Some(found))) => {
+                expected.len() == found.len() && expected.iter().zip(found.iter()).all(|(e, f)| e.accepts(f))
+            }
+            /* Instances typed as a trait are still instances */
+            (Type::Struct, Type::Trait(_)) => true,
+            /* Mutable references can be used as shared ones */
+            (Type::Ref(t), Type::MutRef(o)) => t == o,
+            (Type::Fn(Some(expected)), Type::Fn(Some(found))) => expected.accepts(found),
+            (t, o) => t == o,
+        }
+    }
+
+    /// Check if a type parameter appears anywhere in self.
+    ///
+    pub fn is_generic(&self) -> bool {
+        match self {
+            Type::Param(_) => true,
+            Type::Tuple(Some(types)) => types.iter().any(Type::is_generic),
+            Type::Fn(Some(signature)) => signature.params.iter().any(Type::is_generic) || signature._return.is_generic(),
+            Type::Ref(t) | Type::MutRef(t) | Type::Optional(t) => t.is_generic(),
+            _ => false,
+        }
+    }
+
+    /// Replace type parameters by the types bound to them, unbound ones accept anything.
+    ///
+    pub fn resolve(&self, bindings: &TypeArgs) -> Type {
+        if !self.is_generic() {
+            return self.clone();
+        }
+
+        match self {
+            Type::Param(name) => bindings.get(name).cloned().unwrap_or(Type::UnInit),
+            Type::Tuple(Some(types)) => Type::Tuple(Some(types.iter().map(|t| t.resolve(bindings)).collect())),
+            Type::Fn(Some(signature)) => Type::Fn(Some(Rc::new(Signature {
+                params: signature.params.iter().map(|t| t.resolve(bindings)).collect(),
+                _return: signature._return.resolve(bindings),
+            }))),
+            Type::Ref(t) => Type::Ref(Rc::new(t.resolve(bindings))),
+            Type::MutRef(t) => Type::MutRef(Rc::new(t.resolve(bindings))),
+            Type::Optional(t) => Type::Optional(Rc::new(t.resolve(bindings))),
+            t => t.clone(),
+        }
+    }
+
+    /// Same as `accepts`, binding each type parameter to the type found on it's first occurrence.
+    ///
+    pub fn bind(&self, other: &Type, bindings: &mut TypeArgs) -> bool {
+        match (self, other) {
+            /* Not annotated types don't tell what the parameter is */
+            (Type::Param(_), Type::UnInit) => true,
+            (Type::Param(name), o) => match bindings.get(name) {
+                Some(bound) => bound.accepts(o),
+                None => {
+                    bindings.insert(name.clone(), o.clone());
+                    true
+                }
+            },
+            (Type::Optional(_), Type::Nil) => true,
+            (Type::Optional(t), Type::Optional(o)) => t.bind(o, bindings),
+            (Type::Optional(t), o) => t.bind(o, bindings),
+            (Type::Tuple(Some(expected)), Type::Tuple(Some(found))) => {
+                expected.len() == found.len() && expected.iter().zip(found.iter()).all(|(e, f)| e.bind(f, bindings))
+            }
+            (Type::Ref(t), Type::Ref(o) | Type::MutRef(o)) => t.bind(o, bindings),
+            (Type::MutRef(t), Type::MutRef(o)) => t.bind(o, bindings),
+            (Type::Fn(Some(expected)), Type::Fn(Some(found))) => {
+                expected.params.len() == found.params.len()
+                    && expected.params.iter().zip(found.params.iter()).all(|(e, f)| e.bind(f, bindings))
+                    && expected._return.bind(&found._return, bindings)
+            }
+            (t, o) => t.accepts(o),
+        }
+    }
+}
+
+/// Function parameters and return types.
+///
+/// An UnInit return type means the function return was not annotated, so it is not checked.
+///
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct Signature {
+    pub params: Vec<Type>,
+    pub _return: Type,
+}
+
+impl Signature {
+    /// Check if a function with `other` signature can be used where self is expected.
+    ///
+    pub fn accepts(&self, other: &Signature) -> bool {
+        self.params.len() == other.params.len()
+            && self.params.iter().zip(other.params.iter()).all(|(e, f)| e == f)
+            && self._return.accepts(&other._return)
+    }
+}
+
+impl Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "fn(")?;
+
+        for (i, param) in self.params.iter().enumerate() {
+            if i > 0 { write!(f, ", ")?; }
+            write!(f, "{param}")?;
+        }
+
+        write!(f, ")")?;
+
+        if self._return != Type::UnInit {
+            write!(f, " -> {}", self._return)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::Float => write!(f, "Float"),
+            Type::Int => write!(f, "Int"),
+            Type::Bool => write!(f, "Bool"),
+            Type::String => write!(f, "String"),
+            Type::Struct => write!(f, "Struct"),
+            Type::Tuple(None) => write!(f, "Tuple"),
+            Type::Tuple(Some(types)) => {
+                write!(f, "(")?;
+
+                for (i, t) in types.iter().enumerate() {
+                    if i > 0 { write!(f, ", ")?; }
+                    write!(f, "{t}")?;
+                }
+
+                /* Single element tuple keeps it's comma, as in (Int,) */
+                if types.len() == 1 { write!(f, ",")?; }
+
+                write!(f, ")")
+            }
+            Type::Fn(None) => write!(f, "Fn"),
+            Type::Fn(Some(signature)) => write!(f, "{signature}"),
+            Type::Dyn(_dyn) => match &_dyn.0.borrow().value {
+                Primitive::Struct(_struct) => write!(f, "{}", _struct.name),
+                _ => write!(f, "Dyn"),
+            },
+            Type::NativeFn => write!(f, "NativeFn"),
+            Type::Closure => write!(f, "Closure"),
+            Type::Ref(t) => write!(f, "&{t}"),
+            Type::MutRef(t) => write!(f, "&mut {t}"),
+            Type::Optional(t) => write!(f, "{t}?"),
+            Type::Result => write!(f, "Result"),
+            Type::Trait(t) => write!(f, "{}", t.name),
+            Type::Param(name) => write!(f, "{name}"),
+            Type::Nil => write!(f, "Nil"),
+            Type::Void => write!(f, "Void"),
+            Type::UnInit => write!(f, "UnInit"),
+        }
+    }
+}
+
+/// Methods, by name and signature, a struct must have to be used where the trait is expected.
+///
+#[derive(Clone, PartialEq)]
+pub struct Trait {
+    pub name: String,
+    pub methods: Vec<(IString, Rc<Signature>)>,
+}
+
+impl Trait {
+    /// First trait method missing from struct methods or having a different arity, described as an error.
+    ///
+    pub fn unimplemented(&self, _struct: &Struct) -> Option<String> {
+        self.methods.iter().find_map(|(name, signature)| {
+            let arity = match _struct.method(name).map(|method| method.value) {
+                Some(Primitive::Function(f)) => f.arity,
+                Some(Primitive::Closure(c)) => c._fn.arity,
+                _ => return Some(format!("{} does not implement '{}' of {}.", _struct.name, name, self.name)),
+            };
+
+            (arity != signature.params.len()).then(|| format!(
+                "Method '{}::{}' takes {} parameters but {} declares {}.",
+                _struct.name, name, arity, self.name, signature.params.len()
+            ))
+        })
+    }
+}
+
+/* Annotations errors print the trait and not every method of it */
+impl Debug for Trait {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
 #[derive(Default, Debug, Clone, PartialEq)]
 // type struct's (name, type)
 pub struct Dyn(pub Rc<RefCell<Value>>);
@@ -1,8 +1,8 @@
 use super::{
     primitive::Primitive,
-    types::{Modifier, Type},
+    types::{Modifier, Type, TypeArgs},
 };
-use std::{fmt::Display, ops::{Add, Div, Mul, Not}};
+use std::{fmt::Display, hash::{Hash, Hasher}, ops::{Add, Div, Mul, Not, Rem, Sub}, rc::Rc};
 
 /// All Asterisk Values definition.
 ///
@@ -19,6 +19,12 @@ impl Display for Value {
     }
 }
 
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.hash(state);
+    }
+}
+
 impl Default for Value {
     fn default() -> Self {
         Value {
@@ -29,5 +35,60 @@ impl Default for Value {
     }
 }
 
+impl Value {
+    /// Type-check value against a binding type, resolving closures to it's function signature.
+    ///
+    pub fn conforms_to(&self, t: &Type) -> bool {
+        match (&self.value, t) {
+            (Primitive::Closure(c), Type::Fn(_)) => {
+                t.accepts(&Type::Fn(Some(Rc::clone(&c._fn.signature))))
+            }
+            (Primitive::Closure(_), Type::Optional(inner)) => self.conforms_to(inner),
+            /* Items are checked one by one, as closures conform to their signature */
+            (Primitive::Tuple(tuple), Type::Tuple(Some(types))) => {
+                tuple.items.len() == types.len() && tuple.items.iter().zip(types.iter()).all(|(item, t)| item.conforms_to(t))
+            }
+            (Primitive::Tuple(_), Type::Optional(inner)) => self.conforms_to(inner),
+            /* Traits are structural, any instance with the trait methods conforms to it */
+            (Primitive::Instance(instance), Type::Trait(_trait)) => _trait.unimplemented(&instance._struct).is_none(),
+            (Primitive::Instance(_), Type::Optional(inner)) => self.conforms_to(inner),
+            /* Struct annotations only accept instances of the same declaration, whose methods are it's own */
+            (Primitive::Instance(instance), Type::Dyn(_dyn)) => {
+                matches!(&_dyn.0.borrow().value, Primitive::Struct(_struct) if _struct.methods == instance._struct.methods)
+            }
+            _ => t.accepts(&self._type),
+        }
+    }
+
+    /// Same as `conforms_to`, binding type parameters of t to the types found on value.
+    ///
+    pub fn binds_to(&self, t: &Type, bindings: &mut TypeArgs) -> bool {
+        if !t.is_generic() {
+            return self.conforms_to(t);
+        }
+
+        match (&self.value, t) {
+            (Primitive::Closure(c), Type::Fn(_)) => t.bind(&Type::Fn(Some(Rc::clone(&c._fn.signature))), bindings),
+            (Primitive::Tuple(tuple), Type::Tuple(Some(types))) => {
+                tuple.items.len() == types.len() && tuple.items.iter().zip(types.iter()).all(|(item, t)| item.binds_to(t, bindings))
+            }
+            (Primitive::Closure(_) | Primitive::Tuple(_), Type::Optional(inner)) => self.binds_to(inner, bindings),
+            _ => t.bind(&self._type, bindings),
+        }
+    }
+
+    /// Wrap value as a successful Result, e.g: returned by native functions.
+    ///
+    pub fn ok(v: Value) -> Value {
+        Value { value: Primitive::Result(Ok(Box::new(v))), _type: Type::Result, modifier: Modifier::Const }
+    }
+
+    /// Wrap value as a failed Result, so callers can recover from it.
+    ///
+    pub fn err(e: Value) -> Value {
+        Value { value: Primitive::Result(Err(Box::new(e))), _type: Type::Result, modifier: Modifier::Const }
+    }
+}
+
 crate::macros::gen_primitives_operations!(Float, Int);
 crate::macros::gen_values_operations!(Int, Float);
@@ -0,0 +1,84 @@
+use std::{
+    fmt::{self, Display},
+    fs::File,
+    io::BufReader,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    errors::vm::{InterpretResult, VmError},
+    utils::alloc::{allocations, counting},
+    vm::{profile::Profile, Vm},
+};
+
+/// Measures of a script run, see `bench_file`.
+///
+pub struct BenchReport {
+    pub name: String,
+    /// Compile and run time.
+    pub elapsed: Duration,
+    pub profile: Profile,
+    /// Allocations made by the run, always zero if `CountingAlloc` is not the global allocator.
+    pub allocations: u64,
+    pub allocated_bytes: u64,
+}
+
+impl BenchReport {
+    /// Executed instructions per second.
+    ///
+    pub fn ops_per_sec(&self) -> f64 {
+        self.profile.total() as f64 / self.elapsed.as_secs_f64()
+    }
+}
+
+impl Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {:.2}ms, {} instructions ({:.2}M ops/sec), ",
+            self.name,
+            self.elapsed.as_secs_f64() * 1000.0,
+            self.profile.total(),
+            self.ops_per_sec() / 1_000_000.0,
+        )?;
+
+        if counting() {
+            writeln!(f, "{} allocations ({} bytes)", self.allocations, self.allocated_bytes)?;
+        } else {
+            writeln!(f, "allocations not counted (build with the count-alloc feature)")?;
+        }
+
+        for (name, count) in self.profile.by_opcode() {
+            writeln!(f, "    {name:<16}{count:>12}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Compile and run script on a fresh Vm, collecting it's execution profile.
+///
+pub fn bench_file(path: &Path) -> Result<BenchReport, VmError> {
+    let file = File::open(path).map_err(|e| {
+        VmError::new(format!("Could not open {}: {e}", path.display()), InterpretResult::RuntimeError)
+    })?;
+
+    let mut vm = Vm { profile: Some(Profile::default()), ..Vm::default() };
+
+    let (allocations_before, bytes_before) = allocations();
+    let start = Instant::now();
+
+    vm.interpret(BufReader::new(file))?;
+
+    let elapsed = start.elapsed();
+    let (allocations_after, bytes_after) = allocations();
+
+    Ok(BenchReport {
+        name: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+        elapsed,
+        profile: vm.profile.unwrap_or_default(),
+        allocations: allocations_after - allocations_before,
+        allocated_bytes: bytes_after - bytes_before,
+    })
+}
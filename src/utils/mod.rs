@@ -2,6 +2,8 @@ use std::rc::Rc;
 
 use crate::primitives::{primitive::Primitive, types::Type};
 
+pub mod alloc;
+pub mod bench;
 pub mod hasher;
 pub mod print;
 
@@ -14,8 +16,10 @@ pub fn parse_type(p: &Primitive) -> Type {
         Primitive::String(_) => Type::String,
         Primitive::Bool(_) => Type::Bool,
         Primitive::Ref(t) => Type::Ref(Rc::new(t.borrow()._type.clone())),
-        Primitive::Function(_) => Type::Fn,
+        Primitive::Function(f) => Type::Fn(Some(Rc::clone(&f.signature))),
         Primitive::Struct(_) => Type::Struct,
+        Primitive::Result(_) => Type::Result,
+        Primitive::Nil => Type::Nil,
         _ => panic!("Error parsing type."),
     }
 }
@@ -1,13 +1,36 @@
-use crate::vm::chunk::Chunk;
+use crate::vm::chunk::{decode, Chunk, OpCode};
+use crate::vm::globals::SlotTable;
 use crate::vm::Stack;
 
 #[allow(unused)]
-pub fn disassemble_chunk(chunk: &Chunk, name: String) {
+pub fn disassemble_chunk(chunk: &Chunk, name: String, slots: &SlotTable) {
     println!("===%=== {} ===%===", name);
 
-    for (i, code) in chunk.code.iter().enumerate() {
-        print!("{i:0>4} ");
-        println!("{code:?}");
+    let mut ip = 0;
+
+    while ip < chunk.code.len() {
+        print!("{ip:0>4} ");
+
+        let (code, next) = decode(&chunk.code, ip);
+
+        match code {
+            OpCode::DefineGlobal(slot, _, t) => println!("{code:?} '{}' {}", slots.name(slot), chunk.types[t]),
+            OpCode::DefineLocal(_, _, t) => println!("{code:?} {}", chunk.types[t]),
+            OpCode::GetGlobal(slot)
+            | OpCode::SetGlobal(slot)
+            | OpCode::SetRefGlobal(slot, _) => println!("{code:?} '{}'", slots.name(slot)),
+            /* Jumps are shown with their target position */
+            OpCode::Jump(offset)
+            | OpCode::JumpIfFalse(offset)
+            | OpCode::JumpIfTrue(offset)
+            | OpCode::JumpIfNil(offset)
+            | OpCode::JumpIfNotNil(offset)
+            | OpCode::PushHandler(offset) => println!("{code:?} -> {:0>4}", next + offset),
+            OpCode::Loop(offset) => println!("{code:?} -> {:0>4}", next - offset),
+            _ => println!("{code:?}"),
+        }
+
+        ip = next;
     }
 }
 
@@ -15,7 +38,7 @@ pub fn print_stack(stack: &Stack) {
     println!("==stack-trace==");
     for value in stack.iter().rev() {
         print!(">");
-        println!("{}", &value.borrow().value);
+        println!("{}", &value.value);
     }
     println!("===end-trace===")
 }
@@ -2,7 +2,12 @@ use crate::primitives::{
     primitive::Primitive, types::{Modifier, Type}
 };
 
-#[derive(Debug, Clone, PartialEq)]
+/// Decoded instruction.
+///
+/// Encoded on chunk's code as an u8 opcode followed by it's operands: indices and counts are
+/// variable-width (LEB128), jump offsets are fixed u16 so they can be patched after being emitted.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum OpCode {
     Return,
     Constant(usize),
@@ -21,19 +26,42 @@ pub enum OpCode {
     Negate,
     Print,
     Nil,
-    DefineLocal(usize, Modifier, Type),
+    /// (slot, modifier, type annotation index)
+    DefineLocal(usize, Modifier, usize),
     SetLocal(usize, Modifier),
     GetLocal(usize),
-    SetRefLocal(usize),
-    DefineGlobal(usize, Modifier, Type),
+    /// (slot, modifier), Mut builds a mutable reference.
+    SetRefLocal(usize, Modifier),
+    /// Global slot, resolved at compile time.
+    DefineGlobal(usize, Modifier, usize),
     SetGlobal(usize),
     GetGlobal(usize),
-    SetRefGlobal(usize),
+    SetRefGlobal(usize, Modifier),
+    /// Jumps are relative to the end of the jump instruction.
     JumpIfFalse(usize),
     JumpIfTrue(usize),
+    /// Jump if value on top of stack is nil, without popping it.
+    JumpIfNil(usize),
+    /// Jump if value on top of stack is not nil, without popping it.
+    JumpIfNotNil(usize),
     Jump(usize),
     Loop(usize),
     Call(usize),
+    /// Register try block handler with catch branch offset.
+    PushHandler(usize),
+    PopHandler,
+    Throw,
+    /// Wrap value on top of stack into Ok / Err.
+    Ok,
+    Err,
+    /// Unwrap Ok or return Err from current function (postfix ?).
+    Propagate,
+    /// Replace Result on top of stack by if it matches the variant (true for Ok).
+    MatchResult(bool),
+    /// Replace Result on top of stack by it's inner value.
+    UnwrapResult,
+    /// Call with (args count, named args names' constant index), names are for the trailing args.
+    CallNamed(usize, usize),
     Closure,
     GetUpValue(usize),
     SetUpValue(usize),
@@ -41,21 +69,224 @@ pub enum OpCode {
     CreateInstance(usize),
     Tuple(usize),
     Access,
+    /// Replace value on top of stack by it's String representation (string interpolation).
+    Stringify,
+    /// Replace reference on top of stack by the referenced value.
+    Deref,
+    /// Write value on top of stack through the mutable reference below it.
+    SetDeref,
+    Modulo,
+    /// Push a copy of value on top of stack.
+    Dup,
+    /// Replace instance and value on top of stack by a copy of the instance with field (name constant index) set to value.
+    SetField(usize),
+    /// Replace tuple on top of stack by it's items (count), in order.
+    Unpack(usize),
+    /// CreateInstance taking fields left out from the instance on top of stack.
+    UpdateInstance(usize),
+    /// Replace struct and instance on top of stack by the fields (names' constant index), in order.
+    /// Unless the pattern ends with `..` (bool), every field must be named.
+    UnpackInstance(usize, bool),
+    /// Add function on top of stack as method (name constant index) of the struct below it.
+    Method(usize),
+    Subtract,
+    /// Explicit type arguments (types side table index) of the call or instance creation following it.
+    TypeArgs(usize),
+    /// Check the struct on top of stack has the methods of the trait (types side table index).
+    Implement(usize),
+}
+
+/// Encoded size of jump instructions: opcode + u16 offset.
+///
+pub const JUMP_SIZE: usize = 3;
+
+/// Count of encoded opcodes.
+///
+pub const OPCODE_COUNT: usize = 60;
+
+/// Opcodes names, indexed by their encoded byte.
+///
+pub const OPCODE_NAMES: [&str; OPCODE_COUNT] = [
+    "Return", "Constant", "True", "False", "Equal", "PartialEqual", "Pop", "Greater", "Less",
+    "Not", "Add", "Multiply", "Divide", "Negate", "Print", "Nil", "DefineLocal", "SetLocal",
+    "GetLocal", "SetRefLocal", "DefineGlobal", "SetGlobal", "GetGlobal", "SetRefGlobal",
+    "JumpIfFalse", "JumpIfTrue", "JumpIfNil", "JumpIfNotNil", "Jump", "Loop", "Call",
+    "PushHandler", "PopHandler", "Throw", "Ok", "Err", "Propagate", "MatchResult", "UnwrapResult",
+    "CallNamed", "Closure", "GetUpValue", "SetUpValue", "ParseStructDyn", "CreateInstance",
+    "Tuple", "Access", "Stringify", "Deref", "SetDeref",
+    "Modulo", "Dup", "SetField", "Unpack", "UpdateInstance", "UnpackInstance", "Method", "Subtract",
+    "TypeArgs", "Implement",
+];
+
+/// Encoded opcodes bytes, the Vm dispatches on them directly.
+///
+pub mod op {
+    pub const RETURN: u8 = 0;
+    pub const CONSTANT: u8 = 1;
+    pub const TRUE: u8 = 2;
+    pub const FALSE: u8 = 3;
+    pub const EQUAL: u8 = 4;
+    pub const PARTIAL_EQUAL: u8 = 5;
+    pub const POP: u8 = 6;
+    pub const GREATER: u8 = 7;
+    pub const LESS: u8 = 8;
+    pub const NOT: u8 = 9;
+    pub const ADD: u8 = 10;
+    pub const MULTIPLY: u8 = 11;
+    pub const DIVIDE: u8 = 12;
+    pub const NEGATE: u8 = 13;
+    pub const PRINT: u8 = 14;
+    pub const NIL: u8 = 15;
+    pub const DEFINE_LOCAL: u8 = 16;
+    pub const SET_LOCAL: u8 = 17;
+    pub const GET_LOCAL: u8 = 18;
+    pub const SET_REF_LOCAL: u8 = 19;
+    pub const DEFINE_GLOBAL: u8 = 20;
+    pub const SET_GLOBAL: u8 = 21;
+    pub const GET_GLOBAL: u8 = 22;
+    pub const SET_REF_GLOBAL: u8 = 23;
+    pub const JUMP_IF_FALSE: u8 = 24;
+    pub const JUMP_IF_TRUE: u8 = 25;
+    pub const JUMP_IF_NIL: u8 = 26;
+    pub const JUMP_IF_NOT_NIL: u8 = 27;
+    pub const JUMP: u8 = 28;
+    pub const LOOP: u8 = 29;
+    pub const CALL: u8 = 30;
+    pub const PUSH_HANDLER: u8 = 31;
+    pub const POP_HANDLER: u8 = 32;
+    pub const THROW: u8 = 33;
+    pub const OK: u8 = 34;
+    pub const ERR: u8 = 35;
+    pub const PROPAGATE: u8 = 36;
+    pub const MATCH_RESULT: u8 = 37;
+    pub const UNWRAP_RESULT: u8 = 38;
+    pub const CALL_NAMED: u8 = 39;
+    pub const CLOSURE: u8 = 40;
+    pub const GET_UP_VALUE: u8 = 41;
+    pub const SET_UP_VALUE: u8 = 42;
+    pub const PARSE_STRUCT_DYN: u8 = 43;
+    pub const CREATE_INSTANCE: u8 = 44;
+    pub const TUPLE: u8 = 45;
+    pub const ACCESS: u8 = 46;
+    pub const STRINGIFY: u8 = 47;
+    pub const DEREF: u8 = 48;
+    pub const SET_DEREF: u8 = 49;
+    pub const MODULO: u8 = 50;
+    pub const DUP: u8 = 51;
+    pub const SET_FIELD: u8 = 52;
+    pub const UNPACK: u8 = 53;
+    pub const UPDATE_INSTANCE: u8 = 54;
+    pub const UNPACK_INSTANCE: u8 = 55;
+    pub const METHOD: u8 = 56;
+    pub const SUBTRACT: u8 = 57;
+    pub const TYPE_ARGS: u8 = 58;
+    pub const IMPLEMENT: u8 = 59;
 }
 
 #[derive(Debug, Default, Clone)]
 pub struct Chunk {
-    /// The sequence of Bytecodes used to change the stack state.
-    pub code: Vec<OpCode>,
+    /// The sequence of encoded Bytecodes used to change the stack state.
+    pub code: Vec<u8>,
     /// Where values are saved before being used.
     pub constants: Vec<Primitive>,
+    /// Type annotations referenced by DefineLocal and DefineGlobal.
+    pub types: Vec<Type>,
 }
 
 impl Chunk {
-    /// Push to code vec.
+    /// Encode instruction at the end of code vec.
     ///
-    pub fn write(&mut self, byte: OpCode) {
-        self.code.push(byte);
+    pub fn write(&mut self, instruction: OpCode) {
+        let code = &mut self.code;
+
+        match instruction {
+            OpCode::Return => code.push(op::RETURN),
+            OpCode::Constant(idx) => operand(code, op::CONSTANT, idx),
+            OpCode::True => code.push(op::TRUE),
+            OpCode::False => code.push(op::FALSE),
+            OpCode::Equal => code.push(op::EQUAL),
+            OpCode::PartialEqual => code.push(op::PARTIAL_EQUAL),
+            OpCode::Pop => code.push(op::POP),
+            OpCode::Greater => code.push(op::GREATER),
+            OpCode::Less => code.push(op::LESS),
+            OpCode::Not => code.push(op::NOT),
+            OpCode::Add => code.push(op::ADD),
+            OpCode::Multiply => code.push(op::MULTIPLY),
+            OpCode::Divide => code.push(op::DIVIDE),
+            OpCode::Negate => code.push(op::NEGATE),
+            OpCode::Print => code.push(op::PRINT),
+            OpCode::Nil => code.push(op::NIL),
+            OpCode::DefineLocal(idx, modifier, t) => {
+                operand(code, op::DEFINE_LOCAL, idx);
+                code.push(modifier as u8);
+                write_varint(code, t);
+            }
+            OpCode::SetLocal(idx, modifier) => {
+                operand(code, op::SET_LOCAL, idx);
+                code.push(modifier as u8);
+            }
+            OpCode::GetLocal(idx) => operand(code, op::GET_LOCAL, idx),
+            OpCode::SetRefLocal(idx, modifier) => {
+                operand(code, op::SET_REF_LOCAL, idx);
+                code.push(modifier as u8);
+            }
+            OpCode::DefineGlobal(slot, modifier, t) => {
+                operand(code, op::DEFINE_GLOBAL, slot);
+                code.push(modifier as u8);
+                write_varint(code, t);
+            }
+            OpCode::SetGlobal(slot) => operand(code, op::SET_GLOBAL, slot),
+            OpCode::GetGlobal(slot) => operand(code, op::GET_GLOBAL, slot),
+            OpCode::SetRefGlobal(slot, modifier) => {
+                operand(code, op::SET_REF_GLOBAL, slot);
+                code.push(modifier as u8);
+            }
+            OpCode::JumpIfFalse(offset) => jump(code, op::JUMP_IF_FALSE, offset),
+            OpCode::JumpIfTrue(offset) => jump(code, op::JUMP_IF_TRUE, offset),
+            OpCode::JumpIfNil(offset) => jump(code, op::JUMP_IF_NIL, offset),
+            OpCode::JumpIfNotNil(offset) => jump(code, op::JUMP_IF_NOT_NIL, offset),
+            OpCode::Jump(offset) => jump(code, op::JUMP, offset),
+            OpCode::Loop(offset) => jump(code, op::LOOP, offset),
+            OpCode::Call(count) => operand(code, op::CALL, count),
+            OpCode::PushHandler(offset) => jump(code, op::PUSH_HANDLER, offset),
+            OpCode::PopHandler => code.push(op::POP_HANDLER),
+            OpCode::Throw => code.push(op::THROW),
+            OpCode::Ok => code.push(op::OK),
+            OpCode::Err => code.push(op::ERR),
+            OpCode::Propagate => code.push(op::PROPAGATE),
+            OpCode::MatchResult(ok) => {
+                code.push(op::MATCH_RESULT);
+                code.push(ok as u8);
+            }
+            OpCode::UnwrapResult => code.push(op::UNWRAP_RESULT),
+            OpCode::CallNamed(count, names) => {
+                operand(code, op::CALL_NAMED, count);
+                write_varint(code, names);
+            }
+            OpCode::Closure => code.push(op::CLOSURE),
+            OpCode::GetUpValue(idx) => operand(code, op::GET_UP_VALUE, idx),
+            OpCode::SetUpValue(idx) => operand(code, op::SET_UP_VALUE, idx),
+            OpCode::ParseStructDyn(count) => operand(code, op::PARSE_STRUCT_DYN, count),
+            OpCode::CreateInstance(count) => operand(code, op::CREATE_INSTANCE, count),
+            OpCode::Tuple(size) => operand(code, op::TUPLE, size),
+            OpCode::Access => code.push(op::ACCESS),
+            OpCode::Stringify => code.push(op::STRINGIFY),
+            OpCode::Deref => code.push(op::DEREF),
+            OpCode::SetDeref => code.push(op::SET_DEREF),
+            OpCode::Modulo => code.push(op::MODULO),
+            OpCode::Dup => code.push(op::DUP),
+            OpCode::SetField(name) => operand(code, op::SET_FIELD, name),
+            OpCode::Unpack(count) => operand(code, op::UNPACK, count),
+            OpCode::UpdateInstance(count) => operand(code, op::UPDATE_INSTANCE, count),
+            OpCode::UnpackInstance(names, rest) => {
+                operand(code, op::UNPACK_INSTANCE, names);
+                code.push(rest as u8);
+            }
+            OpCode::Method(name) => operand(code, op::METHOD, name),
+            OpCode::Subtract => code.push(op::SUBTRACT),
+            OpCode::TypeArgs(types) => operand(code, op::TYPE_ARGS, types),
+            OpCode::Implement(_trait) => operand(code, op::IMPLEMENT, _trait),
+        }
     }
 
     /// Push to constants vec.
@@ -64,4 +295,196 @@ impl Chunk {
         self.constants.push(value);
         self.constants.len() - 1
     }
+
+    /// Push to types side table, reusing already present annotations.
+    ///
+    pub fn write_type(&mut self, t: Type) -> usize {
+        match self.types.iter().position(|present| *present == t) {
+            Some(idx) => idx,
+            None => {
+                self.types.push(t);
+                self.types.len() - 1
+            }
+        }
+    }
+
+    /// Set offset of jump instruction starting at `at`.
+    ///
+    pub fn patch_jump(&mut self, at: usize, offset: u16) {
+        self.code[at + 1..at + JUMP_SIZE].copy_from_slice(&offset.to_le_bytes());
+    }
+}
+
+/// Decode instruction starting at `ip`, returning it along with the next instruction position.
+///
+/// Used by the disassembler, the Vm reads operands while dispatching on the opcode byte.
+///
+pub fn decode(code: &[u8], ip: usize) -> (OpCode, usize) {
+    let mut next = ip + 1;
+
+    let instruction = match code[ip] {
+        op::RETURN => OpCode::Return,
+        op::CONSTANT => OpCode::Constant(read_varint(code, &mut next)),
+        op::TRUE => OpCode::True,
+        op::FALSE => OpCode::False,
+        op::EQUAL => OpCode::Equal,
+        op::PARTIAL_EQUAL => OpCode::PartialEqual,
+        op::POP => OpCode::Pop,
+        op::GREATER => OpCode::Greater,
+        op::LESS => OpCode::Less,
+        op::NOT => OpCode::Not,
+        op::ADD => OpCode::Add,
+        op::MULTIPLY => OpCode::Multiply,
+        op::DIVIDE => OpCode::Divide,
+        op::NEGATE => OpCode::Negate,
+        op::PRINT => OpCode::Print,
+        op::NIL => OpCode::Nil,
+        op::DEFINE_LOCAL => {
+            let idx = read_varint(code, &mut next);
+            let modifier = read_modifier(code, &mut next);
+            OpCode::DefineLocal(idx, modifier, read_varint(code, &mut next))
+        }
+        op::SET_LOCAL => {
+            let idx = read_varint(code, &mut next);
+            OpCode::SetLocal(idx, read_modifier(code, &mut next))
+        }
+        op::GET_LOCAL => OpCode::GetLocal(read_varint(code, &mut next)),
+        op::SET_REF_LOCAL => {
+            let idx = read_varint(code, &mut next);
+            OpCode::SetRefLocal(idx, read_modifier(code, &mut next))
+        }
+        op::DEFINE_GLOBAL => {
+            let slot = read_varint(code, &mut next);
+            let modifier = read_modifier(code, &mut next);
+            OpCode::DefineGlobal(slot, modifier, read_varint(code, &mut next))
+        }
+        op::SET_GLOBAL => OpCode::SetGlobal(read_varint(code, &mut next)),
+        op::GET_GLOBAL => OpCode::GetGlobal(read_varint(code, &mut next)),
+        op::SET_REF_GLOBAL => {
+            let slot = read_varint(code, &mut next);
+            OpCode::SetRefGlobal(slot, read_modifier(code, &mut next))
+        }
+        op::JUMP_IF_FALSE => OpCode::JumpIfFalse(read_jump(code, &mut next)),
+        op::JUMP_IF_TRUE => OpCode::JumpIfTrue(read_jump(code, &mut next)),
+        op::JUMP_IF_NIL => OpCode::JumpIfNil(read_jump(code, &mut next)),
+        op::JUMP_IF_NOT_NIL => OpCode::JumpIfNotNil(read_jump(code, &mut next)),
+        op::JUMP => OpCode::Jump(read_jump(code, &mut next)),
+        op::LOOP => OpCode::Loop(read_jump(code, &mut next)),
+        op::CALL => OpCode::Call(read_varint(code, &mut next)),
+        op::PUSH_HANDLER => OpCode::PushHandler(read_jump(code, &mut next)),
+        op::POP_HANDLER => OpCode::PopHandler,
+        op::THROW => OpCode::Throw,
+        op::OK => OpCode::Ok,
+        op::ERR => OpCode::Err,
+        op::PROPAGATE => OpCode::Propagate,
+        op::MATCH_RESULT => OpCode::MatchResult(read_flag(code, &mut next)),
+        op::UNWRAP_RESULT => OpCode::UnwrapResult,
+        op::CALL_NAMED => {
+            let count = read_varint(code, &mut next);
+            OpCode::CallNamed(count, read_varint(code, &mut next))
+        }
+        op::CLOSURE => OpCode::Closure,
+        op::GET_UP_VALUE => OpCode::GetUpValue(read_varint(code, &mut next)),
+        op::SET_UP_VALUE => OpCode::SetUpValue(read_varint(code, &mut next)),
+        op::PARSE_STRUCT_DYN => OpCode::ParseStructDyn(read_varint(code, &mut next)),
+        op::CREATE_INSTANCE => OpCode::CreateInstance(read_varint(code, &mut next)),
+        op::TUPLE => OpCode::Tuple(read_varint(code, &mut next)),
+        op::ACCESS => OpCode::Access,
+        op::STRINGIFY => OpCode::Stringify,
+        op::DEREF => OpCode::Deref,
+        op::SET_DEREF => OpCode::SetDeref,
+        op::MODULO => OpCode::Modulo,
+        op::DUP => OpCode::Dup,
+        op::SET_FIELD => OpCode::SetField(read_varint(code, &mut next)),
+        op::UNPACK => OpCode::Unpack(read_varint(code, &mut next)),
+        op::UPDATE_INSTANCE => OpCode::UpdateInstance(read_varint(code, &mut next)),
+        op::UNPACK_INSTANCE => {
+            let names = read_varint(code, &mut next);
+            OpCode::UnpackInstance(names, read_flag(code, &mut next))
+        }
+        op::METHOD => OpCode::Method(read_varint(code, &mut next)),
+        op::SUBTRACT => OpCode::Subtract,
+        op::TYPE_ARGS => OpCode::TypeArgs(read_varint(code, &mut next)),
+        op::IMPLEMENT => OpCode::Implement(read_varint(code, &mut next)),
+        byte => panic!("Invalid opcode {byte} at {ip}."),
+    };
+
+    (instruction, next)
+}
+
+fn operand(code: &mut Vec<u8>, opcode: u8, value: usize) {
+    code.push(opcode);
+    write_varint(code, value);
+}
+
+fn jump(code: &mut Vec<u8>, opcode: u8, offset: usize) {
+    /* Parser checks patched and loop offsets range, anything else would wrap */
+    debug_assert!(offset <= u16::MAX as usize, "Jump offset {offset} out of u16 range.");
+
+    code.push(opcode);
+    code.extend_from_slice(&(offset as u16).to_le_bytes());
+}
+
+/// LEB128: 7 bits per byte, high bit set while there are more bytes.
+///
+fn write_varint(code: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        code.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+
+    code.push(value as u8);
+}
+
+#[inline(always)]
+pub fn read_opcode(code: &[u8], next: &mut usize) -> u8 {
+    *next += 1;
+
+    code[*next - 1]
+}
+
+#[inline(always)]
+pub fn read_varint(code: &[u8], next: &mut usize) -> usize {
+    let mut value = 0usize;
+    let mut shift = 0;
+
+    loop {
+        let byte = code[*next];
+        *next += 1;
+
+        value |= ((byte & 0x7f) as usize) << shift;
+
+        if byte & 0x80 == 0 {
+            return value;
+        }
+
+        shift += 7;
+    }
+}
+
+#[inline(always)]
+pub fn read_jump(code: &[u8], next: &mut usize) -> usize {
+    let offset = u16::from_le_bytes([code[*next], code[*next + 1]]);
+    *next += 2;
+
+    offset as usize
+}
+
+#[inline(always)]
+pub fn read_modifier(code: &[u8], next: &mut usize) -> Modifier {
+    let modifier = match code[*next] {
+        0 => Modifier::Unassigned,
+        1 => Modifier::Const,
+        _ => Modifier::Mut,
+    };
+    *next += 1;
+
+    modifier
+}
+
+#[inline(always)]
+pub fn read_flag(code: &[u8], next: &mut usize) -> bool {
+    *next += 1;
+
+    code[*next - 1] == 1
 }
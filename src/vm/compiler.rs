@@ -1,14 +1,27 @@
+use std::rc::Rc;
+
 use crate::parser::lexer::*;
 use crate::parser::Parser;
 use crate::primitives::functions::Function;
 use crate::primitives::functions::FunctionType;
+use crate::vm::globals::{Declarations, Slots};
 
-pub fn compile<T: std::io::Read>(source_code: T) -> Function {
+/// Compile a script, resolving it's globals to the given slots.
+///
+/// Constants, aliases, traits and struct type parameters declared by previous scripts are in scope,
+/// the ones declared by this script are kept once it compiled.
+///
+pub fn compile<T: std::io::Read>(source_code: T, slots: &Slots) -> Function {
     let lex = Lexer::new(source_code);
     /* Default app function, "main" so to speak. */
     let function = Function::default();
 
-    let mut parser = Parser::new(function, FunctionType::Script, lex);
+    let mut parser = Parser::new(function, FunctionType::Script, lex, Rc::clone(slots));
+
+    let Declarations { consts, types, struct_params } = slots.borrow().declarations.clone();
+    parser.consts = consts;
+    parser.types = types;
+    parser.struct_params = struct_params;
 
     parser.advance();
 
@@ -16,5 +29,13 @@ pub fn compile<T: std::io::Read>(source_code: T) -> Function {
         parser = parser.declaration();
     }
 
-    parser.end_compiler()
+    let function = parser.end_compiler();
+
+    slots.borrow_mut().declarations = Declarations {
+        consts: std::mem::take(&mut parser.consts),
+        types: std::mem::take(&mut parser.types),
+        struct_params: std::mem::take(&mut parser.struct_params),
+    };
+
+    function
 }
@@ -0,0 +1,334 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet},
+    rc::{Rc, Weak},
+};
+
+use crate::{
+    objects::string::StringObj,
+    primitives::{primitive::Primitive, structs::{Instance, Struct}, value::Value},
+};
+
+/// Tracked cells count which triggers the first collection.
+///
+pub const INITIAL_THRESHOLD: usize = 1024;
+
+/// Next threshold is survivors times this factor, so collections get rarer as the heap grows.
+///
+const GROWTH_FACTOR: usize = 2;
+
+/// Claimed allocations count which triggers the first prune of the dropped ones.
+///
+const INITIAL_PRUNE: usize = 1024;
+
+thread_local! {
+    /* Set by `gc()` native, which has no access to the Vm running it */
+    static REQUESTED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Ask the Vm running the current script for a collection once the native call returns.
+///
+pub fn request_collection() {
+    REQUESTED.set(true);
+}
+
+/// Take pending collection request.
+///
+pub fn take_request() -> bool {
+    REQUESTED.replace(false)
+}
+
+/// String, instance or tuple counted by the heap which claimed it, until it's dropped.
+///
+/// Allocations no Vm claimed (e.g: made by the host) are not counted, see `Heap::claim`.
+///
+#[derive(Debug)]
+enum Claim {
+    String(Weak<StringObj>),
+    Instance(Weak<Instance>),
+    Tuple(Weak<[Value]>),
+}
+
+impl Claim {
+    fn alive(&self) -> bool {
+        match self {
+            Claim::String(string) => string.strong_count() > 0,
+            Claim::Instance(instance) => instance.strong_count() > 0,
+            Claim::Tuple(items) => items.strong_count() > 0,
+        }
+    }
+}
+
+/// Collector counters, see `Heap::stats`.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GcStats {
+    pub collections: usize,
+    /// Cells freed by all collections.
+    pub collected: usize,
+    /// Tracked cells, including the ones already dropped since last collection.
+    pub tracked: usize,
+    /// Tracked cells count which triggers next collection.
+    pub next_collection: usize,
+    /// See `Heap::bytes`.
+    pub bytes: usize,
+}
+
+/// Mark-and-sweep collector of the cells created by the Vm (boxed locals, closures upvalues, globals).
+///
+/// Values are still reference counted, the collector only breaks cycles `Rc` can't free
+/// (e.g: a closure capturing itself, an instance referencing itself through a reference),
+/// clearing unreachable cells so their contents are dropped.
+///
+#[derive(Debug)]
+pub struct Heap {
+    cells: Vec<Weak<RefCell<Value>>>,
+    /// Minimum tracked cells count before collecting.
+    pub threshold: usize,
+    next_collection: usize,
+    collections: usize,
+    collected: usize,
+    /*
+        Allocations claimed by this heap by address, with their bytes.
+        Kept apart from the values, so strings stay plain immutable keys.
+    */
+    claimed: HashMap<usize, (Claim, usize)>,
+    /* Bytes of the claimed allocations, including the ones dropped since last prune */
+    claimed_bytes: usize,
+    next_prune: usize,
+}
+
+impl Default for Heap {
+    fn default() -> Self {
+        Heap {
+            cells: vec![],
+            threshold: INITIAL_THRESHOLD,
+            next_collection: 0,
+            collections: 0,
+            collected: 0,
+            claimed: HashMap::new(),
+            claimed_bytes: 0,
+            next_prune: INITIAL_PRUNE,
+        }
+    }
+}
+
+impl Heap {
+    /// Create a tracked cell.
+    ///
+    pub fn alloc(&mut self, value: Value) -> Rc<RefCell<Value>> {
+        let cell = Rc::new(RefCell::new(value));
+        self.cells.push(Rc::downgrade(&cell));
+
+        cell
+    }
+
+    pub fn should_collect(&self) -> bool {
+        self.cells.len() >= self.next_collection.max(self.threshold)
+    }
+
+    pub fn stats(&self) -> GcStats {
+        GcStats {
+            collections: self.collections,
+            collected: self.collected,
+            tracked: self.cells.len(),
+            next_collection: self.next_collection.max(self.threshold),
+            bytes: self.bytes(),
+        }
+    }
+
+    /// Bytes held by the live strings, instances and tuples claimed by this heap, wherever they are.
+    ///
+    pub fn bytes(&self) -> usize {
+        self.claimed.values().filter(|(claim, _)| claim.alive()).map(|(_, bytes)| bytes).sum()
+    }
+
+    /// Whether the claimed allocations still alive take more than max bytes.
+    ///
+    /// Dropped ones are only pruned from the running total once it exceeds max, or as claims pile up.
+    ///
+    pub fn exceeds(&mut self, max: usize) -> bool {
+        if self.claimed_bytes > max {
+            self.prune();
+        }
+
+        self.claimed_bytes > max
+    }
+
+    /// Whether bytes more would take the claimed allocations over max, e.g: before a string is built.
+    ///
+    pub fn would_exceed(&mut self, bytes: usize, max: usize) -> bool {
+        bytes > max || self.exceeds(max - bytes)
+    }
+
+    /// Count value allocations not claimed yet on this heap, along with the ones it holds.
+    ///
+    /// Contents of an already claimed instance or tuple were claimed with it, so they are not walked again.
+    ///
+    pub fn claim(&mut self, value: &Value) {
+        match &value.value {
+            Primitive::String(string) => {
+                let bytes = size_of::<StringObj>() + string.len();
+                let string = string.downgrade();
+
+                self.track(string.as_ptr() as usize, Claim::String(string), bytes);
+            }
+            Primitive::Instance(instance) => {
+                let bytes = size_of::<Instance>() + instance.values.len() * size_of::<Value>();
+                if !self.track(Rc::as_ptr(instance) as usize, Claim::Instance(Rc::downgrade(instance)), bytes) { return; }
+
+                instance.values.iter().for_each(|field| self.claim(field));
+            }
+            Primitive::Tuple(tuple) => {
+                let bytes = tuple.items.len() * size_of::<Value>();
+                if !self.track(tuple.items.as_ptr() as usize, Claim::Tuple(Rc::downgrade(&tuple.items)), bytes) { return; }
+
+                tuple.items.iter().for_each(|item| self.claim(item));
+            }
+            Primitive::Method(method) => {
+                self.claim(&method.receiver);
+                self.claim(&method.function);
+            }
+            Primitive::Result(Ok(inner)) | Primitive::Result(Err(inner)) => self.claim(inner),
+            _ => (),
+        }
+    }
+
+    /// Count allocation at address, returning false if it was already claimed.
+    ///
+    /// Weak handles keep the address of a dropped allocation from being reused until it's pruned.
+    ///
+    fn track(&mut self, address: usize, claim: Claim, bytes: usize) -> bool {
+        if self.claimed.contains_key(&address) { return false; }
+
+        self.claimed.insert(address, (claim, bytes));
+        self.claimed_bytes += bytes;
+
+        if self.claimed.len() >= self.next_prune {
+            self.prune();
+            self.next_prune = (self.claimed.len() * GROWTH_FACTOR).max(INITIAL_PRUNE);
+        }
+
+        true
+    }
+
+    /// Forget dropped allocations, taking their bytes out of the running total.
+    ///
+    fn prune(&mut self) {
+        self.claimed.retain(|_, (claim, _)| claim.alive());
+        self.claimed_bytes = self.claimed.values().map(|(_, bytes)| bytes).sum();
+    }
+
+    /// Free tracked cells unreachable from `roots` and `root_cells`, returning the freed count.
+    ///
+    /// Cells held outside of the traced graph (e.g: by the host, by natives) are roots as well:
+    /// a cell with more strong references than the ones found on other tracked cells is kept alive.
+    ///
+    /// Must only run between instructions, when every value in use is reachable from the roots.
+    ///
+    pub fn collect<'a>(
+        &mut self,
+        roots: impl Iterator<Item = &'a Value>,
+        root_cells: impl Iterator<Item = &'a Rc<RefCell<Value>>>,
+    ) -> usize {
+        let live: Vec<Rc<RefCell<Value>>> = self.cells.iter().filter_map(Weak::upgrade).collect();
+
+        /* References to each tracked cell coming from the tracked cells themselves */
+        let mut internal: HashMap<usize, usize> = HashMap::new();
+        let mut seen = HashSet::new();
+
+        for cell in live.iter() {
+            trace(&cell.borrow(), &mut seen, &mut |inner| {
+                *internal.entry(address(inner)).or_default() += 1;
+            });
+        }
+
+        let mut pending: Vec<Rc<RefCell<Value>>> = root_cells.map(Rc::clone).collect();
+        let mut seen = HashSet::new();
+
+        for value in roots {
+            trace(value, &mut seen, &mut |inner| pending.push(Rc::clone(inner)));
+        }
+
+        /* Not counting the reference held by `live` */
+        for cell in live.iter() {
+            if Rc::strong_count(cell) - 1 > internal.get(&address(cell)).copied().unwrap_or(0) {
+                pending.push(Rc::clone(cell));
+            }
+        }
+
+        let mut marked = HashSet::new();
+
+        while let Some(cell) = pending.pop() {
+            if !marked.insert(address(&cell)) { continue; }
+
+            trace(&cell.borrow(), &mut seen, &mut |inner| pending.push(Rc::clone(inner)));
+        }
+
+        /* Take unreachable contents before dropping any of them, as it may free other garbage cells */
+        let (survivors, garbage): (Vec<_>, Vec<_>) = live.into_iter().partition(|cell| marked.contains(&address(cell)));
+        let freed: Vec<Value> = garbage.iter().map(|cell| cell.take()).collect();
+        let count = freed.len();
+
+        drop(freed);
+
+        self.cells = survivors.iter().map(Rc::downgrade).collect();
+        self.next_collection = survivors.len() * GROWTH_FACTOR;
+        self.collections += 1;
+        self.collected += count;
+
+        count
+    }
+}
+
+fn address(cell: &Rc<RefCell<Value>>) -> usize {
+    Rc::as_ptr(cell) as usize
+}
+
+/// Visit the cells directly referenced by value, walking through instances, tuples, results and closures.
+///
+/// Shared containers are walked once, `seen` holds their addresses.
+///
+fn trace(value: &Value, seen: &mut HashSet<usize>, visit: &mut impl FnMut(&Rc<RefCell<Value>>)) {
+    match &value.value {
+        Primitive::Ref(cell) | Primitive::Cell(cell) => visit(cell),
+        Primitive::Closure(closure) => {
+            if !seen.insert(Rc::as_ptr(closure) as usize) { return; }
+
+            closure.upvalues.iter().for_each(visit);
+        }
+        Primitive::Instance(instance) => {
+            if !seen.insert(Rc::as_ptr(instance) as usize) { return; }
+
+            for field in instance.values.iter() {
+                trace(field, seen, visit);
+            }
+
+            trace_methods(&instance._struct, seen, visit);
+        }
+        Primitive::Struct(_struct) => trace_methods(_struct, seen, visit),
+        Primitive::Method(method) => {
+            trace(&method.receiver, seen, visit);
+            trace(&method.function, seen, visit);
+        }
+        Primitive::Tuple(tuple) => {
+            if !seen.insert(tuple.items.as_ptr() as usize) { return; }
+
+            for item in tuple.items.iter() {
+                trace(item, seen, visit);
+            }
+        }
+        Primitive::Result(Ok(inner)) | Primitive::Result(Err(inner)) => trace(inner, seen, visit),
+        _ => (),
+    }
+}
+
+/// Methods may be closures, capturing cells of the function the impl block is in.
+///
+fn trace_methods(_struct: &Struct, seen: &mut HashSet<usize>, visit: &mut impl FnMut(&Rc<RefCell<Value>>)) {
+    if !seen.insert(Rc::as_ptr(&_struct.methods.0) as usize) { return; }
+
+    for method in _struct.methods.0.borrow().values() {
+        trace(method, seen, visit);
+    }
+}
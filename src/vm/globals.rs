@@ -0,0 +1,104 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::primitives::{primitive::Primitive, types::Type, value::Value};
+
+/// Name -> slot side table, owned by a Vm and shared with the compiler of every script it runs,
+/// so slots resolved at compile time stay valid across scripts run by the same Vm (e.g: REPL lines, embedding).
+///
+pub type Slots = Rc<RefCell<SlotTable>>;
+
+#[derive(Debug, Default)]
+pub struct SlotTable {
+    slots: HashMap<String, usize>,
+    names: Vec<String>,
+    /// Script level declarations of the scripts compiled so far.
+    pub declarations: Declarations,
+}
+
+/// Declarations only existing at compile time, kept for the next scripts to use like globals.
+///
+#[derive(Debug, Default, Clone)]
+pub struct Declarations {
+    pub consts: HashMap<String, Primitive>,
+    /// Aliases, traits and structs.
+    pub types: HashMap<String, Type>,
+    /// Type parameters of structs, for impl blocks.
+    pub struct_params: HashMap<String, Vec<String>>,
+}
+
+impl SlotTable {
+    /// Get global name slot, assigning a new one on it's first use.
+    ///
+    /// Names are resolved on first mention, so forward references (e.g: a function calling another declared below it)
+    /// get a late-bound slot, which is only filled when the global is defined.
+    ///
+    pub fn resolve(&mut self, name: &str) -> usize {
+        if let Some(slot) = self.slots.get(name) {
+            return *slot;
+        }
+
+        let slot = self.names.len();
+        self.slots.insert(name.to_string(), slot);
+        self.names.push(name.to_string());
+
+        slot
+    }
+
+    /// Get slot of an already resolved global name.
+    ///
+    pub fn lookup(&self, name: &str) -> Option<usize> {
+        self.slots.get(name).copied()
+    }
+
+    /// Get global name of a slot, used by error messages and disassembler.
+    ///
+    pub fn name(&self, slot: usize) -> String {
+        self.names.get(slot).cloned().unwrap_or_else(|| format!("<global {slot}>"))
+    }
+}
+
+/// Global variables storage, indexed by compile-time resolved slots.
+///
+/// Empty slots are globals referenced by code but not defined (yet).
+///
+#[derive(Debug, Default)]
+pub struct Globals {
+    values: Vec<Option<Rc<RefCell<Value>>>>,
+    pub slots: Slots,
+}
+
+impl Globals {
+    /// Get global by name.
+    ///
+    pub fn get(&self, name: &str) -> Option<Rc<RefCell<Value>>> {
+        let slot = self.slots.borrow().lookup(name)?;
+        self.get_slot(slot)
+    }
+
+    /// Define global by name, returning true if it was not defined before.
+    ///
+    pub fn insert(&mut self, name: &str, value: Value) -> bool {
+        let slot = self.slots.borrow_mut().resolve(name);
+        self.set_slot(slot, Rc::new(RefCell::new(value)))
+    }
+
+    /// Defined globals cells.
+    ///
+    pub fn cells(&self) -> impl Iterator<Item = &Rc<RefCell<Value>>> {
+        self.values.iter().flatten()
+    }
+
+    pub fn get_slot(&self, slot: usize) -> Option<Rc<RefCell<Value>>> {
+        self.values.get(slot)?.as_ref().map(Rc::clone)
+    }
+
+    /// Fill global slot, returning true if it was empty.
+    ///
+    pub fn set_slot(&mut self, slot: usize, value: Rc<RefCell<Value>>) -> bool {
+        if slot >= self.values.len() {
+            self.values.resize(slot + 1, None);
+        }
+
+        self.values[slot].replace(value).is_none()
+    }
+}
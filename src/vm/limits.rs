@@ -0,0 +1,18 @@
+/// Sandbox limits of a Vm, all unlimited by default.
+///
+/// Exceeding any limit raises an error catchable by script's try blocks.
+/// Running out of fuel also suspends the script, returning `InterpretResult::OutOfFuel`
+/// to the host, which can `Vm::refuel` and `Vm::resume` it: where it stopped if uncaught, on the catch block otherwise.
+///
+#[derive(Debug, Clone, Default)]
+pub struct Limits {
+    /// Instructions left to execute.
+    pub fuel: Option<u64>,
+    /// Maximum call frames, including the script's one.
+    pub max_frames: Option<usize>,
+    /// Maximum values on stack.
+    pub max_stack: Option<usize>,
+    /// Maximum bytes held by the strings, instances and tuples the Vm made or was handed, see `Heap::bytes`.
+    /// They are only counted while it's set, and strings built by `+` or natives such as `repeat` are checked before being allocated.
+    pub max_heap_bytes: Option<usize>,
+}
@@ -1,45 +1,101 @@
 pub mod chunk;
 pub mod compiler;
+pub mod gc;
+pub mod globals;
+pub mod limits;
+pub mod profile;
+pub mod render;
 
+use std::borrow::Cow;
 use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::rc::Rc;
 #[allow(unused)]
 use std::time::Duration;
 
 use crate::errors::vm::{InterpretResult, VmError};
-use crate::objects::hash_table::HashTable;
-use crate::primitives::native::_typeof;
-use crate::primitives::functions::{Closure, NativeFn};
+use crate::objects::string::{sweep_strings, IString, StringObj};
+use crate::primitives::native::{_typeof, gc, hash};
+use crate::primitives::functions::{Closure, Deferred, Method, NativeCall, NativeFn};
 use crate::primitives::primitive::Primitive;
-use crate::primitives::structs::{Instance};
+use crate::primitives::strings;
+use crate::primitives::structs::{Instance, Methods, Struct};
 use crate::primitives::tuple::Tuple;
 use crate::primitives::types::Dyn;
 use crate::primitives::{
     functions::Function,
-    types::{Modifier, Type},
+    types::{Modifier, Type, TypeArgs},
     value::Value,
 };
 use crate::utils::parse_type;
 #[allow(unused)]
 use crate::utils::print::print_stack;
-use crate::vm::chunk::OpCode;
+use crate::vm::chunk::{op, read_flag, read_jump, read_modifier, read_opcode, read_varint, OPCODE_NAMES};
 use crate::vm::compiler::compile;
+use crate::vm::gc::{take_request, GcStats, Heap};
+use crate::vm::globals::Globals;
+use crate::vm::limits::Limits;
+use crate::vm::profile::Profile;
+use crate::vm::render::Render;
 use crate::{errors::vm::VmResult, primitives::native::duration};
 
-pub type Stack = Vec<Rc<RefCell<Value>>>;
+/// Values are stored inline, locals referenced by `&` or captured by closures are boxed on their slot.
+///
+pub type Stack = Vec<Value>;
 pub struct Vm {
     pub frames: Vec<CallFrame>,
     pub stack: Stack,
-    pub globals: HashTable<String, Value>,
-    pub strings: HashTable<String, String>,
+    pub globals: Globals,
+    /* Active try blocks, innermost last */
+    pub handlers: Vec<Handler>,
+    /* Execution counters, only collected when set */
+    pub profile: Option<Profile>,
+    /* Cells created by running scripts, collected when unreachable */
+    pub heap: Heap,
+    pub limits: Limits,
+    /* Printed or interpolated values waiting on `to_string` methods, innermost last */
+    pub renders: Vec<Render>,
 }
 
 #[derive(Debug)]
 pub struct CallFrame {
     pub function: Rc<Function>,
-    pub ip: *const OpCode,
+    /* Position of next instruction on function's code */
+    pub ip: usize,
     /* Init of frame function arg variables scope range */
     pub arg_offset: usize,
+    /* Captured variables of called closure */
+    pub upvalues: Vec<Rc<RefCell<Value>>>,
+    /* Set on methods called by the Vm itself, finishing the instruction which called them */
+    pub hook: Option<Hook>,
+    /* Types bound to the type parameters of a generic function */
+    pub type_args: TypeArgs,
+}
+
+/// What to do with the value returned by a method the Vm called on it's own, e.g: `to_string` when printing.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Hook {
+    /// Returned value must be a String, filling it's gap on the innermost `Render`.
+    Render,
+    /// Returned value must be a Bool, as returned by `eq`, `lt` and `gt`.
+    Bool,
+    /// Turn the Int returned by `cmp` into the result of `<` (Less) or `>` (Greater).
+    Compare(Ordering),
+    /// Returned value must be an Int, as returned by `hash`.
+    Hash,
+}
+
+/// Where to resume execution when a value is thrown inside a try block.
+///
+#[derive(Debug)]
+pub struct Handler {
+    /* Frames count when try block was entered */
+    pub frame_depth: usize,
+    /* Stack length when try block was entered */
+    pub stack_len: usize,
+    pub catch_ip: usize,
 }
 
 impl Default for Vm {
@@ -47,8 +103,12 @@ impl Default for Vm {
         Self {
             frames: Vec::default(),
             stack: Vec::default(),
-            globals: HashTable::default(),
-            strings: HashTable::default(),
+            globals: Globals::default(),
+            handlers: Vec::default(),
+            profile: None,
+            heap: Heap::default(),
+            limits: Limits::default(),
+            renders: Vec::default(),
         }
     }
 }
@@ -56,10 +116,19 @@ impl Default for Vm {
 impl Vm {
     /// This function is the "compiler" itself, running chunk's Bytecodes.
     ///
-    pub fn interpret<T: std::io::Read>(&mut self, source_code: T) {
+    /// Errors not handled by script's try blocks are returned to the caller.
+    ///
+    /// A script suspended by running out of fuel is discarded.
+    ///
+    pub fn interpret<T: std::io::Read>(&mut self, source_code: T) -> VmResult {
+        self.frames.clear();
+        self.stack.clear();
+        self.handlers.clear();
+        self.renders.clear();
+
         self.init_std_lib();
 
-        let main = compile(source_code);
+        let main = compile(source_code, &self.globals.slots);
 
         self.call(Rc::new(main), 0);
 
@@ -69,41 +138,183 @@ impl Vm {
             self.frames.last_mut().unwrap().function.chunk.constants
         );
 
-        match self.run() {
-            Err(e) => panic!("{:?}", e),
+        self.resume()
+    }
+
+    /// Continue running the script, e.g: after it ran out of fuel.
+    ///
+    pub fn resume(&mut self) -> VmResult {
+        let result = self.run();
+
+        /* Drop what was left by the failed script, so the Vm can be reused (e.g: by REPL) */
+        match &result {
+            Err(e) if e._type != InterpretResult::OutOfFuel => {
+                self.frames.clear();
+                self.stack.clear();
+                self.handlers.clear();
+                self.renders.clear();
+            }
             _ => (),
         }
+
+        result
+    }
+
+    /// Add instructions to fuel budget, only meaningful if `limits.fuel` is set.
+    ///
+    pub fn refuel(&mut self, fuel: u64) {
+        if let Some(left) = &mut self.limits.fuel {
+            *left += fuel;
+        }
     }
 
     pub fn init_std_lib(&mut self) {
+        self.define_native("duration", 0, duration);
+        self.globals.insert(&String::from("Error"), Self::error_blueprint().into());
+        self.define_native("typeof", 1, _typeof);
+        self.define_native("gc", 0, gc);
+
+        for (name, arity, _fn) in strings::NATIVES {
+            self.define_native_fn(NativeFn { name, arity, _fn, deferred: None, allocates: strings::allocates(name) });
+        }
+
+        /* Calling instance methods back */
+        self.define_deferred("hash", 1, hash, Deferred::Hash);
+        self.define_deferred("join", 2, strings::join, Deferred::Join);
+    }
+
+    fn define_native(&mut self, name: &'static str, arity: usize, _fn: NativeCall) {
+        self.define_native_fn(NativeFn { name, arity, _fn, deferred: None, allocates: None });
+    }
+
+    fn define_deferred(&mut self, name: &'static str, arity: usize, _fn: NativeCall, deferred: Deferred) {
+        self.define_native_fn(NativeFn { name, arity, _fn, deferred: Some(deferred), allocates: strings::allocates(name) });
+    }
+
+    fn define_native_fn(&mut self, native: NativeFn) {
         self.globals.insert(
-            &String::from("duration"),
+            native.name,
             Value {
-                value: Primitive::NativeFunction(NativeFn {
-                    arity: 0,
-                    _fn: duration,
-                }),
+                value: Primitive::NativeFunction(native),
                 _type: Type::NativeFn,
                 modifier: Modifier::Const,
             },
         );
+    }
 
-        self.globals.insert(
-            &String::from("typeof"),
-            Value {
-                value: Primitive::NativeFunction(NativeFn {
-                    arity: 1,
-                    _fn: _typeof,
-                }),
-                _type: Type::NativeFn,
-                modifier: Modifier::Const,
-            }
-        );
+    /// Free cells unreachable from stack, frames and globals, returning the freed count.
+    ///
+    /// Interned strings no longer referenced are dropped as well.
+    ///
+    pub fn collect_garbage(&mut self) -> usize {
+        let upvalues = self.frames.iter().flat_map(|frame| frame.upvalues.iter());
+        let collected = self.heap.collect(self.stack.iter(), upvalues.chain(self.globals.cells()));
+
+        sweep_strings();
+
+        collected
+    }
+
+    pub fn gc_stats(&self) -> GcStats {
+        self.heap.stats()
     }
 
+    /// Run until all frames return, handling errors with the innermost try block.
+    ///
+    /// Uncaught errors are returned to the host.
+    ///
     pub fn run(&mut self) -> VmResult {
         while self.frames.len() > 0 {
-            self.exec_code()?;
+            if let Some(fuel) = &mut self.limits.fuel {
+                if *fuel == 0 {
+                    return self.out_of_fuel();
+                }
+
+                *fuel -= 1;
+            }
+
+            if let Err(e) = self.check_limits().and_then(|_| self.exec_code()) {
+                let error = self.error_value(e.message.clone(), &e._type);
+                self.throw(error, e)?;
+            }
+
+            if self.heap.should_collect() {
+                self.collect_garbage();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Suspend the script, handing it's innermost try block the error first if any.
+    ///
+    /// A caught error leaves the script stopped at the start of it's catch block, which runs once it's resumed.
+    ///
+    fn out_of_fuel(&mut self) -> VmResult {
+        let out_of_fuel = || VmError::new("Out of fuel.".to_string(), InterpretResult::OutOfFuel);
+
+        let error = self.error_value(out_of_fuel().message, &InterpretResult::OutOfFuel);
+        self.throw(error, out_of_fuel())?;
+
+        Err(out_of_fuel())
+    }
+
+    /// Fail before allocating bytes which would take the heap over it's limit, e.g: building a string.
+    ///
+    fn reserve(&mut self, bytes: usize) -> VmResult {
+        let Some(max) = self.limits.max_heap_bytes else { return Ok(()) };
+
+        if self.heap.would_exceed(bytes, max) {
+            /* Cycles may be holding the memory */
+            self.collect_garbage();
+        }
+
+        if self.heap.would_exceed(bytes, max) {
+            return Err(Self::out_of_memory(max));
+        }
+
+        Ok(())
+    }
+
+    fn out_of_memory(max: usize) -> VmError {
+        VmError::new(format!("Maximum heap size of {max} bytes exceeded."), InterpretResult::OutOfMemory)
+    }
+
+    /// Check call depth, stack and heap usage against limits.
+    ///
+    fn check_limits(&mut self) -> VmResult {
+        if let Some(max) = self.limits.max_frames {
+            if self.frames.len() > max {
+                return Err(VmError::new(
+                    format!("Maximum call depth of {max} exceeded."),
+                    InterpretResult::CallDepthExceeded,
+                ));
+            }
+        }
+
+        if let Some(max) = self.limits.max_stack {
+            if self.stack.len() > max {
+                return Err(VmError::new(
+                    format!("Maximum stack size of {max} exceeded."),
+                    InterpretResult::StackOverflow,
+                ));
+            }
+        }
+
+        if let Some(max) = self.limits.max_heap_bytes {
+            /* Values made by last instruction are left on top of stack */
+            if let Some(value) = self.stack.last() {
+                self.heap.claim(value);
+            }
+
+            if self.heap.exceeds(max) {
+                /* Cycles may be holding the memory */
+                self.collect_garbage();
+            }
+
+            if self.heap.exceeds(max) {
+                return Err(Self::out_of_memory(max));
+            }
         }
 
         Ok(())
@@ -118,41 +329,39 @@ impl Vm {
                 "current frame: {:?}",
                 self.frames.last().unwrap().function.name
             );
-            println!("current code: {:?}", unsafe {
-                self.frames.last().unwrap().ip.read()
-            });
+            let frame = self.frames.last().unwrap();
+            println!("current code: {:?}", crate::vm::chunk::decode(&frame.function.chunk.code, frame.ip).0);
         }
 
         #[cfg(feature = "delay-exec")]
         std::thread::sleep(Duration::from_secs(1));
 
-        match unsafe { self.frames.last().unwrap().ip.read() } {
-            OpCode::Return => {
-                let _return = self.stack.pop().ok_or(VmError::new(
+        let instruction = self.read(read_opcode);
+
+        if let Some(profile) = &mut self.profile {
+            profile.instructions[instruction as usize] += 1;
+        }
+
+        match instruction {
+            op::RETURN => {
+                let _return = self.stack.pop().ok_or_else(|| VmError::new(
                     "Could not return from function".to_string(),
                     InterpretResult::CompilerError,
                 ))?;
 
-                let last_frame = self.frames.pop().unwrap();
-                let last_frame_args = last_frame.function.arity;
+                return self.return_from_frame(_return);
+            }
+            op::NEGATE => {
+                let n = self.stack.pop().unwrap();
 
-                /* Sanitize locals after frame is dropped */
-                for _ in 0..last_frame_args {
-                    self.stack.pop();
+                if let Some(neg) = Self::method(&n, "neg") {
+                    return self.call_method(neg, vec![n], None);
                 }
 
-                if self.frames.len() == 0 {
-                    return Ok(());
+                if let Primitive::Int(i64::MIN) = n.value {
+                    return self.error(format!("Integer overflow in -{}.", i64::MIN));
                 }
 
-                unsafe { self.advance_ip() }
-                self.stack.push(_return);
-
-                return Ok(());
-            }
-            OpCode::Negate => {
-                let n = self.stack.pop().unwrap().take();
-
                 match n {
                     Value {
                         _type: Type::Bool, ..
@@ -163,7 +372,7 @@ impl Vm {
                     | Value {
                         _type: Type::Int, ..
                     } => {
-                        self.stack.push(Rc::new(RefCell::new(!n)));
+                        self.stack.push(!n);
                         VmResult::Ok(())?
                     }
                     _ => VmResult::Err(VmError::new(
@@ -172,72 +381,87 @@ impl Vm {
                     ))?,
                 }
             }
-            OpCode::Not => {
-                let to_be_negated = self.stack.last().unwrap().take();
+            op::NOT => {
+                if let Some(not) = Self::method(self.stack.last().unwrap(), "not") {
+                    let value = self.stack.pop().unwrap();
+                    return self.call_method(not, vec![value], None);
+                }
 
-                match to_be_negated {
-                    Value {
-                        value: Primitive::Bool(value),
-                        ..
-                    } => {
-                        self.stack.last().unwrap().borrow_mut().value = Primitive::Bool(!value)
+                let to_be_negated = self.stack.last_mut().unwrap();
+
+                match to_be_negated.value {
+                    Primitive::Bool(value) => to_be_negated.value = Primitive::Bool(!value),
+                    _ => {
+                        let message = format!("Cannot apply '!' to {}.", to_be_negated._type);
+                        self.error(message)?
                     }
-                    _ => panic!("Value should be a boolean."),
                 };
             }
-            OpCode::Add => self.binary_op("+")?,
-            OpCode::Multiply => self.binary_op("*")?,
-            OpCode::Divide => self.binary_op("/")?,
-            OpCode::True => {
-                self.stack.push(Rc::new(RefCell::new(Value {
+            op::ADD => self.binary_op("+")?,
+            op::SUBTRACT => self.binary_op("-")?,
+            op::MULTIPLY => self.binary_op("*")?,
+            op::DIVIDE => self.binary_op("/")?,
+            op::TRUE => {
+                self.stack.push(Value {
                     value: Primitive::Bool(true),
                     modifier: Modifier::Unassigned,
                     _type: Type::Bool,
-                })));
+                });
             }
-            OpCode::False => {
-                self.stack.push(Rc::new(RefCell::new(Value {
+            op::FALSE => {
+                self.stack.push(Value {
                     value: Primitive::Bool(false),
                     modifier: Modifier::Unassigned,
                     _type: Type::Bool,
-                })));
+                });
             }
-            OpCode::Equal => {
+            op::EQUAL => {
                 let a = self.stack.pop().unwrap();
                 let b = self.stack.pop().unwrap();
 
-                self.stack.push(Rc::new(RefCell::new(Value {
+                if let Some(eq) = Self::method(&b, "eq") {
+                    return self.call_method(eq, vec![b, a], Some(Hook::Bool));
+                }
+
+                self.stack.push(Value {
                     value: Primitive::Bool(a == b),
                     modifier: Modifier::Unassigned,
                     _type: Type::Bool,
-                })));
+                });
             }
-            OpCode::PartialEqual => {
+            op::PARTIAL_EQUAL => {
                 let a = self.stack.pop().unwrap();
-                let b = self.stack.pop().unwrap();
+                let b = self.stack.last().unwrap();
 
-                self.stack.push(Rc::clone(&b));
-                self.stack.push(Rc::new(RefCell::new(Value {
-                    value: Primitive::Bool(a == b),
+                let equal = a == *b;
+
+                self.stack.push(Value {
+                    value: Primitive::Bool(equal),
                     modifier: Modifier::Unassigned,
                     _type: Type::Bool,
-                })));
+                });
             }
-            OpCode::Greater => self.binary_op(">")?,
-            OpCode::Less => self.binary_op("<")?,
-            OpCode::Print => {
+            op::GREATER => self.binary_op(">")?,
+            op::LESS => self.binary_op("<")?,
+            op::PRINT => {
                 let value = self.stack.pop().expect("Could not find value to print.");
 
-                println!("{}", &value.borrow().value);
+                return self.render(Render::new(&value, true, self.frames.len()));
             }
-            OpCode::Nil => {
-                self.stack.push(Rc::new(RefCell::new(Value::default())));
+            op::NIL => {
+                self.stack.push(Value {
+                    value: Primitive::Nil,
+                    modifier: Modifier::Unassigned,
+                    _type: Type::Nil,
+                });
             }
-            OpCode::Pop => {
+            op::POP => {
                 self.stack.pop().expect("Error on pop: stack underflow.");
             }
             // Bring value from constants vector to stack
-            OpCode::Constant(var_index) => {
+            op::CONSTANT => {
+                let var_index = self.read(read_varint);
+
                 let constant =
                     self
                     .frames
@@ -250,498 +474,756 @@ impl Vm {
 
                 let _type = parse_type(&constant);
 
-                self.stack.push(
-                    Rc::new(RefCell::new(
-                        Value {
-                            value: constant,
-                            modifier: Modifier::Unassigned,
-                            _type,
-                        }
-                    ))
-                );
+                self.stack.push(Value {
+                    value: constant,
+                    modifier: Modifier::Unassigned,
+                    _type,
+                });
             }
             /* Check Local Type; Basically constraint value into type */
-            OpCode::DefineLocal(var_index, modifier, t) => {
-                let var_offset = self.frames.last().unwrap().arg_offset;
+            op::DEFINE_LOCAL => {
+                let var_index = self.read(read_varint);
+                let modifier = self.read(read_modifier);
+                let type_index = self.read(read_varint);
 
-                let variable = Rc::clone(
-                    &self.stack[
-                        // checked_sub handle global and defined function args offset handling
-                        var_index + var_offset.checked_sub(1).unwrap_or(var_offset)
-                    ],
-                );
+                let t = Self::annotation(&self.frames, type_index);
+                let slot = self.local_slot(var_index);
 
-                // Rc explicit drop 
-                {
-                    let var_type = &variable.borrow()._type;
-                    if *var_type != t && t != Type::UnInit {
-                        self.error(format!("Cannot assign {:?} to {:?}", t, variable.borrow()._type))?
-                    }
+                if !self.stack[slot].conforms_to(&t) {
+                    return self.error(Self::mismatch(&self.stack[slot], &t));
                 }
 
-                let mut v_borrow = variable.borrow_mut();
-                v_borrow.modifier = modifier;
+                let variable = &mut self.stack[slot];
+
+                variable.modifier = modifier;
+
+                /* Optional bindings keep accepting nil after definition, trait ones any instance of the trait */
+                if let Type::Optional(_) | Type::Trait(_) = *t {
+                    variable._type = t.into_owned();
+                }
             }
             /*
                 Set new value to local variable.
             */
-            OpCode::SetLocal(var_index, modifier) => {
-                let variable = Rc::clone(&self.stack[var_index]);
+            op::SET_LOCAL => {
+                let var_index = self.read(read_varint);
+                let modifier = self.read(read_modifier);
+
+                let incoming_value = self.stack.pop().unwrap();
+                let slot = self.local_slot(var_index);
+
+                let cell = match &self.stack[slot].value {
+                    Primitive::Cell(cell) => Some(Rc::clone(cell)),
+                    _ => None,
+                };
+
+                let unchanged = match &cell {
+                    Some(cell) => Self::holds_reference(&cell.borrow(), &incoming_value),
+                    None => Self::holds_reference(&self.stack[slot], &incoming_value),
+                };
+
+                if unchanged {
+                    return Ok(());
+                }
 
                 if modifier != Modifier::Mut {
                     self.error("Cannot assign to immutable variable.".to_string())?
                 }
 
-                let incoming_value = self.stack.pop().unwrap().take();
-
-                if variable.borrow()._type != incoming_value._type 
-                    && variable.borrow()._type != Type::UnInit {
-                    self.error(format!("Cannot assign {:?} to {:?}", incoming_value._type, variable.borrow()._type))?
+                match cell {
+                    Some(cell) => Self::assign(&mut cell.borrow_mut(), incoming_value)?,
+                    None => Self::assign(&mut self.stack[slot], incoming_value)?,
                 }
-
-                variable.borrow_mut().value = incoming_value.value;
             }
             /*
                 Get value from value position and load it into the top of stack,
                 this way other operations can interact with the value.
             */
-            OpCode::GetLocal(var_index) => {
-                let variable = Rc::clone(
-                    &self.stack[var_index
-                        + (self
-                            .frames
-                            .last()
-                            .unwrap()
-                            .arg_offset
-                            .checked_sub(1)
-                            .unwrap_or(0))],
-                );
-
-                self.stack.push(variable);
+            op::GET_LOCAL => {
+                let var_index = self.read(read_varint);
+
+                let variable = &self.stack[self.local_slot(var_index)];
+
+                let value = match &variable.value {
+                    Primitive::Cell(cell) => cell.borrow().clone(),
+                    _ => variable.clone(),
+                };
+
+                self.stack.push(value);
             }
             /*
                 As local variables are defined as not the same as global ones, it needs a different treatment
-                Set ref to stack bucket where variable value is and let it available on stack.
+                Box variable's stack slot so the reference keeps pointing to it after re-assigns.
             */
-            OpCode::SetRefLocal(var_value_index) => {
-                let referenced_value = Rc::clone(&self.stack[var_value_index]);
+            op::SET_REF_LOCAL => {
+                let var_index = self.read(read_varint);
+                let modifier = self.read(read_modifier);
 
-                let _ref = Value {
-                    value: Primitive::Ref(Rc::clone(&referenced_value)),
-                    _type: Type::Ref(Rc::new((referenced_value).borrow()._type.clone())),
-                    modifier: Modifier::Const,
-                };
+                let referenced_value = self.box_local(var_index);
+                let reference = Self::reference(referenced_value, modifier)?;
 
-                self.stack.push(Rc::new(RefCell::new(_ref)));
+                self.stack.push(reference);
             }
             /*
-                Get variable name from constants and value from top of stack assigning it to globals HashMap
+                Get value from top of stack assigning it to it's global slot
             */
-            OpCode::DefineGlobal(var_name_index, modifier, t) => {
-                let var_name = &self
-                    .frames
-                    .last()
-                    .unwrap()
-                    .function
-                    .chunk
-                    .constants[var_name_index];
+            op::DEFINE_GLOBAL => {
+                let slot = self.read(read_varint);
+                let modifier = self.read(read_modifier);
+                let type_index = self.read(read_varint);
 
-                let mut var_value = self
-                    .stack
-                    .pop()
-                    .unwrap()
-                    .borrow()
-                    .clone();
+                let t = Self::annotation(&self.frames, type_index);
+                let mut var_value = self.stack.pop().unwrap();
 
                 var_value.modifier = modifier;
 
-                if t != Type::UnInit && var_value._type != t {
-                    self.error(format!("Cannot assign {:?} to {:?}", var_value._type, t))?
+                if !var_value.conforms_to(&t) {
+                    self.error(Self::mismatch(&var_value, &t))?
+                }
+
+                /* Optional bindings keep accepting nil after definition, trait ones any instance of the trait */
+                if let Type::Optional(_) | Type::Trait(_) = *t {
+                    var_value._type = t.into_owned();
                 }
 
-                /*  Only strings are allowed to be var names, into() ensure this */
-                self.globals.insert(var_name.into(), var_value);
+                let cell = self.heap.alloc(var_value);
+                self.globals.set_slot(slot, cell);
             }
             /*
-                Get address from get globals and set it in stack.
-                This means every value referencing this value is referencing the value itself, not a copy on stack as globals and stack are exchangeable.
+                Copy value from global slot into the stack.
             */
-            OpCode::GetGlobal(var_index) => {
-                let name = match &self
-                    .frames
-                    .last_mut()
-                    .unwrap()
-                    .function
-                    .chunk
-                    .constants[var_index] 
-                {
-                    Primitive::String(name) => name,
-                    _ => panic!("Invalid global variable name."),
-                };
+            op::GET_GLOBAL => {
+                let slot = self.read(read_varint);
 
-                let value = match self.globals.get(&name) {
-                    Some(value) => value,
-                    None => panic!("Use of undeclared variable '{}'", name),
+                let Some(value) = self.globals.get_slot(slot) else {
+                    return self.error(format!("Use of undeclared variable '{}'.", self.globals.slots.borrow().name(slot)));
                 };
 
-                self.stack.push(Rc::clone(&value));
+                self.stack.push(value.borrow().clone());
             }
             /*
-                Re-assign to already set global variable.
+                Re-assign to already set global variable, writing into it's cell so references to it see the new value.
             */
-            OpCode::SetGlobal(name_index) => {
-                let name = match &self
-                    .frames
-                    .last_mut()
-                    .unwrap()
-                    .function
-                    .chunk
-                    .constants[name_index]
-                {
-                    Primitive::String(name) => name,
-                    _ => panic!("Invalid global variable name."),
-                };
+            op::SET_GLOBAL => {
+                let slot = self.read(read_varint);
 
-                let variable = self.globals.get(name).unwrap();
-                if variable.borrow().modifier != Modifier::Mut {
-                    panic!("Cannot assign to a immutable variable.")
-                }
+                let Some(variable) = self.globals.get_slot(slot) else {
+                    return self.error(format!("Use of undeclared variable '{}'.", self.globals.slots.borrow().name(slot)));
+                };
 
-                let mut to_be_inserted = self.stack.pop().unwrap().take();
+                let to_be_inserted = self.stack.pop().unwrap();
 
-                /* Check if type of dangling value are equal the to-be-assigned variable */
-                if variable.borrow()._type != to_be_inserted._type 
-                    && variable.borrow()._type != Type::UnInit
-                {
-                    panic!(
-                        "Error: Cannot assign {:?} to {:?} ",
-                        to_be_inserted._type,
-                        variable.borrow()._type
-                    );
+                if Self::holds_reference(&variable.borrow(), &to_be_inserted) {
+                    return Ok(());
                 }
 
-                to_be_inserted.modifier = variable.borrow().modifier;
-                to_be_inserted._type = variable.borrow()._type.clone();
-
-                if self.globals.insert(name, to_be_inserted) {
-                    let _ = self.globals.delete(name);
-                    panic!("Global variable is used before it's initialization.");
+                if variable.borrow().modifier != Modifier::Mut {
+                    return self.error(format!("Cannot assign to immutable variable '{}'.", self.globals.slots.borrow().name(slot)));
                 }
+
+                Self::assign(&mut variable.borrow_mut(), to_be_inserted)?;
             }
             /*
-                Get var name from constants and craft a ref value based on globals' referenced Value
+                Craft a ref value based on global slot's referenced Value
             */
-            OpCode::SetRefGlobal(var_index) => {
-                let referenced_name =
-                    match self.frames.last_mut().unwrap().function.chunk.constants[var_index]
-                        .clone()
-                    {
-                        Primitive::String(str) => str,
-                        _ => panic!("Invalid var name reference."),
-                    };
+            op::SET_REF_GLOBAL => {
+                let slot = self.read(read_varint);
+                let modifier = self.read(read_modifier);
 
                 /* Get value to be referenced */
-                let referenced_value = self
-                    .globals
-                    .get(&referenced_name)
-                    .expect("Invalid referenced value.");
-                let referenced_type = referenced_value.borrow()._type.clone();
-
-                let _ref = Value {
-                    value: Primitive::Ref(referenced_value),
-                    _type: Type::Ref(Rc::new(referenced_type)),
-                    modifier: Modifier::Const,
+                let Some(referenced_value) = self.globals.get_slot(slot) else {
+                    return self.error(format!("Use of undeclared variable '{}'.", self.globals.slots.borrow().name(slot)));
                 };
+                let reference = Self::reference(referenced_value, modifier)?;
 
-                match self.stack.pop() {
-                    Some(value) => match value.take() {
-                        Value { _type, .. } => {
-                            if _type != _ref._type {
-                                dbg!(&_type, &_ref._type);
-                                panic!("Cannot assign {:?} to {:?}", _ref._type, _type)
-                            };
-                        }
-                    },
-                    None => (),
-                }
-
-                self.stack.push(Rc::new(RefCell::new(_ref)));
+                self.stack.push(reference);
             }
-            OpCode::JumpIfFalse(offset) => {
-                let value = Rc::clone(self.stack.last().unwrap());
+            op::JUMP_IF_FALSE => {
+                let offset = self.read(read_jump);
 
-                match value.borrow().value {
+                match self.stack.last().unwrap().value {
                     Primitive::Bool(v) => {
                         if v == false {
                             /* Set current opcode index to current + offset */
-                            unsafe {
-                                self.jump_ip(offset as isize);
-                            }
-
-                            return Ok(());
+                            self.jump(offset);
                         }
                     }
                     _ => (),
                 };
             }
-            OpCode::JumpIfTrue(offset) => {
-                let value = Rc::clone(self.stack.last().unwrap());
+            op::JUMP_IF_TRUE => {
+                let offset = self.read(read_jump);
 
-                match value.borrow().value {
+                match self.stack.last().unwrap().value {
                     Primitive::Bool(v) => {
                         if v == true {
                             /* Set current opcode index to current + offset */
-                            unsafe { self.jump_ip(offset as isize) }
-
-                            return Ok(());
+                            self.jump(offset);
                         }
                     }
                     _ => (),
                 };
             }
-            OpCode::Jump(offset) => {
-                unsafe {
-                    self.jump_ip(offset as isize);
+            op::JUMP_IF_NIL => {
+                let offset = self.read(read_jump);
+
+                if self.stack.last().unwrap().value == Primitive::Nil {
+                    self.jump(offset);
                 }
-                return Ok(());
             }
-            OpCode::Loop(offset) => {
-                unsafe { self.putback_ip(offset) }
-                return Ok(());
+            op::JUMP_IF_NOT_NIL => {
+                let offset = self.read(read_jump);
+
+                if self.stack.last().unwrap().value != Primitive::Nil {
+                    self.jump(offset);
+                }
+            }
+            op::PUSH_HANDLER => {
+                let offset = self.read(read_jump);
+
+                let catch_ip = self.frames.last().unwrap().ip + offset;
+
+                self.handlers.push(Handler {
+                    frame_depth: self.frames.len(),
+                    stack_len: self.stack.len(),
+                    catch_ip,
+                });
             }
-            OpCode::Call(args_count) => {
-                if self.call_value(args_count) {
-                    self.stack.remove(self.stack.len() - 1 - args_count);
+            op::POP_HANDLER => {
+                self.handlers.pop();
+            }
+            op::THROW => {
+                let value = self.stack.pop().unwrap();
+
+                if value.value == Primitive::Nil {
+                    return self.error("Cannot throw nil.".to_string());
                 }
 
-                return Ok(());
+                let uncaught = VmError::new(format!("Uncaught exception: {}", value), InterpretResult::RuntimeError);
+
+                return self.throw(value, uncaught);
             }
-            OpCode::Closure => {
-                let _fn = match self.stack.pop().unwrap().take().value {
-                    Primitive::Function(f) => f,
-                    _ => panic!("Could not find fn to enclosure"),
+            op::OK => {
+                let value = self.stack.pop().unwrap();
+                self.stack.push(Value::ok(value));
+            }
+            op::ERR => {
+                let value = self.stack.pop().unwrap();
+                self.stack.push(Value::err(value));
+            }
+            op::PROPAGATE => {
+                let value = self.stack.pop().unwrap();
+
+                match value.value {
+                    Primitive::Result(Ok(v)) => self.stack.push(*v),
+                    Primitive::Result(Err(_)) => return self.return_from_frame(value),
+                    v => return self.error(format!("Cannot apply '?' to {}, expect Result.", v)),
+                }
+            }
+            op::MATCH_RESULT => {
+                let ok = self.read(read_flag);
+
+                let value = self.stack.pop().unwrap();
+
+                let matches = match &value.value {
+                    Primitive::Result(result) => result.is_ok() == ok,
+                    _ => false,
                 };
 
-                self.stack.push(Rc::new(RefCell::new(Self::enclosure(_fn))));
+                self.stack.push(Value {
+                    value: Primitive::Bool(matches),
+                    modifier: Modifier::Unassigned,
+                    _type: Type::Bool,
+                });
             }
-            OpCode::SetUpValue(var_idx) => {
-                let variable = Rc::clone(&self.stack[var_idx]);
+            op::UNWRAP_RESULT => {
+                let value = self.stack.pop().unwrap();
 
-                if variable.borrow().modifier != Modifier::Mut {
-                    self.error("Cannot assign to immutable variable.".to_string())?
-                }
+                let inner = match value.value {
+                    Primitive::Result(Ok(v)) | Primitive::Result(Err(v)) => *v,
+                    v => return self.error(format!("Expect Result, got {}.", v)),
+                };
 
-                let incoming_value = self.stack.pop().expect("Could not find value to assign.").take();
+                self.stack.push(inner);
+            }
+            op::JUMP => {
+                let offset = self.read(read_jump);
 
-                if variable.borrow()._type != incoming_value._type
-                    && variable.borrow()._type != Type::UnInit {
-                    self.error(format!("Cannot assign {:?} to {:?}", incoming_value._type, variable.borrow()._type))?
-                }
+                self.jump(offset);
+            }
+            op::LOOP => {
+                let offset = self.read(read_jump);
 
-                variable.borrow_mut().value = incoming_value.value;
+                self.frames.last_mut().unwrap().ip -= offset;
             }
-            OpCode::GetUpValue(var_idx) => {
-                self.stack.push(Rc::clone(&self.stack[var_idx]));
+            op::CALL => {
+                let args_count = self.read(read_varint);
+
+                self.call_value(args_count, &[])?;
             }
-            OpCode::ParseStructDyn(dyn_count) => {
-                // The newly created dynamic struct
-                let mut _struct_value = self.stack.pop().unwrap();
+            op::TYPE_ARGS => {
+                let type_index = self.read(read_varint);
 
-                {
-                    let mut _struct_borrow = _struct_value.borrow_mut();
+                let Type::Tuple(Some(type_args)) = Self::annotation(&self.frames, type_index).into_owned() else {
+                    panic!("Expect type arguments tuple.")
+                };
 
-                    let Primitive::Struct(ref mut _struct) = &mut _struct_borrow.value else {
-                        panic!("Expect struct, found {:?}", _struct_borrow.value);
-                    };
+                /* Prefix of the instruction they apply to, which is run right away */
+                match self.read(read_opcode) {
+                    op::CALL => {
+                        let args_count = self.read(read_varint);
 
-                    let mut structs: Vec<Rc<RefCell<Value>>> = vec![];
+                        self.call_typed(args_count, &[], Some(&type_args))?;
+                    }
+                    op::CALL_NAMED => {
+                        let args_count = self.read(read_varint);
+                        let names_index = self.read(read_varint);
+                        let names = self.field_names(names_index);
 
-                    for _ in 0..dyn_count {
-                        let _struct = self.stack.pop().unwrap();
-                        structs.push(_struct);
+                        self.call_typed(args_count, &names, Some(&type_args))?;
                     }
+                    op::CREATE_INSTANCE => {
+                        let arg_count = self.read(read_varint);
 
-                    structs.reverse();
+                        let instance = self.instance(arg_count, None, Some(&type_args))?;
+                        self.stack.push(instance.into());
+                    }
+                    op::UPDATE_INSTANCE => {
+                        let arg_count = self.read(read_varint);
 
-                    // Extract dyn types from stack
-                    for ((_, v), _struct) in _struct.field_indices
-                        .iter_mut()
-                        .filter(
-                            |(_, v)| 
-                                if let Some(&Type::Dyn(_)) = Some(&v.0) { true } else { false }
-                        )
-                        .zip(structs)
-                    {
-                        v.0 = Type::Dyn(Dyn { 0: _struct });
-                    };
+                        let base = self.stack.pop().unwrap();
+                        let instance = self.instance(arg_count, Some(base), Some(&type_args))?;
+                        self.stack.push(instance.into());
+                    }
+                    i => panic!("Type arguments can't apply to {}.", OPCODE_NAMES[i as usize]),
                 }
-
-                self.stack.push(_struct_value);
             }
-            OpCode::CreateInstance(arg_count) => {
-                // Tupled values (field_name[String], value[Value]),+[...]
-                let mut tupled_values: Vec<Rc<RefCell<Value>>> = vec![];
+            op::CALL_NAMED => {
+                let args_count = self.read(read_varint);
+                let names_index = self.read(read_varint);
+
+                let names = self.field_names(names_index);
 
-                for _ in 0..arg_count {
-                    tupled_values.push(self.stack.pop().unwrap());
+                self.call_value(args_count, &names)?;
+            }
+            op::CLOSURE => {
+                let _fn = match self.stack.pop().unwrap().value {
+                    Primitive::Function(f) => f,
+                    _ => panic!("Could not find fn to enclosure"),
                 };
 
-                // The base struct
-                let blueprint = self.stack.pop().unwrap();
+                let slot = self.stack.len();
 
-                // The values to be mapped based on blueprint field_indices
-                let mut values: Vec<Value> = vec![Value::default(); arg_count];
+                /* Recursive local function captures it's own slot, which is not on stack until the closure is created */
+                let recursive = _fn.upvalues.iter().any(|up| up.is_local && self.local_slot(up.index) == slot);
 
-                for _tuple in tupled_values {
-                    let wrapped_tuple = _tuple.take();
+                if recursive {
+                    self.stack.push(Value::default());
+                }
 
-                    // Here, tuple (.items) is expect to be a vec! with 2 slots 
-                    // where [Value.value::String, Value.value::Value]
-                    let tuple = match wrapped_tuple.value {
-                        Primitive::Tuple(t) => t,
-                        t => panic!("Tried to destruct Tuple found {t:?}")
-                    };
+                /* Capture variables cells, so they outlive enclosing function frame */
+                let upvalues = _fn
+                    .upvalues
+                    .iter()
+                    .map(|up| match up.is_local {
+                        true => self.box_local(up.index),
+                        false => Rc::clone(&self.frames.last().unwrap().upvalues[up.index]),
+                    })
+                    .collect();
+
+                let type_args = self.frames.last().unwrap().type_args.clone();
+                let closure = Self::enclosure(_fn, upvalues, type_args);
+
+                if !recursive {
+                    self.stack.push(closure);
+                    return Ok(());
+                }
 
-                    let blueprint_value = &blueprint.borrow().value; 
+                let variable = &mut self.stack[slot];
+                let Primitive::Cell(cell) = &variable.value else { unreachable!() };
 
-                    let _struct = match blueprint_value {
-                        Primitive::Struct(ref stct) => {
-                            stct
-                        }
-                        _ => panic!("Invalid blueprint object.")
+                variable._type = closure._type.clone();
+                *cell.borrow_mut() = closure;
+            }
+            op::SET_UP_VALUE => {
+                let var_idx = self.read(read_varint);
+
+                let variable = Rc::clone(&self.frames.last().unwrap().upvalues[var_idx]);
+                let incoming_value = self.stack.pop().expect("Could not find value to assign.");
+
+                if Self::holds_reference(&variable.borrow(), &incoming_value) {
+                    return Ok(());
+                }
+
+                if variable.borrow().modifier != Modifier::Mut {
+                    self.error("Cannot assign to immutable variable.".to_string())?
+                }
+
+                Self::assign(&mut variable.borrow_mut(), incoming_value)?;
+            }
+            op::GET_UP_VALUE => {
+                let var_idx = self.read(read_varint);
+
+                let value = self.frames.last().unwrap().upvalues[var_idx].borrow().clone();
+                self.stack.push(value);
+            }
+            op::PARSE_STRUCT_DYN => {
+                let dyn_count = self.read(read_varint);
+
+                // The newly created dynamic struct
+                let mut _struct_value = self.stack.pop().unwrap();
+
+                {
+                    let Primitive::Struct(ref mut _struct) = _struct_value.value else {
+                        panic!("Expect struct, found {:?}", _struct_value.value);
                     };
 
-                    let field_name = match &tuple.items[0] {
-                        Value { value: Primitive::String(name), .. } => name.clone(),
-                        _ => panic!("Could not find struct name.")
+                    let structs = self.stack.split_off(self.stack.len() - dyn_count);
+
+                    /* Fields still holding the empty Dyn set by the parser, directly or as optional: m: M, m: M? */
+                    let unresolved = |t: &Type| match t {
+                        Type::Dyn(_dyn) => matches!(_dyn.0.borrow().value, Primitive::Void(_)),
+                        Type::Optional(t) => matches!(&**t, Type::Dyn(_dyn) if matches!(_dyn.0.borrow().value, Primitive::Void(_))),
+                        _ => false,
                     };
 
-                    let field_info = _struct
-                        .field_indices
-                        .get(&field_name)
-                        .expect("Use of undeclared field.");
+                    let mut fields: Vec<_> = Rc::make_mut(_struct).field_indices
+                        .values_mut()
+                        .filter(|(t, _)| unresolved(t))
+                        .collect();
 
-                    // Type-Check
-                    match &field_info.0 {
-                        Type::Dyn(_dyn) => {
-                            let incoming_instance = &tuple.items[1];
+                    /* Structs were pushed in field declaration order */
+                    fields.sort_by_key(|(_, index)| *index);
 
-                            let Primitive::Instance(ref instance) = incoming_instance.value else {
-                                panic!("Expect Instance got {}", *_dyn.0.borrow())
-                            };
+                    // Extract dyn types from stack
+                    for ((t, _), _struct) in fields.into_iter().zip(structs) {
+                        let resolved = Type::Dyn(Dyn(Rc::new(RefCell::new(_struct))));
 
-                            if *_dyn.0.borrow() != *instance._struct.borrow() {
-                                panic!("Cannot assign {} to {}", tuple.items[1], _dyn.0.borrow().value)
-                            }
-                        }
-                        t => {
-                            if *t != tuple.items[1]._type {
-                                panic!("Cannot assign {:?} to {:?}.", field_info.0, tuple.items[1]._type);
-                            }
-                        }
+                        *t = match t {
+                            Type::Optional(_) => Type::Optional(Rc::new(resolved)),
+                            _ => resolved,
+                        };
                     }
-
-                    values[field_info.1] = tuple.items[1].clone();
                 }
 
-                let instance = Instance {
-                    _struct: Rc::clone(&blueprint),
-                    values
-                };
+                self.stack.push(_struct_value);
+            }
+            op::CREATE_INSTANCE => {
+                let arg_count = self.read(read_varint);
 
-                self.stack.push(Rc::new(RefCell::new(instance.into())));
+                let instance = self.instance(arg_count, None, None)?;
+                self.stack.push(instance.into());
             }
-            OpCode::Tuple(size) => {
-                let mut tuple_values: Vec<Value> = vec![Value::default(); size];
+            op::UPDATE_INSTANCE => {
+                let arg_count = self.read(read_varint);
 
-                // Map tuple
-                for i in 0..size {
-                    let value = self.stack.pop().unwrap().take();
-                    tuple_values[i] = value;
-                }
+                let base = self.stack.pop().unwrap();
+                let instance = self.instance(arg_count, Some(base), None)?;
+                self.stack.push(instance.into());
+            }
+            op::METHOD => {
+                let name_index = self.read(read_varint);
 
-                let tuple = Tuple {
-                    items: tuple_values.into_boxed_slice(),
-                };
+                let method = self.stack.pop().unwrap();
 
-                let value_tuple = Value {
-                    value: Primitive::Tuple(tuple),
-                    _type: Type::Tuple,
-                    modifier: Modifier::Const,
+                let Primitive::String(ref name) = self.frames.last().unwrap().function.chunk.constants[name_index] else {
+                    panic!("Expect method name constant.")
                 };
 
-                self.stack.push(Rc::new(RefCell::new(value_tuple)));
-            }
-            OpCode::Access => {
-                // Value :: String :: Ident :: Field_Name
-                let field_name = match self.stack.pop().unwrap().take() {
-                    Value { value: Primitive::String(str), .. } => str,
-                    t => panic!("Expect String got {t}")
+                let Primitive::Struct(ref _struct) = self.stack.last().unwrap().value else {
+                    return self.error(format!("Cannot implement methods for {}.", self.stack.last().unwrap()._type));
                 };
 
-                // Instance being accessed
-                let instance_val = self.stack.pop().unwrap();
-
-                let field_value: Value = match &instance_val.borrow().value {
-                    Primitive::Instance(ref instc)  => {
-                        let instc_struct_borrow = instc._struct.borrow();
+                if _struct.field_indices.contains_key(name) {
+                    return self.error(format!("Method '{}' conflicts with field of {}.", name, _struct.name));
+                }
 
-                        let inst_struct = match instc_struct_borrow.value {
-                            Primitive::Struct(ref _struct) => _struct,
-                            _ => panic!("Invalid primitive struct")
-                        };
+                _struct.methods.0.borrow_mut().insert(name.clone(), method);
+            }
+            op::IMPLEMENT => {
+                let type_index = self.read(read_varint);
 
-                        let field_index = inst_struct
-                            .field_indices
-                            .get(&field_name)
-                            .expect(&format!("Invalid field access: {} is not in {}", field_name, inst_struct.name));
+                let Type::Trait(_trait) = Self::annotation(&self.frames, type_index).into_owned() else {
+                    panic!("Expect trait annotation.")
+                };
 
-                        instc.values[field_index.1].clone()
-                    },
-                    t => panic!("Expect Instance found {t}")
+                let Primitive::Struct(ref _struct) = self.stack.last().unwrap().value else {
+                    return self.error(format!("Cannot implement {} for {}.", _trait.name, self.stack.last().unwrap()._type));
                 };
 
-                self.stack.push(Rc::new(RefCell::new(field_value)));
+                if let Some(message) = _trait.unimplemented(_struct) {
+                    return self.error(message);
+                }
             }
-        }
+            op::UNPACK_INSTANCE => {
+                let names_index = self.read(read_varint);
+                let rest = self.read(read_flag);
 
-        unsafe { self.advance_ip(); };
+                let blueprint = self.stack.pop().unwrap();
 
-        Ok(())
+                /* References to instances are followed */
+                let instance_val = match self.stack.pop().unwrap() {
+                    Value { value: Primitive::Ref(cell), .. } => cell.borrow().clone(),
+                    value => value,
+                };
+
+                let Primitive::Struct(ref _struct) = blueprint.value else {
+                    return self.error(format!("Cannot destructure {} as {}.", instance_val, blueprint));
+                };
+
+                let instance = match &instance_val.value {
+                    Primitive::Instance(instance) if blueprint.value == Primitive::Struct(Rc::clone(&instance._struct)) => instance,
+                    _ => return self.error(format!("Cannot destructure {} as {}.", instance_val, _struct.name)),
+                };
+
+                let names = self.field_names(names_index);
+                let mut indices = vec![];
+
+                for name in names.iter() {
+                    let Some((_, field_index)) = _struct.field_indices.get(name) else {
+                        return self.error(format!("Invalid field access: {} is not in {}.", name, _struct.name));
+                    };
+
+                    indices.push(*field_index);
+                }
+
+                /* Every field must be bound, unless pattern ends with `..` */
+                if !rest {
+                    if let Some(field) = Self::first_field(_struct, |idx| !indices.contains(&idx)) {
+                        return self.error(format!("Missing field '{}' in {} pattern.", field, _struct.name));
+                    }
+                }
+
+                let values: Vec<Value> = indices.into_iter().map(|idx| instance.values[idx].clone()).collect();
+                self.stack.extend(values);
+            }
+            op::TUPLE => {
+                let size = self.read(read_varint);
+
+                let items: Vec<Value> = self.stack.split_off(self.stack.len() - size);
+
+                self.stack.push(Tuple::new(items.into()).into());
+            }
+            op::UNPACK => {
+                let count = self.read(read_varint);
+
+                /* References to tuples are followed */
+                let tuple_val = match self.stack.pop().unwrap() {
+                    Value { value: Primitive::Ref(cell), .. } => cell.borrow().clone(),
+                    value => value,
+                };
+
+                let Primitive::Tuple(tuple) = tuple_val.value else {
+                    return self.error(format!("Cannot destructure {} into {} bindings.", tuple_val._type, count));
+                };
+
+                if tuple.items.len() != count {
+                    return self.error(format!("Cannot destructure tuple of {} items into {} bindings.", tuple.items.len(), count));
+                }
+
+                self.stack.extend(tuple.items.iter().cloned());
+            }
+            op::ACCESS => {
+                // Value :: String :: Ident :: Field_Name, or Int :: Tuple index
+                let key = self.stack.pop().unwrap();
+
+                // Instance being accessed, references to instances are followed
+                let instance_val = match self.stack.pop().unwrap() {
+                    Value { value: Primitive::Ref(cell), .. } => cell.borrow().clone(),
+                    value => value,
+                };
+
+                let field_value: Value = match (&instance_val.value, &key.value) {
+                    (Primitive::Instance(ref instc), Primitive::String(field_name)) => {
+                        let inst_struct = &instc._struct;
+
+                        match inst_struct.field_indices.get(field_name) {
+                            Some(field_index) => instc.values[field_index.1].clone(),
+                            /* Methods are bound to the instance they are read from */
+                            None => match inst_struct.method(field_name) {
+                                Some(function) => Value {
+                                    value: Primitive::Method(Rc::new(Method { receiver: instance_val.clone(), function })),
+                                    _type: Type::Closure,
+                                    modifier: Modifier::Const,
+                                },
+                                None => return self.error(format!("Invalid field access: {} is not in {}.", field_name, inst_struct.name)),
+                            },
+                        }
+                    },
+                    (Primitive::Tuple(tuple), Primitive::Int(index)) => {
+                        let Some(item) = tuple.items.get(*index as usize) else {
+                            return self.error(format!("Index {} out of range for tuple of {} items.", index, tuple.items.len()));
+                        };
+
+                        item.clone()
+                    },
+                    (t, _) => return self.error(format!("Cannot access field '{}' of {}.", key, t)),
+                };
+
+                self.stack.push(field_value);
+            }
+            op::DEREF => {
+                let reference = self.stack.pop().unwrap();
+
+                let Primitive::Ref(cell) = reference.value else {
+                    return self.error(format!("Cannot dereference {}.", reference._type));
+                };
+
+                let value = cell.borrow().clone();
+                self.stack.push(value);
+            }
+            op::SET_DEREF => {
+                let incoming_value = self.stack.pop().unwrap();
+                let reference = self.stack.pop().unwrap();
+
+                let (Primitive::Ref(cell), Type::MutRef(_)) = (&reference.value, &reference._type) else {
+                    return self.error(format!("Cannot assign through {}, expect a mutable reference.", reference._type));
+                };
+
+                Self::assign(&mut cell.borrow_mut(), incoming_value)?;
+            }
+            op::MODULO => self.binary_op("%")?,
+            op::DUP => {
+                let value = self.stack.last().unwrap().clone();
+                self.stack.push(value);
+            }
+            op::SET_FIELD => {
+                let name_index = self.read(read_varint);
+
+                let incoming_value = self.stack.pop().unwrap();
+                let mut instance_val = self.stack.pop().unwrap();
+
+                let key = self.frames.last().unwrap().function.chunk.constants[name_index].clone();
+
+                match &instance_val.value {
+                    /* Fields are set on the referenced value, the reference itself is left as is */
+                    Primitive::Ref(cell) => {
+                        if !matches!(instance_val._type, Type::MutRef(_)) {
+                            return self.error(format!("Cannot set field '{}' through {}, expect a mutable reference.", key, instance_val._type));
+                        }
+
+                        let cell = Rc::clone(cell);
+                        let mut referenced = cell.borrow().clone();
+                        self.set_field(&mut referenced, &key, incoming_value)?;
+
+                        /* Updated copy is not left on stack */
+                        self.heap.claim(&referenced);
+                        *cell.borrow_mut() = referenced;
+                    }
+                    _ => self.set_field(&mut instance_val, &key, incoming_value)?,
+                }
+
+                self.stack.push(instance_val);
+            }
+            op::STRINGIFY => {
+                let value = self.stack.pop().unwrap();
+
+                if let Primitive::String(_) = value.value {
+                    self.stack.push(Value { _type: Type::String, modifier: Modifier::Unassigned, ..value });
+                    return Ok(());
+                }
+
+                return self.render(Render::new(&value, false, self.frames.len()));
+            }
+            byte => panic!("Invalid opcode {byte}."),
+        }
+
+        Ok(())
+    }
+
+    /// Read operand of the running instruction, moving current frame's ip past it.
+    ///
+    #[inline(always)]
+    fn read<T>(&mut self, read: fn(&[u8], &mut usize) -> T) -> T {
+        let frame = self.frames.last_mut().unwrap();
+
+        read(&frame.function.chunk.code, &mut frame.ip)
     }
 
-    fn call_value(&mut self, args_count: usize) -> bool {
+    /// Call the callee below the arguments on top of stack, removing it from stack once called.
+    ///
+    /// `names` are the parameter names of the trailing named arguments, if any.
+    ///
+    fn call_value(&mut self, args_count: usize, names: &[IString]) -> VmResult {
+        self.call_typed(args_count, names, None)
+    }
+
+    /// Same as `call_value`, with the explicit type arguments of the call if any: first::<Int>(x)
+    ///
+    fn call_typed(&mut self, args_count: usize, names: &[IString], type_args: Option<&[Type]>) -> VmResult {
+        self.call_bound(args_count, names, type_args, TypeArgs::new())
+    }
+
+    /// Same as `call_typed`, with the type parameters the callee inherits from it's instance or enclosing function.
+    ///
+    fn call_bound(&mut self, args_count: usize, names: &[IString], type_args: Option<&[Type]>, outer: TypeArgs) -> VmResult {
         /* The function being called */
-        let callee = Rc::clone(
-            &self.stack[
-                self
-                .stack
-                .len()
-                .checked_sub(1)
-                .unwrap_or(0)
-                .checked_sub(args_count)
-                .unwrap_or(0)
-            ],
-        );
-        let value = callee.borrow();
+        let callee = self.stack[
+            self
+            .stack
+            .len()
+            .checked_sub(1)
+            .unwrap_or(0)
+            .checked_sub(args_count)
+            .unwrap_or(0)
+        ].value.clone();
+
+        match callee {
+            Primitive::Closure(closure) => {
+                let Closure { _fn, upvalues, type_args: captured } = &*closure;
+                let args_count = self.bind_args(_fn, args_count, names)?;
+                let outer = captured.clone().into_iter().chain(outer).collect();
+                let bindings = self.check_args(_fn, args_count, type_args, outer)?;
+                self.call(Rc::clone(_fn), args_count);
+
+                let frame = self.frames.last_mut().unwrap();
+                frame.upvalues = upvalues.clone();
+                frame.type_args = bindings;
 
-        match &*value {
-            Value {
-                value: Primitive::Closure(Closure { _fn, .. } ),
-                ..
-            } => {
-                return self.call(Rc::clone(_fn), args_count);
+                self.stack.remove(self.stack.len() - 1 - args_count);
+                Ok(())
             }
-            Value {
-                value: Primitive::Function(f),
-                ..
-            } => {
-                return self.call(Rc::clone(f), args_count);
+            Primitive::Method(method) => {
+                /* Receiver is passed as first argument: p.m(x) -> m(p, x) */
+                let callee_slot = self.stack.len() - 1 - args_count;
+                self.stack[callee_slot] = method.function.clone();
+                self.stack.insert(callee_slot + 1, method.receiver.clone());
+
+                /* Methods see the type parameters bound by their instance */
+                let outer = match &method.receiver.value {
+                    Primitive::Instance(instance) => instance.bindings(),
+                    _ => TypeArgs::new(),
+                };
+
+                self.call_bound(args_count + 1, names, type_args, outer)
             }
-            Value {
-                value: Primitive::NativeFunction(f),
-                ..
-            } => {
+            Primitive::Function(_fn) => {
+                let args_count = self.bind_args(&_fn, args_count, names)?;
+                let bindings = self.check_args(&_fn, args_count, type_args, outer)?;
+                self.call(_fn, args_count);
+                self.frames.last_mut().unwrap().type_args = bindings;
+
+                self.stack.remove(self.stack.len() - 1 - args_count);
+                Ok(())
+            }
+            Primitive::NativeFunction(mut f) => {
+                if !names.is_empty() {
+                    self.error(format!("Function '{}' does not accept named arguments.", f.name))?
+                }
+
+                if type_args.is_some() {
+                    self.error(format!("Function '{}' does not take type arguments.", f.name))?
+                }
+
+                if let Some(size) = f.allocates.filter(|_| self.limits.max_heap_bytes.is_some()) {
+                    let bytes = size(&self.stack[self.stack.len().saturating_sub(args_count)..]);
+                    self.reserve(bytes)?;
+                }
+
+                if let Some(deferred) = f.deferred.and_then(|deferred| self.defer_native(deferred, args_count)) {
+                    return deferred;
+                }
+
                 /* Pop function from stack so it remains clean */
                 self.stack.remove(self.stack.len() - 1 - args_count);
 
@@ -750,15 +1232,116 @@ impl Vm {
                     ..self.stack.len()
                 ];
 
-                self.stack.push(Rc::new(RefCell::new(f.clone().call(args))));
+                let result = f.call(args)?;
+                self.stack.truncate(self.stack.len() - args.len());
+                self.stack.push(result);
+
+                /* Natives can't reach the Vm, so collections they request run once they return */
+                if take_request() {
+                    self.collect_garbage();
+                }
 
-                unsafe { self.advance_ip() }
-                false
+                Ok(())
             }
-            _ => panic!("Object {callee:#?} is not callabble"),
+            v => self.error(format!("Object {} is not callable.", v)),
         }
     }
 
+    /// Lay the arguments on top of stack out as function's parameters:
+    /// named arguments are moved to their slots, missing ones are filled with defaults
+    /// and exceeding ones are collected in a tuple if function is variadic.
+    ///
+    /// Return the final arguments count.
+    ///
+    fn bind_args(&mut self, function: &Function, args_count: usize, names: &[IString]) -> Result<usize, VmError> {
+        let positional_count = args_count - names.len();
+        let fixed = function.fixed();
+
+        if (positional_count > fixed && !function.variadic) || args_count < function.required() {
+            self.error(format!(
+                "Function '{}' expects {} arguments but got {}.",
+                function.name, function.arity_range(), args_count
+            ))?
+        }
+
+        /* Arguments are already laid out as parameters */
+        if names.is_empty() && !function.variadic && args_count == fixed {
+            return Ok(args_count);
+        }
+
+        let mut positional = self.stack.split_off(self.stack.len() - args_count);
+        let named = positional.split_off(positional_count);
+        let rest = positional.split_off(positional_count.min(fixed));
+
+        let mut slots: Vec<Option<Value>> = positional.into_iter().map(Some).collect();
+        slots.resize(fixed, None);
+
+        for (name, value) in names.iter().zip(named) {
+            let Some(idx) = function.params[..fixed].iter().position(|p| p.name == *name) else {
+                return Err(VmError::new(
+                    format!("Function '{}' has no parameter '{}'.", function.name, name),
+                    InterpretResult::RuntimeError,
+                ));
+            };
+
+            if slots[idx].is_some() {
+                self.error(format!("Function '{}' got multiple values for '{}'.", function.name, name))?
+            }
+
+            slots[idx] = Some(value);
+        }
+
+        for (slot, param) in slots.iter_mut().zip(function.params.iter()) {
+            if slot.is_some() { continue; }
+
+            match &param.default {
+                Some(default) => *slot = Some(default.clone()),
+                None => self.error(format!("Function '{}' missing argument '{}'.", function.name, param.name))?,
+            }
+        }
+
+        self.stack.extend(slots.into_iter().flatten());
+
+        if function.variadic {
+            self.stack.push(Tuple::new(rest.into()).into());
+        }
+
+        Ok(function.params.len())
+    }
+
+    /// Check the arguments on top of stack against function's signature.
+    ///
+    /// Return the types bound to function's type parameters, either explicit or inferred from the arguments.
+    ///
+    fn check_args(&self, function: &Function, args_count: usize, type_args: Option<&[Type]>, outer: TypeArgs) -> Result<TypeArgs, VmError> {
+        /* Function own type parameters shadow the inherited ones */
+        let mut bindings: TypeArgs = outer.into_iter().filter(|(name, _)| !function.type_params.contains(name)).collect();
+
+        if let Some(type_args) = type_args {
+            if type_args.len() != function.type_params.len() {
+                return Err(VmError::new(
+                    format!("Function '{}' expects {} type arguments but got {}.", function.name, function.type_params.len(), type_args.len()),
+                    InterpretResult::RuntimeError,
+                ));
+            }
+
+            bindings.extend(function.type_params.iter().cloned().zip(type_args.iter().cloned()));
+        }
+
+        let args = &self.stack[self.stack.len() - args_count..];
+
+        for (i, (param, arg)) in function.signature.params.iter().zip(args).enumerate() {
+            if !arg.binds_to(param, &mut bindings) {
+                self.error(format!(
+                    "Function '{}' expects {} on argument {} but got {}.",
+                    function.name, param.resolve(&bindings), i + 1, Self::described(arg, param)
+                ))?
+            }
+        }
+
+        Ok(bindings)
+    }
+
     /// Check fn arity, adjust and set (by pushing to frames) a new CallFrame with correct arg_offset.
     /// 
     pub fn call(&mut self, function: Rc<Function>, args_count: usize) -> bool {
@@ -772,12 +1355,14 @@ impl Vm {
         }
 
         let stack_len = self.stack.len();
-        let bytecode_ptr = &function.chunk.code[0] as *const OpCode;
 
         let frame = CallFrame {
             function,
-            ip: bytecode_ptr,
+            ip: 0,
             arg_offset: stack_len - args_count,
+            upvalues: vec![],
+            hook: None,
+            type_args: TypeArgs::new(),
         };
 
         self.frames.push(frame);
@@ -787,48 +1372,269 @@ impl Vm {
 
     /// Turns a function into a closure
     /// 
-    fn enclosure(_fn: Rc<Function>) -> Value {
+    fn enclosure(_fn: Rc<Function>, upvalues: Vec<Rc<RefCell<Value>>>, type_args: TypeArgs) -> Value {
         Value {
-            value: Primitive::Closure(Closure { _fn }),
+            value: Primitive::Closure(Rc::new(Closure { _fn, upvalues, type_args })),
             _type: Type::Closure,
             modifier: Modifier::Const,
         }
     }
 
-    /// + - / * value operations
+    /// Whether Int arithmetic would overflow i64, e.g: i64::MIN / -1.
+    ///
+    fn int_overflows(op: &str, a: i64, b: i64) -> bool {
+        let result = match op {
+            "+" => a.checked_add(b),
+            "-" => a.checked_sub(b),
+            "*" => a.checked_mul(b),
+            "/" => a.checked_div(b),
+            "%" => a.checked_rem(b),
+            _ => Some(0),
+        };
+
+        result.is_none()
+    }
+
+    /// + - / * % < > value operations
     /// 
     pub fn binary_op(&mut self, op: &str) -> VmResult {
-        let b = Rc::clone(&self.stack.pop().ok_or(VmError::new(
+        let b = self.stack.pop().ok_or_else(|| VmError::new(
             "Value 'b' not loaded. (a [op] b)".to_string(),
             InterpretResult::RuntimeError,
-        ))?);
+        ))?;
 
-        let a = Rc::clone(&self.stack.pop().ok_or(VmError::new(
+        let a = self.stack.pop().ok_or_else(|| VmError::new(
             "Value 'a' not loaded. (a [op] b)".to_string(),
             InterpretResult::RuntimeError,
-        ))?);
+        ))?;
+
+        /* Left instance operand may overload the operator: a + b -> a.add(b) */
+        if let Some((method, hook)) = Self::operator_method(&a, op) {
+            return self.call_method(method, vec![a, b], hook);
+        }
+
+        /* Reject operands Primitive operations would panic on, so the error can be caught */
+        match (&a.value, &b.value) {
+            (Primitive::Int(_), Primitive::Int(0)) if op == "/" || op == "%" => {
+                return self.error("Division by zero.".to_string());
+            }
+            (Primitive::Int(x), Primitive::Int(y)) if Self::int_overflows(op, *x, *y) => {
+                return self.error(format!("Integer overflow in {x} {op} {y}."));
+            }
+            (Primitive::Int(_), Primitive::Int(_)) | (Primitive::Float(_), Primitive::Float(_)) => {}
+            (Primitive::String(x), Primitive::String(y)) if op == "+" => {
+                self.reserve(size_of::<StringObj>() + x.len() + y.len())?;
+            }
+            /* Strings, bools, tuples and instances of the same struct are ordered as well */
+            (a, b) if (op == "<" || op == ">") && a.partial_cmp(b).is_some() => {}
+            (a, b) => return self.error(format!("Operation '{}' not allowed between {} and {}.", op, a, b)),
+        }
 
         let mut c = Value::default();
 
-        c.modifier = a.borrow().modifier;
-        c._type = a.borrow()._type.clone();
+        c.modifier = a.modifier;
 
         match op {
-            "+" => c.value = a.borrow().value.clone() + b.borrow().value.clone(),
-            "*" => c.value = a.borrow().value.clone() * b.borrow().value.clone(),
-            "/" => c.value = a.borrow().value.clone() / b.borrow().value.clone(),
+            "+" => c.value = a.value + b.value,
+            "-" => c.value = a.value - b.value,
+            "*" => c.value = a.value * b.value,
+            "/" => c.value = a.value / b.value,
+            "%" => c.value = a.value % b.value,
             ">" => {
-                c.value = Primitive::Bool(a.borrow().value > b.borrow().value);
+                c.value = Primitive::Bool(a.value > b.value);
                 c._type = Type::Bool
             }
             "<" => {
-                c.value = Primitive::Bool(a.borrow().value < b.borrow().value);
+                c.value = Primitive::Bool(a.value < b.value);
                 c._type = Type::Bool
             }
             _ => panic!("Invalid binary operation."),
         }
 
-        self.stack.push(Rc::new(RefCell::new(c)));
+        /* Result type comes from the value, as operands may be bound to optional types */
+        if c._type == Type::Void {
+            c._type = parse_type(&c.value);
+        }
+
+        self.stack.push(c);
+
+        Ok(())
+    }
+
+    /// Type annotation of current function's types side table.
+    ///
+    /// Type parameters of generic functions are replaced by the types bound on current call,
+    /// otherwise the annotation is borrowed from the chunk.
+    ///
+    fn annotation(frames: &[CallFrame], type_index: usize) -> Cow<'_, Type> {
+        let frame = frames.last().unwrap();
+        let t = &frame.function.chunk.types[type_index];
+
+        match frame.type_args.is_empty() {
+            true => Cow::Borrowed(t),
+            false => Cow::Owned(t.resolve(&frame.type_args)),
+        }
+    }
+
+    /// Absolute stack index of current frame's local variable.
+    ///
+    fn local_slot(&self, var_index: usize) -> usize {
+        var_index + self.frames.last().unwrap().arg_offset.saturating_sub(1)
+    }
+
+    /// Move local variable into a shared cell, leaving it boxed on it's stack slot.
+    /// Already boxed variables return their current cell, so every reference and closure shares it.
+    ///
+    fn box_local(&mut self, var_index: usize) -> Rc<RefCell<Value>> {
+        let slot = self.local_slot(var_index);
+        let variable = &mut self.stack[slot];
+
+        if let Primitive::Cell(cell) = &variable.value {
+            return Rc::clone(cell);
+        }
+
+        let _type = variable._type.clone();
+        let modifier = variable.modifier;
+        let cell = self.heap.alloc(std::mem::take(variable));
+
+        *variable = Value { value: Primitive::Cell(Rc::clone(&cell)), _type, modifier };
+
+        cell
+    }
+
+    /// Set field (or tuple item) key of target to incoming value.
+    ///
+    fn set_field(&self, target: &mut Value, key: &Primitive, incoming_value: Value) -> VmResult {
+        match (&mut target.value, key) {
+            (Primitive::Instance(ref mut instance), Primitive::String(field_name)) => {
+                let Some((field_type, field_index)) = instance._struct.field_indices.get(field_name).cloned() else {
+                    return self.error(format!("Invalid field access: {} is not in {}.", field_name, instance._struct.name));
+                };
+
+                let mut bindings = instance._struct.bindings(&instance.type_args);
+                self.check_field(&field_type, &incoming_value, &mut bindings)?;
+
+                /* Instances are values, copies made before this one are left untouched */
+                Rc::make_mut(instance).values[field_index] = incoming_value;
+            }
+            (Primitive::Tuple(ref mut tuple), Primitive::Int(index)) => {
+                let Some(item) = tuple.items.get(*index as usize) else {
+                    return self.error(format!("Index {} out of range for tuple of {} items.", index, tuple.items.len()));
+                };
+
+                /* Items keep the type they were created with */
+                self.check_field(&item._type, &incoming_value, &mut TypeArgs::new())?;
+
+                let mut items = tuple.items.to_vec();
+                items[*index as usize] = incoming_value;
+                *tuple = Tuple::new(items.into());
+            }
+            (_, key) => {
+                return self.error(format!("Cannot set field '{}' of {}.", key, target));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Type-check value being assigned to a field of type field_type.
+    ///
+    /// Type parameters are bound to the value type if they were not yet.
+    ///
+    fn check_field(&self, field_type: &Type, value: &Value, bindings: &mut TypeArgs) -> VmResult {
+        match field_type {
+            Type::Dyn(_dyn) => {
+                let Primitive::Instance(ref instance) = value.value else {
+                    return self.error(format!("Expect instance of {} got {}.", _dyn.0.borrow(), value));
+                };
+
+                if _dyn.0.borrow().value != Primitive::Struct(Rc::clone(&instance._struct)) {
+                    return self.error(format!("Cannot assign {} to {}.", value, _dyn.0.borrow().value));
+                }
+            }
+            t => {
+                if !value.binds_to(t, bindings) {
+                    return self.error(Self::mismatch(value, &field_type.resolve(bindings)));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Build reference to variable's cell, mutable ones can only point to mutable variables.
+    ///
+    fn reference(cell: Rc<RefCell<Value>>, modifier: Modifier) -> Result<Value, VmError> {
+        let referenced_type = Rc::new(cell.borrow()._type.clone());
+
+        let _type = match modifier {
+            Modifier::Mut if cell.borrow().modifier != Modifier::Mut => {
+                return Err(VmError::new(
+                    "Cannot take a mutable reference to an immutable variable.".to_string(),
+                    InterpretResult::RuntimeError,
+                ));
+            }
+            Modifier::Mut => Type::MutRef(referenced_type),
+            _ => Type::Ref(referenced_type),
+        };
+
+        Ok(Value { value: Primitive::Ref(cell), _type, modifier: Modifier::Const })
+    }
+
+    /// Whether incoming is the reference variable already holds, as stored back after setting a field through it: r.x = v.
+    ///
+    fn holds_reference(variable: &Value, incoming: &Value) -> bool {
+        matches!((&variable.value, &incoming.value), (Primitive::Ref(a), Primitive::Ref(b)) if Rc::ptr_eq(a, b))
+    }
+
+    /// Error of value not conforming to annotation t.
+    ///
+    fn mismatch(value: &Value, t: &Type) -> String {
+        match Self::trait_gap(value, t) {
+            Some((name, missing)) => format!("Cannot assign {} to {}: {}", name, t, missing),
+            None => format!("Cannot assign {} to {}.", Self::type_name(value), t),
+        }
+    }
+
+    /// Type of value as shown on errors, instances are named after their struct.
+    ///
+    fn type_name(value: &Value) -> String {
+        match &value.value {
+            Primitive::Instance(instance) => instance._struct.name.clone(),
+            _ => value._type.to_string(),
+        }
+    }
+
+    /// Type of value as shown last on errors about it not conforming to t, without the final period.
+    ///
+    fn described(value: &Value, t: &Type) -> String {
+        match Self::trait_gap(value, t) {
+            Some((name, missing)) => format!("{}: {}", name, missing.trim_end_matches('.')),
+            None => Self::type_name(value),
+        }
+    }
+
+    /// Struct name of an instance expected to conform to trait t, and the first trait method it lacks.
+    ///
+    fn trait_gap<'a>(value: &'a Value, t: &Type) -> Option<(&'a str, String)> {
+        let (Primitive::Instance(instance), Type::Trait(_trait)) = (&value.value, t) else {
+            return None;
+        };
+
+        _trait.unimplemented(&instance._struct).map(|missing| (instance._struct.name.as_str(), missing))
+    }
+
+    /// Type-check incoming value against variable, replacing variable's value.
+    ///
+    fn assign(variable: &mut Value, incoming: Value) -> VmResult {
+        if !incoming.conforms_to(&variable._type) {
+            return Err(VmError::new(
+                Self::mismatch(&incoming, &variable._type),
+                InterpretResult::RuntimeError,
+            ));
+        }
+
+        variable.value = incoming.value;
 
         Ok(())
     }
@@ -842,24 +1648,367 @@ impl Vm {
         panic!()
     }
 
-    unsafe fn advance_ip(&mut self) {
-        unsafe { self.frames.last_mut().unwrap().ip = self.frames.last().unwrap().ip.offset(1) };
+    fn jump(&mut self, offset: usize) {
+        self.frames.last_mut().unwrap().ip += offset;
     }
 
-    unsafe fn jump_ip(&mut self, offset: isize) {
-        unsafe {
-            self.frames.last_mut().unwrap().ip = self.frames.last().unwrap().ip.offset(offset)
+    fn error(&self, message: String) -> VmResult {
+        VmResult::Err(
+            VmError { message, _type: InterpretResult::RuntimeError }
+        )
+    }
+
+    /// Unwind frames and stack to the innermost try block, resuming execution on it's catch branch
+    /// with the thrown value on top of stack.
+    ///
+    /// Return `uncaught` if there's no try block to handle the value.
+    ///
+    fn throw(&mut self, value: Value, uncaught: VmError) -> VmResult {
+        let Some(handler) = self.handlers.pop() else {
+            return Err(uncaught);
+        };
+
+        /* Texts rendered by unwound frames are dropped along with them */
+        self.renders.retain(|render| render.frame_depth < handler.frame_depth);
+
+        self.frames.truncate(handler.frame_depth);
+        self.stack.truncate(handler.stack_len);
+        self.stack.push(value);
+        self.frames.last_mut().unwrap().ip = handler.catch_ip;
+
+        Ok(())
+    }
+
+    /// Leave current frame checking function return type and pushing returned value on caller's stack.
+    ///
+    fn return_from_frame(&mut self, _return: Value) -> VmResult {
+        {
+            let frame = self.frames.last().unwrap();
+            let function = &frame.function;
+            let expected = &function.signature._return.resolve(&frame.type_args);
+
+            if !_return.conforms_to(expected) {
+                self.error(format!(
+                    "Function '{}' must return {} but returned {}.",
+                    function.name, expected, Self::described(&_return, expected)
+                ))?
+            }
+        }
+
+        /* Try blocks left open by an early return are discarded along with it's frame */
+        let depth = self.frames.len();
+        self.handlers.retain(|h| h.frame_depth < depth);
+
+        let last_frame = self.frames.pop().unwrap();
+
+        if self.frames.len() == 0 {
+            return Ok(());
+        }
+
+        /* Sanitize args and locals after frame is dropped; callee was already removed so they start one slot before arg_offset */
+        self.stack.truncate(last_frame.arg_offset - 1);
+
+        match last_frame.hook {
+            Some(hook) => self.finish_hook(hook, &last_frame.function.name, _return),
+            None => {
+                self.stack.push(_return);
+                Ok(())
+            }
+        }
+    }
+
+    /// Finish instruction which called a method on it's own with the method returned value.
+    ///
+    fn finish_hook(&mut self, hook: Hook, method: &str, _return: Value) -> VmResult {
+        let expected = match (hook, &_return.value) {
+            (Hook::Render, Primitive::String(s)) => {
+                let mut render = self.renders.pop().unwrap();
+                render.fill(s);
+
+                return self.render(render);
+            }
+            (Hook::Bool, Primitive::Bool(_)) | (Hook::Hash, Primitive::Int(_)) => {
+                self.stack.push(_return);
+                return Ok(());
+            }
+            (Hook::Compare(ordering), Primitive::Int(n)) => {
+                self.stack.push(Value {
+                    value: Primitive::Bool(n.cmp(&0) == ordering),
+                    modifier: Modifier::Unassigned,
+                    _type: Type::Bool,
+                });
+                return Ok(());
+            }
+            (Hook::Render, _) => Type::String,
+            (Hook::Bool, _) => Type::Bool,
+            (Hook::Compare(_), _) | (Hook::Hash, _) => Type::Int,
         };
+
+        self.error(format!("Method '{}' must return {} but returned {}.", method, expected, _return._type))
     }
 
-    unsafe fn putback_ip(&mut self, offset: usize) {
-        unsafe { self.frames.last_mut().unwrap().ip = self.frames.last().unwrap().ip.sub(offset) };
+    /// Method of the instance value is, if any.
+    ///
+    fn method(value: &Value, name: &str) -> Option<Value> {
+        match &value.value {
+            Primitive::Instance(instance) => instance._struct.method(name),
+            _ => None,
+        }
     }
 
-    fn error(&self, message: String) -> VmResult {
-        VmResult::Err(
-            VmError { message, _type: InterpretResult::CompilerError }
-        )
+    /// Call method on it's own, the instruction is finished by the hook once it returns.
+    ///
+    /// Without a hook the returned value is the instruction result, as for operator methods.
+    ///
+    fn call_method(&mut self, method: Value, args: Vec<Value>, hook: Option<Hook>) -> VmResult {
+        let args_count = args.len();
+
+        self.stack.push(method);
+        self.stack.extend(args);
+        self.call_value(args_count, &[])?;
+
+        self.frames.last_mut().unwrap().hook = hook;
+
+        Ok(())
+    }
+
+    /// Call next `to_string` method the text is waiting on, or print it / push it as a String once complete.
+    ///
+    fn render(&mut self, mut render: Render) -> VmResult {
+        if let Some((method, receiver)) = render.next_gap() {
+            self.renders.push(render);
+            return self.call_method(method, vec![receiver], Some(Hook::Render));
+        }
+
+        match render.print {
+            true => println!("{}", render.text),
+            false => self.stack.push(Value {
+                value: Primitive::String(IString::new(render.text)),
+                _type: Type::String,
+                modifier: Modifier::Unassigned,
+            }),
+        }
+
+        Ok(())
+    }
+
+    /// Run natives depending on instance methods, which only the Vm can call, removing callee and arguments from stack:
+    /// `hash(v)` calls v's own `hash` method and `join(items, separator)` the `to_string` methods of the items.
+    ///
+    fn defer_native(&mut self, deferred: Deferred, args_count: usize) -> Option<VmResult> {
+        let args = &self.stack[self.stack.len() - args_count..];
+
+        let (method, receiver, render) = match (deferred, args) {
+            (Deferred::Hash, [value]) => (Self::method(value, "hash")?, value.clone(), None),
+            (Deferred::Join, [Value { value: Primitive::Tuple(items), .. }, Value { value: Primitive::String(separator), .. }]) => {
+                let mut render = Render::join(&items.items, separator, self.frames.len());
+                let (method, receiver) = render.next_gap()?;
+
+                (method, receiver, Some(render))
+            }
+            _ => return None,
+        };
+
+        self.stack.truncate(self.stack.len() - args_count - 1);
+
+        let hook = match render {
+            Some(render) => {
+                self.renders.push(render);
+                Hook::Render
+            }
+            None => Hook::Hash,
+        };
+
+        Some(self.call_method(method, vec![receiver], Some(hook)))
+    }
+
+    /// Method overloading binary operator on left operand, comparisons fall back to it's `cmp` method.
+    ///
+    fn operator_method(left: &Value, op: &str) -> Option<(Value, Option<Hook>)> {
+        let (name, ordering) = match op {
+            "+" => ("add", None),
+            "-" => ("sub", None),
+            "*" => ("mul", None),
+            "/" => ("div", None),
+            "%" => ("rem", None),
+            "<" => ("lt", Some(Ordering::Less)),
+            ">" => ("gt", Some(Ordering::Greater)),
+            _ => return None,
+        };
+
+        if let Some(method) = Self::method(left, name) {
+            return Some((method, ordering.map(|_| Hook::Bool)));
+        }
+
+        Self::method(left, "cmp").zip(ordering).map(|(cmp, ordering)| (cmp, Some(Hook::Compare(ordering))))
+    }
+
+    /// Names tuple constant, as used by named arguments and struct patterns.
+    ///
+    fn field_names(&self, names_index: usize) -> Vec<IString> {
+        match &self.frames.last().unwrap().function.chunk.constants[names_index] {
+            Primitive::Tuple(t) => t.items.iter().map(|name| match &name.value {
+                Primitive::String(name) => name.clone(),
+                _ => panic!("Invalid field name."),
+            }).collect(),
+            _ => panic!("Invalid field names."),
+        }
+    }
+
+    /// Build an instance of the struct below the arg_count (field_name, value) tuples on top of stack.
+    ///
+    /// Fields left out are taken from base instance (P { x: 1, ..p }) if any, otherwise from struct defaults.
+    ///
+    /// Type parameters of generic structs are bound to type_args if given, otherwise inferred from the fields.
+    ///
+    fn instance(&mut self, arg_count: usize, base: Option<Value>, type_args: Option<&[Type]>) -> Result<Instance, VmError> {
+        // Tupled values (field_name[String], value[Value]),+[...]
+        let tupled_values = self.stack.split_off(self.stack.len() - arg_count);
+
+        // The base struct
+        let blueprint = self.stack.pop().unwrap();
+
+        let _struct = match blueprint.value {
+            Primitive::Struct(stct) => stct,
+            _ => return Err(VmError::new(format!("Cannot instantiate {} of type {}.", blueprint.value, blueprint._type), InterpretResult::RuntimeError)),
+        };
+
+        let mut bindings = TypeArgs::new();
+
+        if let Some(type_args) = type_args {
+            if type_args.len() != _struct.type_params.len() {
+                return Err(VmError::new(
+                    format!("Struct {} expects {} type arguments but got {}.", _struct.name, _struct.type_params.len(), type_args.len()),
+                    InterpretResult::RuntimeError,
+                ));
+            }
+
+            bindings = _struct.bindings(type_args);
+        }
+
+        // The values to be mapped based on blueprint field_indices
+        let mut values: Vec<Option<Value>> = vec![None; _struct.field_count];
+
+        for wrapped_tuple in tupled_values {
+            // Here, tuple (.items) is expect to be a vec! with 2 slots
+            // where [Value.value::String, Value.value::Value]
+            let tuple = match wrapped_tuple.value {
+                Primitive::Tuple(t) => t,
+                t => panic!("Tried to destruct Tuple found {t:?}")
+            };
+
+            let field_name = match &tuple.items[0] {
+                Value { value: Primitive::String(name), .. } => name.clone(),
+                _ => panic!("Could not find struct name.")
+            };
+
+            let Some(field_info) = _struct.field_indices.get(&field_name) else {
+                return Err(VmError::new(format!("Use of undeclared field '{}' on {}.", field_name, _struct.name), InterpretResult::RuntimeError));
+            };
+
+            self.check_field(&field_info.0, &tuple.items[1], &mut bindings)?;
+
+            values[field_info.1] = Some(tuple.items[1].clone());
+        }
+
+        /* Fields of generic types which were left out still have to match the bound types */
+        let left_out: Vec<bool> = values.iter().map(Option::is_none).collect();
+
+        if let Some(base) = base {
+            /* References to instances are followed */
+            let base = match base.value {
+                Primitive::Ref(cell) => cell.borrow().clone(),
+                _ => base,
+            };
+
+            let base = match base.value {
+                Primitive::Instance(ref instance) if instance._struct == _struct => instance,
+                _ => return Err(VmError::new(format!("Cannot update {} from {}.", _struct.name, base), InterpretResult::RuntimeError)),
+            };
+
+            for (value, field) in values.iter_mut().zip(base.values.iter()) {
+                value.get_or_insert_with(|| field.clone());
+            }
+        }
+
+        for (value, default) in values.iter_mut().zip(_struct.defaults.iter()) {
+            if let (None, Some(default)) = (&value, default) {
+                *value = Some(default.clone());
+            }
+        }
+
+        if let Some(field) = Self::first_field(&_struct, |idx| values[idx].is_none()) {
+            return Err(VmError::new(format!("Missing field '{}' in {}.", field, _struct.name), InterpretResult::RuntimeError));
+        }
+
+        let values: Vec<Value> = values.into_iter().flatten().collect();
+
+        if !_struct.type_params.is_empty() {
+            for (field_type, idx) in _struct.field_indices.values() {
+                if left_out[*idx] {
+                    self.check_field(field_type, &values[*idx], &mut bindings)?;
+                }
+            }
+        }
+
+        let type_args = _struct.type_params.iter().map(|param| bindings.get(param).cloned().unwrap_or_default()).collect();
+
+        Ok(Instance::new(_struct, values, type_args))
+    }
+
+    /// Name of the first declared field matching predicate on it's index.
+    ///
+    fn first_field(_struct: &Struct, predicate: impl Fn(usize) -> bool) -> Option<IString> {
+        _struct.field_indices
+            .iter()
+            .filter(|(_, (_, idx))| predicate(*idx))
+            .min_by_key(|(_, (_, idx))| *idx)
+            .map(|(name, _)| name.clone())
+    }
+
+    /// Built-in struct of errors raised by the Vm itself.
+    ///
+    fn error_blueprint() -> Struct {
+        let mut field_indices = HashMap::new();
+        field_indices.insert("message".into(), (Type::String, 0));
+        field_indices.insert("trace".into(), (Type::String, 1));
+        /* InterpretResult of errors raised by the Vm, so scripts can tell limits apart from other failures */
+        field_indices.insert("kind".into(), (Type::String, 2));
+
+        let kind = Value { value: Primitive::String("RuntimeError".into()), _type: Type::String, modifier: Modifier::Const };
+
+        Struct {
+            name: "Error".to_string(),
+            field_indices,
+            field_count: 3,
+            defaults: vec![None, None, Some(kind)],
+            methods: Methods::default(),
+            type_params: vec![],
+        }
+    }
+
+    /// Craft an Error instance of the given kind with current call stack as trace.
+    ///
+    fn error_value(&self, message: String, kind: &InterpretResult) -> Value {
+        let string = |s: String| Value { value: Primitive::String(s.into()), _type: Type::String, modifier: Modifier::Const };
+
+        let values = vec![string(message), string(self.stack_trace()), string(format!("{kind:?}"))];
+
+        Instance::new(Rc::new(Self::error_blueprint()), values, vec![]).into()
+    }
+
+    /// Called functions names, innermost first.
+    ///
+    fn stack_trace(&self) -> String {
+        self.frames
+            .iter()
+            .rev()
+            .map(|frame| match frame.function.name.as_str() {
+                "" => "at <script>".to_string(),
+                name => format!("at {name}"),
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
     }
 
 }
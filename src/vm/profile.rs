@@ -0,0 +1,37 @@
+use crate::vm::chunk::{OPCODE_COUNT, OPCODE_NAMES};
+
+/// Execution counters, collected while `Vm.profile` is set.
+///
+#[derive(Debug, Clone)]
+pub struct Profile {
+    /// Executed instructions, indexed by encoded opcode.
+    pub instructions: [u64; OPCODE_COUNT],
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Profile { instructions: [0; OPCODE_COUNT] }
+    }
+}
+
+impl Profile {
+    /// Count of executed instructions.
+    ///
+    pub fn total(&self) -> u64 {
+        self.instructions.iter().sum()
+    }
+
+    /// Executed opcodes with their counts, most executed first.
+    ///
+    pub fn by_opcode(&self) -> Vec<(&'static str, u64)> {
+        let mut counts: Vec<(&'static str, u64)> = OPCODE_NAMES
+            .iter()
+            .zip(self.instructions)
+            .filter(|(_, count)| *count > 0)
+            .map(|(name, count)| (*name, count))
+            .collect();
+
+        counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        counts
+    }
+}
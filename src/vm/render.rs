@@ -0,0 +1,124 @@
+use std::collections::VecDeque;
+
+use crate::primitives::{primitive::Primitive, value::Value};
+
+/// Text of a value being printed or interpolated, waiting on the `to_string` methods of the instances in it.
+///
+/// Values are laid out as by their derived printing, each instance defining `to_string`
+/// leaves a gap the Vm fills by calling it, in order, once the previous call returned.
+///
+#[derive(Debug)]
+pub struct Render {
+    /// Printed once complete, otherwise pushed on stack as a String.
+    pub print: bool,
+    /// Frames count when the instruction started rendering.
+    pub frame_depth: usize,
+    pub text: String,
+    /* Text following the gap of the method being called */
+    after: String,
+    pending: VecDeque<Gap>,
+}
+
+#[derive(Debug)]
+struct Gap {
+    method: Value,
+    receiver: Value,
+    /// Text up to next gap.
+    after: String,
+}
+
+impl Render {
+    pub fn new(value: &Value, print: bool, frame_depth: usize) -> Self {
+        let mut render = Render { print, frame_depth, text: String::new(), after: String::new(), pending: VecDeque::new() };
+        render.write(value);
+
+        render
+    }
+
+    /// Tuple items separated by separator, as in `join(items, separator)`.
+    ///
+    pub fn join(items: &[Value], separator: &str, frame_depth: usize) -> Self {
+        let mut render = Render { print: false, frame_depth, text: String::new(), after: String::new(), pending: VecDeque::new() };
+
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 { render.push(separator); }
+            render.write(item);
+        }
+
+        render
+    }
+
+    /// Take next `to_string` method to call, with the instance it's called on.
+    ///
+    pub fn next_gap(&mut self) -> Option<(Value, Value)> {
+        let gap = self.pending.pop_front()?;
+        self.after = gap.after;
+
+        Some((gap.method, gap.receiver))
+    }
+
+    /// Fill the gap of last method taken by `next_gap` with the string it returned.
+    ///
+    pub fn fill(&mut self, string: &str) {
+        self.text.push_str(string);
+        self.text.push_str(&std::mem::take(&mut self.after));
+    }
+
+    /// Append to the text after last gap.
+    ///
+    fn push(&mut self, string: &str) {
+        match self.pending.back_mut() {
+            Some(gap) => gap.after.push_str(string),
+            None => self.text.push_str(string),
+        }
+    }
+
+    fn write(&mut self, value: &Value) {
+        match &value.value {
+            Primitive::Instance(instance) => {
+                if let Some(method) = instance._struct.method("to_string") {
+                    self.pending.push_back(Gap { method, receiver: value.clone(), after: String::new() });
+                    return;
+                }
+
+                self.push(&format!("{} {{", instance._struct.name));
+
+                for (i, name) in instance._struct.field_names().iter().enumerate() {
+                    if i > 0 { self.push(","); }
+                    self.push(&format!(" {name}: "));
+                    self.write(&instance.values[i]);
+                }
+
+                self.push(if instance.values.is_empty() { "}" } else { " }" });
+            }
+            Primitive::Tuple(tuple) => {
+                self.push("(");
+
+                for (i, item) in tuple.items.iter().enumerate() {
+                    if i > 0 { self.push(", "); }
+                    self.write(item);
+                }
+
+                if tuple.items.len() == 1 { self.push(","); }
+
+                self.push(")");
+            }
+            Primitive::Result(result) => {
+                let (name, inner) = match result {
+                    Ok(v) => ("Ok", v),
+                    Err(e) => ("Err", e),
+                };
+
+                self.push(&format!("{name}("));
+                self.write(inner);
+                self.push(")");
+            }
+            Primitive::Ref(cell) => {
+                self.push("&");
+                self.write(&cell.borrow());
+            }
+            Primitive::Cell(cell) => self.write(&cell.borrow()),
+            v => self.push(&v.to_string()),
+        }
+    }
+}
@@ -0,0 +1,124 @@
+#[cfg(test)]
+mod bytecode {
+    use std::io::Cursor;
+
+    use asterisk::{
+        primitives::{primitive::Primitive, types::Modifier},
+        vm::{chunk::{decode, Chunk, OpCode, OPCODE_COUNT, OPCODE_NAMES}, compiler::compile, globals::Slots, Vm},
+    };
+
+    #[test]
+    fn encode_decode_roundtrip() {
+        let instructions = [
+            OpCode::Constant(0),
+            OpCode::Constant(300),
+            OpCode::DefineLocal(2, Modifier::Mut, 1),
+            OpCode::SetLocal(70000, Modifier::Const),
+            OpCode::SetRefLocal(5, Modifier::Mut),
+            OpCode::SetRefGlobal(300, Modifier::Const),
+            OpCode::DefineGlobal(128, Modifier::Unassigned, 0),
+            OpCode::JumpIfFalse(513),
+            OpCode::Loop(65535),
+            OpCode::MatchResult(true),
+            OpCode::MatchResult(false),
+            OpCode::CallNamed(3, 129),
+            OpCode::Access,
+            OpCode::Deref,
+            OpCode::SetDeref,
+            OpCode::Modulo,
+            OpCode::Dup,
+            OpCode::SetField(200),
+            OpCode::Unpack(3),
+            OpCode::UpdateInstance(2),
+            OpCode::UnpackInstance(300, true),
+            OpCode::UnpackInstance(1, false),
+            OpCode::Method(7),
+            OpCode::Subtract,
+            OpCode::TypeArgs(4),
+            OpCode::Implement(130),
+            OpCode::Return,
+        ];
+
+        let mut chunk = Chunk::default();
+        for instruction in instructions {
+            chunk.write(instruction);
+        }
+
+        let mut ip = 0;
+        let mut decoded = vec![];
+
+        while ip < chunk.code.len() {
+            let (instruction, next) = decode(&chunk.code, ip);
+            decoded.push(instruction);
+            ip = next;
+        }
+
+        assert_eq!(decoded, instructions);
+    }
+
+    #[test]
+    fn opcode_names_match_encoding() {
+        for byte in 0..OPCODE_COUNT {
+            let (instruction, _) = decode(&[byte as u8, 0, 0, 0, 0], 0);
+
+            assert!(format!("{instruction:?}").starts_with(OPCODE_NAMES[byte]), "{instruction:?}");
+        }
+    }
+
+    #[test]
+    fn small_operands_take_one_byte() {
+        let mut chunk = Chunk::default();
+
+        chunk.write(OpCode::GetLocal(127));
+        assert_eq!(chunk.code.len(), 2);
+
+        chunk.write(OpCode::GetLocal(128));
+        assert_eq!(chunk.code.len(), 5);
+    }
+
+    #[test]
+    fn types_side_table_reuses_annotations() {
+        let source = r"
+            let a: Int = 1;
+            let b: Int = 2;
+            let c: String = 'c';
+        ";
+
+        let function = compile(Cursor::new(source), &Slots::default());
+
+        assert_eq!(function.chunk.types.len(), 2);
+    }
+
+    #[test]
+    fn wide_constant_indices() {
+        let mut vm = Vm::default();
+        let source = (0..200).map(|i| format!("let v{i} = {i};\n")).collect::<String>();
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let v = vm.globals.get(&"v199".to_string()).unwrap();
+        assert_eq!(v.borrow().value, Primitive::Int(199));
+    }
+
+    #[test]
+    fn long_jumps() {
+        let mut vm = Vm::default();
+        let body = "sum = sum + 1;\n".repeat(100);
+        let source = format!(r"
+            let mut sum = 0;
+            let mut i = 0;
+
+            while (i < 3) {{
+                {body}
+                i = i + 1;
+            }}
+
+            if (false) {{ {body} }}
+        ");
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let sum = vm.globals.get(&"sum".to_string()).unwrap();
+        assert_eq!(sum.borrow().value, Primitive::Int(300));
+    }
+}
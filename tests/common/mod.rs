@@ -1,13 +1,16 @@
-use asterisk::{parser::{lexer::Lexer, Parser}, primitives::functions::{Function, FunctionType}};
+use std::rc::Rc;
 
-/// Crafts a default parser given a source
+use asterisk::{parser::{lexer::Lexer, Parser}, primitives::functions::{Function, FunctionType}, vm::globals::Slots};
+
+/// Crafts a default parser given a source, resolving globals to the given Vm slots
 /// 
-pub fn mk_parser<R: std::io::Read>(source: R) -> Parser<R> {
+pub fn mk_parser<R: std::io::Read>(source: R, slots: &Slots) -> Parser<R> {
     let mut p = Parser::new(
         Function::default(),
         FunctionType::Script,
-        Lexer::new(source)
+        Lexer::new(source),
+        Rc::clone(slots)
     );
     p.advance();
     p
-}
\ No newline at end of file
+}
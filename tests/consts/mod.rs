@@ -0,0 +1,149 @@
+#[cfg(test)]
+mod consts {
+    use std::{io::Cursor, panic::{catch_unwind, AssertUnwindSafe}};
+
+    use asterisk::{
+        primitives::primitive::Primitive,
+        vm::{chunk::{decode, OpCode}, compiler::compile, globals::Slots, Vm},
+    };
+
+    #[test]
+    fn constants_folding() {
+        let mut vm = Vm::default();
+        let source = r#"
+            const KB: Int = 1024;
+            const MAX: Int = 10 * KB;
+            const NEG = -(2 + 3) * 2 - 1;
+            const HALF = 1.5 / 2.0;
+            const GREETING: String = "hi" + " there";
+            const BIG: Bool = MAX > 5000 and !(KB == 1);
+
+            let max = MAX;
+            let neg = NEG;
+            let half = HALF;
+            let greeting = GREETING;
+            let big = BIG;
+
+            fn shadowed() {
+                const LOCAL = MAX % 1000;
+                let MAX = 1;
+
+                return LOCAL + MAX;
+            }
+            let local = shadowed();
+
+            fn scoped() {
+                const K = 1;
+                {
+                    const K = 2;
+                    const INNER = K;
+                }
+
+                return K;
+            }
+            let outer = scoped();
+        "#;
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let expected = [
+            ("max", Primitive::Int(10240)),
+            ("neg", Primitive::Int(-11)),
+            ("half", Primitive::Float(0.75)),
+            ("greeting", Primitive::String("hi there".into())),
+            ("big", Primitive::Bool(true)),
+            ("local", Primitive::Int(241)),
+            ("outer", Primitive::Int(1)),
+        ];
+
+        for (name, value) in expected {
+            let global = vm.globals.get(name).unwrap();
+            assert_eq!(global.borrow().value, value, "{name}");
+        }
+    }
+
+    #[test]
+    fn constants_compile_to_chunk_constants() {
+        let function = compile(Cursor::new("const MAX = 10 * 1024; let max = MAX;"), &Slots::default());
+
+        assert!(function.chunk.constants.contains(&Primitive::Int(10240)));
+        assert!(!function.chunk.constants.contains(&Primitive::Int(1024)));
+
+        let (first, _) = decode(&function.chunk.code, 0);
+        assert!(matches!(first, OpCode::Constant(_)), "{first:?}");
+    }
+
+    #[test]
+    fn declarations_outlive_script() {
+        let mut vm = Vm::default();
+        let lines = [
+            "const A = 5;",
+            "let a = A + 1;",
+            "type Id = Int;",
+            "let id: Id = 1;",
+            "trait Named { fn name(this) -> String; }",
+            "struct Box<T> { v: T }",
+            "impl Named for Box { fn name(this) -> String { return 'box'; } }",
+            "impl Box { fn get(this) -> T { return this.v; } }",
+            "fn named(n: Named) -> String { return n.name(); } let name = named(Box { v: 1 });",
+            "let v = Box { v: 2 }.get();",
+        ];
+
+        /* As REPL lines, each one compiled on it's own */
+        for line in lines {
+            vm.interpret(Cursor::new(line)).unwrap();
+        }
+
+        let expected = [
+            ("a", Primitive::Int(6)),
+            ("id", Primitive::Int(1)),
+            ("name", Primitive::String("box".into())),
+            ("v", Primitive::Int(2)),
+        ];
+
+        for (name, value) in expected {
+            let global = vm.globals.get(name).unwrap();
+            assert_eq!(global.borrow().value, value, "{name}");
+        }
+
+        let slots = &vm.globals.slots;
+        let result = catch_unwind(AssertUnwindSafe(|| compile(Cursor::new("let A = 1;"), slots)));
+        let error = result.expect_err("constant redeclared by a later script");
+        let error = error.downcast_ref::<String>().unwrap();
+
+        assert!(error.contains("Cannot redeclare constant 'A'."), "{error}");
+    }
+
+    #[test]
+    fn constants_errors() {
+        let failing = [
+            ("const A = 1; A = 2;", "Cannot assign to constant 'A'."),
+            ("const A = 1; A += 2;", "Cannot assign to constant 'A'."),
+            ("const A = 1; let r = &A;", "Cannot reference constant 'A'."),
+            ("const A = 1; let A = 2;", "Cannot redeclare constant 'A'."),
+            ("const A = 1; const A = 2;", "Constant 'A' is already declared."),
+            ("{ const K = 5; } const A = K;", "'K' is not a constant."),
+            ("let X = 1; const X = 2;", "Cannot declare constant 'X', a variable with this name exists."),
+            ("fn f() { let x = 1; { const x = 2; } }", "Cannot declare constant 'x', a variable with this name exists."),
+            ("fn f() { let x = 1; fn g() { const x = 2; } }", "Cannot declare constant 'x', a variable with this name exists."),
+            ("let x = 1; const A = x + 1;", "'x' is not a constant."),
+            ("fn f() {} const A = f();", "'f' is not a constant."),
+            ("const A = \"${1}\";", "Initializer of constant must be a constant expression."),
+            ("const A: String = 1;", "Constant 'A' expects String but got Int."),
+            ("const A = 1 / 0;", "Division by zero in constant expression."),
+            ("const A = 1 + \"s\";", "Operation '+' not allowed between 1 and s in constant expression."),
+            ("const A = 9223372036854775807 + 1;", "Integer overflow in 9223372036854775807 + 1 in constant expression."),
+            ("const A = -9223372036854775807 - 1; const B = A / -1;", "Integer overflow in -9223372036854775808 / -1 in constant expression."),
+            ("const A = -9223372036854775807 - 1; const B = -A;", "Integer overflow negating -9223372036854775808 in constant expression."),
+            ("const A = -true;", "Cannot negate true in constant expression."),
+        ];
+
+        for (source, message) in failing {
+            let result = catch_unwind(|| compile(Cursor::new(source), &Slots::default()));
+            let error = result.expect_err(source);
+            let error = error.downcast_ref::<String>().unwrap();
+
+            assert!(error.contains(message), "{error}");
+        }
+    }
+}
@@ -13,13 +13,13 @@ mod _while {
             }
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut vm = Vm::default();
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         // var_declaration()
         parser = parser.declaration();
         // while
         parser = parser.declaration();
 
-        let mut vm = Vm::default();
         vm.call(Rc::new(parser.end_compiler()), 0);
 
         vm.run().unwrap();
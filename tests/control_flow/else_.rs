@@ -17,7 +17,7 @@ mod else_ {
             }
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         // var decl
         parser = parser.declaration();
         // if statement
@@ -43,7 +43,7 @@ mod else_ {
             }
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         // var decl
         parser = parser.declaration();
         // if statement
@@ -19,7 +19,7 @@ mod elsif {
             }
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         // var decl
         parser = parser.declaration();
         // if statement
@@ -47,7 +47,7 @@ mod elsif {
             }
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         // var decl
         parser = parser.declaration();
         // if statement
@@ -16,13 +16,13 @@ mod for_ {
             }
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut vm = Vm::default();
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         // define_struct()
         parser = parser.declaration();
         // for
         parser = parser.declaration();
 
-        let mut vm = Vm::default();
         vm.call(Rc::new(parser.end_compiler()), 0);
 
         vm.run().unwrap();
@@ -45,13 +45,13 @@ mod for_ {
             }
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut vm = Vm::default();
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         // define_struct()
         parser = parser.declaration();
         // for
         parser = parser.declaration();
 
-        let mut vm = Vm::default();
         vm.call(Rc::new(parser.end_compiler()), 0);
 
         vm.run().unwrap();
@@ -77,13 +77,13 @@ mod for_ {
             }
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut vm = Vm::default();
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         // define_struct()
         parser = parser.declaration();
         // for
         parser = parser.declaration();
 
-        let mut vm = Vm::default();
         vm.call(Rc::new(parser.end_compiler()), 0);
 
         vm.run().unwrap();
@@ -107,13 +107,13 @@ mod for_ {
             }
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut vm = Vm::default();
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         // var_declaration()
         parser = parser.declaration();
         // for
         parser = parser.declaration();
 
-        let mut vm = Vm::default();
         vm.call(Rc::new(parser.end_compiler()), 0);
 
         vm.run().unwrap();
@@ -126,4 +126,23 @@ mod for_ {
 
         assert_eq!(n_value, 10);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn for_body_locals_per_iteration() {
+        let source = r"
+            let mut n: Int = 0;
+
+            for (let mut i = 0; i < 4; i = i + 1) {
+                let k = i;
+                n = n + k;
+            }
+        ";
+
+        let mut vm = Vm::default();
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let n = vm.globals.get(&"n".to_string()).unwrap();
+        assert_eq!(n.borrow().value, Primitive::Int(6));
+        assert!(vm.stack.is_empty());
+    }
+}
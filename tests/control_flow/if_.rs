@@ -17,7 +17,7 @@ mod if_ {
             }
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         // var decl
         parser = parser.declaration();
         // if statement
@@ -40,7 +40,7 @@ mod if_ {
             }
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         // var decl
         parser = parser.declaration();
         // if statement
@@ -64,7 +64,7 @@ mod if_ {
             }
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         // var decl
         parser = parser.declaration();
         // if statement
@@ -88,7 +88,7 @@ mod if_ {
             }
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         // var decl
         parser = parser.declaration();
         // if statement
@@ -112,7 +112,7 @@ mod if_ {
             }
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         // var decl
         parser = parser.declaration();
         // if statement
@@ -136,7 +136,7 @@ mod if_ {
             }
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         // var decl
         parser = parser.declaration();
         // if statement
@@ -161,7 +161,7 @@ mod if_ {
             }
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         // var decl
         parser = parser.declaration();
         // if statement
@@ -186,7 +186,7 @@ mod if_ {
             }
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         // var decl
         parser = parser.declaration();
         // if statement
@@ -211,7 +211,7 @@ mod if_ {
             }
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         // var decl
         parser = parser.declaration();
         // if statement
@@ -236,7 +236,7 @@ mod if_ {
             }
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         // var decl
         parser = parser.declaration();
         // if statement
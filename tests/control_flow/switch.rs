@@ -21,13 +21,13 @@ mod switch {
             }
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut vm = Vm::default();
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         // var_declaration()
         parser = parser.declaration();
         // switch
         parser = parser.declaration();
 
-        let mut vm = Vm::default();
         vm.call(Rc::new(parser.end_compiler()), 0);
 
         vm.run().unwrap();
@@ -60,13 +60,13 @@ mod switch {
             }
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut vm = Vm::default();
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         // var_declaration()
         parser = parser.declaration();
         // switch
         parser = parser.declaration();
 
-        let mut vm = Vm::default();
         vm.call(Rc::new(parser.end_compiler()), 0);
 
         vm.run().unwrap();
@@ -79,4 +79,24 @@ mod switch {
 
         assert_eq!(n_value, 3);
     }
+
+    #[test]
+    fn switch_case_declares_local() {
+        let source = r"
+            let mut n: Int = 1;
+
+            switch (n) {
+                case 1 => {
+                    let m = 5;
+                    n = m;
+                }
+            }
+        ";
+
+        let mut vm = Vm::default();
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let n = vm.globals.get(&"n".to_string()).unwrap();
+        assert_eq!(n.borrow().value, Primitive::Int(5));
+    }
 }
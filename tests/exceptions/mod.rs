@@ -0,0 +1,207 @@
+#[cfg(test)]
+mod exceptions {
+    use std::io::Cursor;
+
+    use asterisk::{primitives::primitive::Primitive, vm::Vm};
+
+    #[test]
+    fn throw_caught_by_catch() {
+        let mut vm = Vm::default();
+        let source = r"
+            let mut r = '';
+            try {
+                throw 'boom';
+                r = 'unreachable';
+            } catch (e) {
+                r = e;
+            }
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let r = vm.globals.get(&"r".to_string()).unwrap();
+        assert_eq!(r.borrow().value, Primitive::String("boom".into()));
+    }
+
+    #[test]
+    fn finally_runs_on_both_paths() {
+        let mut vm = Vm::default();
+        let source = r"
+            let mut a = 0;
+            let mut b = 0;
+
+            try { a = 1; } catch (e) { a = 2; } finally { b = b + 1; }
+            try { throw 1; } catch (e) { a = a + e; } finally { b = b + 1; }
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let a = vm.globals.get(&"a".to_string()).unwrap();
+        assert_eq!(a.borrow().value, Primitive::Int(2));
+
+        let b = vm.globals.get(&"b".to_string()).unwrap();
+        assert_eq!(b.borrow().value, Primitive::Int(2));
+    }
+
+    #[test]
+    fn unwind_through_call_frames() {
+        let mut vm = Vm::default();
+        let source = r"
+            fn inner() { throw 'deep'; }
+            fn outer() { let n = 1; inner(); return n; }
+
+            let mut r = '';
+            try { outer(); } catch (e) { r = e; }
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let r = vm.globals.get(&"r".to_string()).unwrap();
+        assert_eq!(r.borrow().value, Primitive::String("deep".into()));
+        assert_eq!(vm.frames.len(), 0);
+    }
+
+    #[test]
+    fn finally_rethrows_pending_error() {
+        let mut vm = Vm::default();
+        let source = r"
+            let mut log = '';
+            try {
+                try { throw 'inner'; } finally { log = log + 'finally '; }
+            } catch (e) {
+                log = log + e;
+            }
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let log = vm.globals.get(&"log".to_string()).unwrap();
+        assert_eq!(log.borrow().value, Primitive::String("finally inner".into()));
+    }
+
+    #[test]
+    fn finally_runs_before_return() {
+        let mut vm = Vm::default();
+        let source = r"
+            let mut log = '';
+
+            fn from_try() -> Int {
+                try { let n = 1; return n; } finally { log = log + 'try '; }
+                return 0;
+            }
+            fn from_catch() -> Int {
+                try { throw 2; } catch (e) { let m = e; return m; } finally { log = log + 'catch '; }
+                return 0;
+            }
+            fn nested() {
+                try {
+                    try { return; } finally { log = log + 'inner '; }
+                } finally {
+                    log = log + 'outer';
+                }
+                log = log + ' unreachable';
+            }
+            fn overridden() -> Int {
+                try { return 1; } finally { return 2; }
+            }
+
+            let a = from_try();
+            let b = from_catch();
+            let none = nested();
+            let c = overridden();
+
+            fn caught() -> Int {
+                try {
+                    try { return 1; } finally { throw 'finally'; }
+                } catch (e) {
+                    return 3;
+                }
+            }
+            let d = caught();
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let expected = [
+            ("a", Primitive::Int(1)),
+            ("b", Primitive::Int(2)),
+            ("c", Primitive::Int(2)),
+            ("d", Primitive::Int(3)),
+            ("log", Primitive::String("try catch inner outer".into())),
+        ];
+
+        for (name, value) in expected {
+            let global = vm.globals.get(name).unwrap();
+            assert_eq!(global.borrow().value, value, "{name}");
+        }
+        assert_eq!(vm.stack.len(), 0);
+        assert_eq!(vm.handlers.len(), 0);
+    }
+
+    #[test]
+    fn runtime_error_is_catchable() {
+        let mut vm = Vm::default();
+        let source = r"
+            fn div(a: Int, b: Int) -> Int { return a / b; }
+
+            let mut message = '';
+            let mut trace = '';
+            let mut kind = '';
+            try { div(1, 0); } catch (e) { message = e.message; trace = e.trace; kind = e.kind; }
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let message = vm.globals.get(&"message".to_string()).unwrap();
+        assert_eq!(message.borrow().value, Primitive::String("Division by zero.".into()));
+
+        let trace = vm.globals.get(&"trace".to_string()).unwrap();
+        assert_eq!(trace.borrow().value, Primitive::String("at div\nat <script>".into()));
+
+        let kind = vm.globals.get(&"kind".to_string()).unwrap();
+        assert_eq!(kind.borrow().value, Primitive::String("RuntimeError".into()));
+    }
+
+    #[test]
+    fn vm_errors_are_catchable() {
+        let sources = [
+            r"
+                fn f(n: Int) {}
+                try { f('str'); } catch (e) { caught = true; }
+            ",
+            r"
+                fn f(n: Int) {}
+                try { f(); } catch (e) { caught = true; }
+            ",
+            r"
+                struct P { x: Int }
+                let p = P { x: 1 };
+                try { p.y; } catch (e) { caught = true; }
+            ",
+            r"
+                try { let n: Int = 'str'; } catch (e) { caught = true; }
+            ",
+        ];
+
+        for source in sources {
+            let mut vm = Vm::default();
+            let source = format!("let mut caught = false; {source}");
+
+            vm.interpret(Cursor::new(source)).unwrap();
+
+            let caught = vm.globals.get(&"caught".to_string()).unwrap();
+            assert_eq!(caught.borrow().value, Primitive::Bool(true));
+        }
+    }
+
+    #[test]
+    fn uncaught_error_surfaces_to_host() {
+        let mut vm = Vm::default();
+        let source = r"
+            throw 'oops';
+        ";
+
+        let err = vm.interpret(Cursor::new(source)).unwrap_err();
+        assert_eq!(err.message, "Uncaught exception: oops");
+    }
+}
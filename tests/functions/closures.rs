@@ -26,7 +26,7 @@ mod closures {
             let c = counter();
         "#;
 
-        vm.interpret(Cursor::new(source));
+        vm.interpret(Cursor::new(source)).unwrap();
 
         let a = vm.globals.get(&"a".to_string()).unwrap();
 
@@ -51,7 +51,7 @@ mod closures {
             let g = n('some');
         ";
 
-        vm.interpret(Cursor::new(source));
+        vm.interpret(Cursor::new(source)).unwrap();
 
         let g = vm.globals.get(&"g".to_string()).unwrap().take();
         let Primitive::Int(n) = g.value else {
@@ -69,7 +69,7 @@ mod closures {
             let g = n;
         ";
 
-        vm.interpret(Cursor::new(source));
+        vm.interpret(Cursor::new(source)).unwrap();
         let g = vm.globals.get(&"g".to_string()).unwrap().take();
         let Primitive::Int(n) = g.value else {
             panic!()
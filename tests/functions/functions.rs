@@ -13,7 +13,7 @@ mod functions {
             let n = 2;
             f(n);
         ";
-        let mut parser = mk_parser(Cursor::new(sources));
+        let mut parser = mk_parser(Cursor::new(sources), &vm.globals.slots);
         // fun_declaration
         parser = parser.declaration();
         // var_declaration
@@ -58,7 +58,7 @@ mod functions {
 
             f(n, m, p, g, b, c, d);
         ";
-        let mut parser = mk_parser(Cursor::new(sources));
+        let mut parser = mk_parser(Cursor::new(sources), &vm.globals.slots);
         // fun declaration
         parser = parser.declaration();
 
@@ -97,7 +97,7 @@ mod functions {
             let r = fib(10);
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         // fun declaration
         parser = parser.declaration();
         // var declaration
@@ -139,7 +139,7 @@ mod functions {
             a();
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         // fn
         parser = parser.declaration();
         // var declaration
@@ -151,4 +151,206 @@ mod functions {
         let a = vm.globals.get(&"a".to_string()).unwrap().take();
         assert_eq!(a._type, Type::Closure);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn fun_return_type() {
+        let mut vm = Vm::default();
+        let source = r"
+            fn add(a: Int, b: Int) -> Int { return a + b; }
+
+            let r = add(1, 2);
+        ";
+
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
+        // fun declaration
+        parser = parser.declaration();
+        // var declaration
+        parser = parser.declaration();
+
+        vm.call(Rc::new(parser.end_compiler()), 0);
+        vm.run().unwrap();
+
+        let r = vm.globals.get(&"r".to_string()).unwrap();
+        assert_eq!(r.borrow().value, Primitive::Int(3));
+
+        let add = vm.globals.get(&"add".to_string()).unwrap();
+        let Type::Fn(Some(ref signature)) = add.borrow()._type else {
+            panic!("Expect fn signature.");
+        };
+
+        assert_eq!(signature.params, vec![Type::Int, Type::Int]);
+        assert_eq!(signature._return, Type::Int);
+    }
+
+    #[test]
+    fn fun_return_type_mismatch() {
+        let mut vm = Vm::default();
+        let source = r"
+            fn f() -> Int { return 'str'; }
+
+            f();
+        ";
+
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
+        // fun declaration
+        parser = parser.declaration();
+        // stmt
+        parser = parser.declaration();
+
+        vm.call(Rc::new(parser.end_compiler()), 0);
+
+        assert!(vm.run().is_err());
+    }
+
+    #[test]
+    fn fun_argument_type_mismatch() {
+        let mut vm = Vm::default();
+        let source = r"
+            fn f(n: Int) { return n; }
+
+            f('str');
+        ";
+
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
+        // fun declaration
+        parser = parser.declaration();
+        // stmt
+        parser = parser.declaration();
+
+        vm.call(Rc::new(parser.end_compiler()), 0);
+
+        assert!(vm.run().is_err());
+    }
+
+    #[test]
+    fn fun_default_arguments() {
+        let mut vm = Vm::default();
+        let source = r"
+            fn f(a: Int, b: Int = 2) -> Int { return a + b; }
+
+            let x = f(1);
+            let y = f(1, 5);
+        ";
+
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
+        // fun declaration
+        parser = parser.declaration();
+        // var declarations
+        parser = parser.declaration();
+        parser = parser.declaration();
+
+        vm.call(Rc::new(parser.end_compiler()), 0);
+        vm.run().unwrap();
+
+        let x = vm.globals.get(&"x".to_string()).unwrap();
+        let y = vm.globals.get(&"y".to_string()).unwrap();
+
+        assert_eq!(x.borrow().value, Primitive::Int(3));
+        assert_eq!(y.borrow().value, Primitive::Int(6));
+    }
+
+    #[test]
+    fn fun_named_arguments() {
+        let mut vm = Vm::default();
+        let source = r"
+            fn sub(a: Int, b: Int = 1) -> Int { return a - b; }
+
+            let x = sub(b: 3, a: 10);
+            let y = sub(10, b: 4);
+            let z = sub(a: 10);
+        ";
+
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
+        // fun declaration
+        parser = parser.declaration();
+        // var declarations
+        for _ in 0..3 {
+            parser = parser.declaration();
+        }
+
+        vm.call(Rc::new(parser.end_compiler()), 0);
+        vm.run().unwrap();
+
+        let x = vm.globals.get(&"x".to_string()).unwrap();
+        let y = vm.globals.get(&"y".to_string()).unwrap();
+        let z = vm.globals.get(&"z".to_string()).unwrap();
+
+        assert_eq!(x.borrow().value, Primitive::Int(7));
+        assert_eq!(y.borrow().value, Primitive::Int(6));
+        assert_eq!(z.borrow().value, Primitive::Int(9));
+    }
+
+    #[test]
+    fn fun_variadic_arguments() {
+        let mut vm = Vm::default();
+        let source = r"
+            fn log(fmt: String, ...args) { return args; }
+
+            let a = log('x', 1, true);
+            let b = log('x');
+        ";
+
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
+        // fun declaration
+        parser = parser.declaration();
+        // var declarations
+        parser = parser.declaration();
+        parser = parser.declaration();
+
+        vm.call(Rc::new(parser.end_compiler()), 0);
+        vm.run().unwrap();
+
+        let a = vm.globals.get(&"a".to_string()).unwrap();
+        let Primitive::Tuple(ref a) = a.borrow().value else { panic!("Expect tuple.") };
+
+        assert_eq!(a.items.len(), 2);
+        assert_eq!(a.items[0].value, Primitive::Int(1));
+        assert_eq!(a.items[1].value, Primitive::Bool(true));
+
+        let b = vm.globals.get(&"b".to_string()).unwrap();
+        let Primitive::Tuple(ref b) = b.borrow().value else { panic!("Expect tuple.") };
+
+        assert_eq!(b.items.len(), 0);
+    }
+
+    #[test]
+    fn fun_arity_mismatch() {
+        let sources = [
+            r"
+                fn f(a, b = 2) {}
+                f();
+            ",
+            r"
+                fn f(a, b = 2) {}
+                f(1, 2, 3);
+            ",
+            r"
+                fn f(a, b = 2) {}
+                f(1, c: 3);
+            ",
+        ];
+
+        for source in sources {
+            let mut vm = Vm::default();
+            let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
+            // fun declaration
+            parser = parser.declaration();
+            // stmt
+            parser = parser.declaration();
+
+            vm.call(Rc::new(parser.end_compiler()), 0);
+
+            let err = vm.run().unwrap_err();
+            assert!(err.message.contains("'f'"));
+        }
+
+        let mut vm = Vm::default();
+        let mut parser = mk_parser(Cursor::new(sources[0]), &vm.globals.slots);
+        parser = parser.declaration();
+        parser = parser.declaration();
+
+        vm.call(Rc::new(parser.end_compiler()), 0);
+
+        assert_eq!(vm.run().unwrap_err().message, "Function 'f' expects 1 to 2 arguments but got 0.");
+    }
+}
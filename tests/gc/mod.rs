@@ -0,0 +1,128 @@
+#[cfg(test)]
+mod gc {
+    use std::io::Cursor;
+
+    use asterisk::{
+        primitives::{primitive::Primitive, value::Value},
+        vm::Vm,
+    };
+
+    const RECURSIVE_CLOSURES: &str = r"
+        fn count_down(n: Int) {
+            fn down(k: Int) {
+                if (k < 1) { return 0; }
+
+                return down(k + -1);
+            }
+
+            return down(n);
+        }
+
+        let mut i = 0;
+
+        while (i < 100) {
+            count_down(3);
+            i = i + 1;
+        }
+    ";
+
+    #[test]
+    fn collects_self_capturing_closures() {
+        let mut vm = Vm::default();
+
+        vm.interpret(Cursor::new(RECURSIVE_CLOSURES)).unwrap();
+
+        assert_eq!(vm.collect_garbage(), 100);
+
+        let stats = vm.gc_stats();
+        assert_eq!(stats.collections, 1);
+        assert_eq!(stats.collected, 100);
+        assert!(stats.tracked < 10);
+    }
+
+    #[test]
+    fn gc_native_collects() {
+        let mut vm = Vm::default();
+        let source = format!("{RECURSIVE_CLOSURES} gc();");
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let stats = vm.gc_stats();
+        assert_eq!(stats.collections, 1);
+        assert_eq!(stats.collected, 100);
+    }
+
+    #[test]
+    fn threshold_triggers_collection() {
+        let mut vm = Vm::default();
+        vm.heap.threshold = 16;
+
+        vm.interpret(Cursor::new(RECURSIVE_CLOSURES)).unwrap();
+
+        let stats = vm.gc_stats();
+        assert!(stats.collections >= 5);
+        assert!(stats.tracked < 16);
+    }
+
+    #[test]
+    fn reachable_cells_survive() {
+        let mut vm = Vm::default();
+        let source = r"
+            fn make_counter() {
+                let mut i = 0;
+
+                fn count() {
+                    i = i + 1;
+
+                    return i;
+                }
+
+                return count;
+            }
+
+            fn fact(n: Int) {
+                fn go(k: Int) {
+                    if (k < 2) { return 1; }
+
+                    return k * go(k + -1);
+                }
+
+                return go;
+            }
+
+            let counter = make_counter();
+            counter();
+            gc();
+            counter();
+            let f = fact(5);
+            gc();
+            let a = counter();
+            let b = f(5);
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        assert_eq!(vm.gc_stats().collected, 0);
+
+        let a = vm.globals.get("a").unwrap();
+        assert_eq!(a.borrow().value, Primitive::Int(3));
+
+        let b = vm.globals.get("b").unwrap();
+        assert_eq!(b.borrow().value, Primitive::Int(120));
+    }
+
+    #[test]
+    fn host_held_cells_survive() {
+        let mut vm = Vm::default();
+        let cell = vm.heap.alloc(Value::default());
+
+        *cell.borrow_mut() = Value { value: Primitive::Ref(cell.clone()), ..Value::default() };
+
+        assert_eq!(vm.collect_garbage(), 0);
+        assert!(matches!(cell.borrow().value, Primitive::Ref(_)));
+
+        /* Cycle is collected once host drops it */
+        drop(cell);
+        assert_eq!(vm.collect_garbage(), 1);
+    }
+}
@@ -0,0 +1,169 @@
+#[cfg(test)]
+mod generics {
+    use std::{io::Cursor, panic::catch_unwind};
+
+    use asterisk::{
+        parser::lexer::{Lexer, Token},
+        primitives::primitive::Primitive,
+        vm::{compiler::compile, globals::Slots, Vm},
+    };
+
+    #[test]
+    fn generic_structs() {
+        let mut vm = Vm::default();
+        let source = r#"
+            struct Pair<A, B> { a: A, b: B }
+            struct Box<T> { v: T? }
+
+            let inferred = Pair { a: 1, b: "x" };
+            let explicit = Pair::<Float, Bool> { a: 1.5, b: true };
+            let updated = Pair { b: "y", ..inferred };
+
+            let mut empty = Box { v: nil };
+            empty.v = "any";
+
+            let mut full = Box { v: 1 };
+            full.v = 2;
+            full.v = nil;
+
+            let s = "${inferred} ${explicit} ${updated} ${empty} ${full}";
+        "#;
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let s = vm.globals.get("s").unwrap();
+        assert_eq!(s.borrow().value.to_string(), [
+            "Pair { a: 1, b: x }",
+            "Pair { a: 1.5, b: true }",
+            "Pair { a: 1, b: y }",
+            "Box { v: any }",
+            "Box { v: nil }",
+        ].join(" "));
+    }
+
+    #[test]
+    fn generic_functions() {
+        let mut vm = Vm::default();
+        let source = r#"
+            fn first<T>(xs: (T, T)) -> T { return xs.0; }
+            fn swap<A, B>(p: (A, B)) -> (B, A) { return (p.1, p.0); }
+            fn keep<T>(a: T, b: T) -> T {
+                let c: T = b;
+                return c;
+            }
+            fn make<T>(f: fn() -> T) -> T { return f(); }
+
+            let a = first((1, 2));
+            let b = first::<String>(("x", "y"));
+            let c = swap((1, "one"));
+            let d = keep(1.5, 2.5);
+            let e = make(fn() -> Bool { return true; });
+        "#;
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let a = vm.globals.get("a").unwrap();
+        assert_eq!(a.borrow().value, Primitive::Int(1));
+
+        let b = vm.globals.get("b").unwrap();
+        assert_eq!(b.borrow().value, Primitive::String("x".into()));
+
+        let c = vm.globals.get("c").unwrap();
+        assert_eq!(c.borrow()._type.to_string(), "(String, Int)");
+
+        let d = vm.globals.get("d").unwrap();
+        assert_eq!(d.borrow().value, Primitive::Float(2.5));
+
+        let e = vm.globals.get("e").unwrap();
+        assert_eq!(e.borrow().value, Primitive::Bool(true));
+    }
+
+    #[test]
+    fn inherited_type_parameters() {
+        let mut vm = Vm::default();
+        let source = r#"
+            struct Box<T> { v: T }
+
+            impl Box {
+                fn get(this) -> T { return this.v; }
+                fn with(this, v: T) { return Box { v: v }; }
+            }
+
+            fn outer<T>(x: T) -> T {
+                fn inner(y: T) -> T {
+                    let z: T = y;
+                    return z;
+                }
+
+                return inner(x);
+            }
+
+            let a = Box { v: 1 }.get();
+            let b = Box { v: "x" }.with("y").get();
+            let c = outer(1.5);
+        "#;
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let a = vm.globals.get("a").unwrap();
+        assert_eq!(a.borrow().value, Primitive::Int(1));
+
+        let b = vm.globals.get("b").unwrap();
+        assert_eq!(b.borrow().value, Primitive::String("y".into()));
+
+        let c = vm.globals.get("c").unwrap();
+        assert_eq!(c.borrow().value, Primitive::Float(1.5));
+    }
+
+    #[test]
+    fn type_arguments_mismatch() {
+        let failing = [
+            (r#"struct P<A, B> { a: A, b: B } let p = P::<Int, String> { a: "x", b: "y" };"#, "Cannot assign String to Int."),
+            (r#"struct P<A, B> { a: A, b: B } let p = P::<Int> { a: 1, b: "y" };"#, "Struct P expects 2 type arguments but got 1."),
+            (r#"struct P<A> { a: A, b: A } let p = P { a: 1, b: "y" };"#, "Cannot assign String to Int."),
+            (r#"struct P<T> { v: T } let mut p = P { v: 1 }; p.v = "s";"#, "Cannot assign String to Int."),
+            (r#"struct P<T> { v: T = 1 } let p = P::<String> {};"#, "Cannot assign Int to String."),
+            (r#"fn f<T>(a: T, b: T) {} f(1, "x");"#, "Function 'f' expects Int on argument 2 but got String."),
+            (r#"fn f<T>(a: T) -> T { return "s"; } f(1);"#, "Function 'f' must return Int but returned String."),
+            (r#"fn f<T>(a: T) -> T { return a; } f::<Int>("s");"#, "Function 'f' expects Int on argument 1 but got String."),
+            (r#"fn f<T>(a: T) {} f::<Int, Int>(1);"#, "Function 'f' expects 1 type arguments but got 2."),
+            (r#"fn f(a) {} f::<Int>(1);"#, "Function 'f' expects 0 type arguments but got 1."),
+            (r#"struct B<T> { v: T } impl B { fn set(this, v: T) {} } B { v: 1 }.set("s");"#, "Function 'B::set' expects Int on argument 2 but got String."),
+            (r#"fn f<T>(a: T) { fn g(b: T) {} g("s"); } f(1);"#, "Function 'g' expects Int on argument 1 but got String."),
+            (r#"len::<Int>("s");"#, "Function 'len' does not take type arguments."),
+        ];
+
+        for (source, message) in failing {
+            let err = Vm::default().interpret(Cursor::new(source)).unwrap_err();
+            assert_eq!(err.message, message, "{source}");
+        }
+    }
+
+    #[test]
+    fn type_parameters_compile_errors() {
+        let failing = [
+            ("fn f<T, T>(a: T) {}", "Type parameter 'T' is already declared."),
+            ("fn f<T>(a: U) {}", "Invalid Var Type."),
+            ("fn f(a) {} f::(1);", "Expect '<' after '::'."),
+        ];
+
+        for (source, message) in failing {
+            let result = catch_unwind(|| compile(Cursor::new(source), &Slots::default()));
+            let error = result.expect_err(source);
+            let error = error.downcast_ref::<String>().unwrap();
+
+            assert!(error.contains(message), "{error}");
+        }
+    }
+
+    #[test]
+    fn lex_path_separator() {
+        let mut lex = Lexer::new(Cursor::new("f::<Int> a: b"));
+
+        let tokens: Vec<Token> = (0..7).map(|_| lex.next()).collect();
+
+        assert_eq!(tokens[1], Token::ColonColon);
+        assert_eq!(tokens[2], Token::Less);
+        assert_eq!(tokens[6], Token::Colon);
+    }
+}
@@ -1,7 +1,7 @@
 #[cfg(test)]
 mod hash_table {
-    use std::{cell::RefCell, rc::Rc};
-    use asterisk::{objects::hash_table::{Entry, HashTable}, primitives::{primitive::Primitive, value::Value}};
+    use std::{cell::RefCell, io::Cursor, rc::Rc};
+    use asterisk::{objects::hash_table::{Entry, HashTable}, primitives::{primitive::Primitive, value::Value}, vm::Vm};
 
     #[test]
     fn insert_get_single() {
@@ -102,13 +102,13 @@ mod hash_table {
         table.insert(
             &String::from("a"),
             Value {
-                value: Primitive::String(String::from("newa")),
+                value: Primitive::String("newa".into()),
                 ..Default::default()
             }
         );
 
         assert!(table.get(&String::from("a")).is_some());
-        assert_eq!(table.get(&String::from("a")).unwrap().borrow().value, Primitive::String(String::from("newa")));
+        assert_eq!(table.get(&String::from("a")).unwrap().borrow().value, Primitive::String("newa".into()));
         assert!(table.get(&String::from("b")).is_some());
         assert_eq!(table.get(&String::from("b")).unwrap().borrow().value, Primitive::Int(2));
         assert!(table.get(&String::from("c")).is_some());
@@ -155,7 +155,7 @@ mod hash_table {
         table.insert(
             &String::from("a"),
             Value {
-                value: Primitive::String(String::from("newa")),
+                value: Primitive::String("newa".into()),
                 ..Default::default()
             }
         );
@@ -163,17 +163,17 @@ mod hash_table {
         table.insert(
             &String::from("c"),
             Value {
-                value: Primitive::String(String::from("newc")),
+                value: Primitive::String("newc".into()),
                 ..Default::default()
             }
         );
 
         assert!(table.get(&String::from("a")).is_some());
-        assert_eq!(table.get(&String::from("a")).unwrap().borrow().value, Primitive::String(String::from("newa")));
+        assert_eq!(table.get(&String::from("a")).unwrap().borrow().value, Primitive::String("newa".into()));
         assert!(table.get(&String::from("b")).is_some());
         assert_eq!(table.get(&String::from("b")).unwrap().borrow().value, Primitive::Int(2));
         assert!(table.get(&String::from("c")).is_some());
-        assert_eq!(table.get(&String::from("c")).unwrap().borrow().value, Primitive::String(String::from("newc")));
+        assert_eq!(table.get(&String::from("c")).unwrap().borrow().value, Primitive::String("newc".into()));
         assert!(table.get(&String::from("d")).is_some());
         assert_eq!(table.get(&String::from("d")).unwrap().borrow().value, Primitive::Int(4));
     }
@@ -201,4 +201,65 @@ mod hash_table {
         entries[2] = Some((String::from("m"), Rc::new(RefCell::new(3))));
         assert_eq!(HashTable::<String, i32>::probe_idx(&entries, 0), 1);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn probe_idx_wraps_around() {
+        let mut entries: Vec<Option<Entry<String, i32>>> = vec![None; 4];
+
+        entries[2] = Some((String::from("n"), Rc::new(RefCell::new(2))));
+        entries[3] = Some((String::from("m"), Rc::new(RefCell::new(3))));
+        assert_eq!(HashTable::<String, i32>::probe_idx(&entries, 2), 0);
+    }
+
+    #[test]
+    fn delete_frees_buckets() {
+        let keys: Vec<String> = (0..50).map(|i| format!("key{i}")).collect();
+        let mut table = HashTable::<String, i32>::default();
+
+        for (i, key) in keys.iter().enumerate() {
+            table.insert(key, i as i32);
+        }
+        let capacity = table.entries.capacity();
+
+        /* Every other key, so remaining ones sit behind freed buckets on their probe sequences */
+        for key in keys.iter().step_by(2) {
+            assert!(table.delete(key));
+        }
+        assert!(!table.delete(&keys[0]));
+
+        assert_eq!(table.count(), 25);
+        for (i, key) in keys.iter().enumerate() {
+            assert_eq!(table.get(key).map(|v| *v.borrow()), (i % 2 == 1).then_some(i as i32), "{key}");
+        }
+
+        /* Re-inserting deleted keys reuses their buckets */
+        for key in keys.iter().step_by(2) {
+            assert!(table.insert(key, 0));
+        }
+        assert_eq!(table.count(), 50);
+        assert_eq!(table.entries.capacity(), capacity);
+    }
+
+    #[test]
+    fn instance_keys() {
+        let mut vm = Vm::default();
+        let source = r"
+            struct Point { x: Int, y: Int }
+            let a = Point { x: 1, y: 2 };
+            let b = Point { x: 1, y: 2 };
+            let c = Point { x: 2, y: 1 };
+        ";
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let key = |name: &str| vm.globals.get(name).unwrap().borrow().clone();
+        let mut table = HashTable::<Value, Value>::default();
+
+        assert!(table.insert(&key("a"), Value { value: Primitive::Int(1), ..Default::default() }));
+        /* Equal instances are the same key */
+        assert!(!table.insert(&key("b"), Value { value: Primitive::Int(2), ..Default::default() }));
+        assert!(table.insert(&key("c"), Value { value: Primitive::Int(3), ..Default::default() }));
+
+        assert_eq!(table.count(), 2);
+        assert_eq!(table.get(&key("a")).unwrap().borrow().value, Primitive::Int(2));
+    }
+}
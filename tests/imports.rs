@@ -1,13 +1,27 @@
+
+mod bytecode;
 mod common;
+mod consts;
 mod control_flow;
 mod examples;
+mod exceptions;
 mod functions;
+mod gc;
+mod generics;
 mod hashtable;
 mod lexer;
+mod limits;
 mod ops;
+mod optionals;
+mod profile;
 mod refs;
+mod results;
 mod scopes;
 mod std_lib;
+mod strings;
 mod structs;
+mod traits;
+mod tuples;
 mod types;
-mod variables;
\ No newline at end of file
+mod variables;
+
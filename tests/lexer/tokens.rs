@@ -1,6 +1,12 @@
 #[cfg(test)]
 mod tokens {
-    use asterisk::{parser::lexer::{Lexer, Token}, primitives::types::Type};
+    use std::panic::catch_unwind;
+
+    use asterisk::{
+        parser::lexer::{Lexer, Position, Token},
+        primitives::types::Type,
+        vm::{compiler::compile, globals::Slots},
+    };
 
     #[test]
     pub fn word_matching() {
@@ -80,4 +86,217 @@ mod tokens {
             assert_eq!(t, l_tok);
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    pub fn string_escapes() {
+        let source = r#" "a\n\t\r\\\"\'\$\u{1F600}\u{e9}" 'it\'s' "#;
+        let mut lex = Lexer::new(std::io::Cursor::new(source));
+
+        assert_eq!(lex.next(), Token::String("a\n\t\r\\\"'$😀é".as_bytes().to_vec()));
+        assert_eq!(lex.next(), Token::String("it's".as_bytes().to_vec()));
+    }
+
+    #[test]
+    pub fn invalid_strings() {
+        for source in [r#" "\q" "#, r#" "\u{110000}" "#, r#" "\u{}" "#, r#" "unterminated "#] {
+            let mut lex = Lexer::new(std::io::Cursor::new(source));
+
+            assert!(matches!(lex.next(), Token::Error(_)), "{source}");
+        }
+    }
+
+    #[test]
+    pub fn raw_strings() {
+        let source = "r\"no \\n ${escapes}\nat all\" r'single'";
+        let mut lex = Lexer::new(std::io::Cursor::new(source));
+
+        assert_eq!(lex.next(), Token::String("no \\n ${escapes}\nat all".as_bytes().to_vec()));
+        assert_eq!(lex.next(), Token::String("single".as_bytes().to_vec()));
+        assert_eq!(lex.position.line, 2);
+    }
+
+    #[test]
+    pub fn interpolation() {
+        let source = r#" "a ${x + { 1 }} b ${"c ${y}"}" "#;
+        let tokens = [
+            Token::Interpolation("a ".as_bytes().to_vec()),
+            Token::Identifier(String::from("x")),
+            Token::Plus,
+            Token::LeftBrace,
+            Token::Integer(1),
+            Token::RightBrace,
+            Token::Interpolation(" b ".as_bytes().to_vec()),
+            Token::Interpolation("c ".as_bytes().to_vec()),
+            Token::Identifier(String::from("y")),
+            Token::String(vec![]),
+            Token::String(vec![]),
+            Token::Eof,
+        ];
+
+        let mut lex = Lexer::new(std::io::Cursor::new(source));
+
+        for t in tokens.into_iter() {
+            assert_eq!(t, lex.next());
+        }
+    }
+
+    #[test]
+    pub fn unicode_identifiers() {
+        let source = "let café = \"naïve\"; _ñ1 变量";
+        let tokens = [
+            Token::Var,
+            Token::Identifier(String::from("café")),
+            Token::Equal,
+            Token::String("naïve".as_bytes().to_vec()),
+            Token::SemiColon,
+            Token::Identifier(String::from("_ñ1")),
+            Token::Identifier(String::from("变量")),
+            Token::Eof,
+        ];
+
+        let mut lex = Lexer::new(std::io::Cursor::new(source));
+
+        for t in tokens.into_iter() {
+            assert_eq!(t, lex.next());
+        }
+
+        /* Not an identifier start */
+        let mut lex = Lexer::new(std::io::Cursor::new("·a"));
+        assert!(matches!(lex.next(), Token::Error(_)));
+    }
+
+    #[test]
+    pub fn positions() {
+        let mut lex = Lexer::new(std::io::Cursor::new("é =\n  \"ü\" x"));
+
+        lex.next();
+        assert_eq!(lex.start, Position { offset: 0, line: 1, column: 1 });
+        lex.next();
+        assert_eq!(lex.start, Position { offset: 3, line: 1, column: 3 });
+        lex.next();
+        assert_eq!(lex.start, Position { offset: 7, line: 2, column: 3 });
+        lex.next();
+        assert_eq!(lex.start, Position { offset: 12, line: 2, column: 7 });
+    }
+
+    #[test]
+    pub fn invalid_utf8() {
+        /* Stray continuation byte, truncated sequence, overlong encoding and surrogate */
+        for bytes in [&b"\x80"[..], b"\xC3", b"\xC0\xAF", b"\xED\xA0\x80"] {
+            let source = [&b"a\n \"\xC3\xA9"[..], bytes, b"\""].concat();
+            let mut lex = Lexer::new(std::io::Cursor::new(source));
+
+            assert_eq!(lex.next(), Token::Identifier(String::from("a")));
+            assert_eq!(lex.next(), Token::Error("Invalid UTF-8."));
+            assert_eq!(lex.start, Position { offset: 6, line: 2, column: 4 });
+            /* Scanning stops on invalid input */
+            assert_eq!(lex.next(), Token::Error("Invalid UTF-8."));
+        }
+    }
+
+    struct FailingReader;
+
+    impl std::io::Read for FailingReader {
+        fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::other("disk on fire"))
+        }
+    }
+
+    #[test]
+    pub fn read_error() {
+        let mut lex = Lexer::new(FailingReader);
+
+        assert_eq!(lex.next(), Token::Error("Could not read source."));
+        assert_eq!(lex.start, Position::default());
+    }
+
+    /* Reads the source, then fails instead of reaching it's end */
+    struct PartialReader(std::io::Cursor<&'static str>);
+
+    impl std::io::Read for PartialReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            match self.0.read(buf)? {
+                0 => Err(std::io::Error::other("disk on fire")),
+                n => Ok(n),
+            }
+        }
+    }
+
+    #[test]
+    pub fn read_error_position() {
+        let mut lex = Lexer::new(PartialReader(std::io::Cursor::new("let a\n  = ")));
+
+        assert_eq!(lex.next(), Token::Var);
+        assert_eq!(lex.next(), Token::Identifier(String::from("a")));
+        assert_eq!(lex.next(), Token::Equal);
+        assert_eq!(lex.next(), Token::Error("Could not read source."));
+        assert_eq!(lex.start, Position { offset: 10, line: 2, column: 5 });
+    }
+
+    #[test]
+    pub fn compile_errors() {
+        let failing = [
+            (&b"let a = 1;\nlet b = \xFF;"[..], "Invalid UTF-8. at line 2, column 9 (byte 19)"),
+            (b"let a = 1;\n\0\nprint a;", "Invalid Token at line 2, column 1 (byte 11)"),
+        ];
+
+        for (source, message) in failing {
+            let result = catch_unwind(|| compile(std::io::Cursor::new(source), &Slots::default()));
+            let error = result.expect_err(message);
+            let error = error.downcast_ref::<String>().unwrap();
+
+            assert!(error.contains(message), "{error}");
+        }
+
+        let result = catch_unwind(|| compile(PartialReader(std::io::Cursor::new("let a = 1;\nlet b")), &Slots::default()));
+        let error = result.expect_err("read error");
+        let error = error.downcast_ref::<String>().unwrap();
+
+        assert!(error.contains("Could not read source. at line 2, column 6 (byte 16)"), "{error}");
+    }
+
+    #[test]
+    pub fn nul_bytes() {
+        /* Only the end of source is Eof, a NUL byte is an invalid token */
+        let mut lex = Lexer::new(std::io::Cursor::new("a\n\0 b"));
+
+        assert_eq!(lex.next(), Token::Identifier(String::from("a")));
+        assert_eq!(lex.next(), Token::Error("Invalid Token"));
+        assert_eq!(lex.start, Position { offset: 2, line: 2, column: 1 });
+        assert_eq!(lex.next(), Token::Identifier(String::from("b")));
+        assert_eq!(lex.next(), Token::Eof);
+
+        /* But it's kept in strings and skipped in comments */
+        let mut lex = Lexer::new(std::io::Cursor::new("\"a\0b\" // \0\n r'\0' c"));
+
+        assert_eq!(lex.next(), Token::String(b"a\0b".to_vec()));
+        assert_eq!(lex.next(), Token::String(b"\0".to_vec()));
+        assert_eq!(lex.next(), Token::Identifier(String::from("c")));
+        assert_eq!(lex.next(), Token::Eof);
+    }
+
+    #[test]
+    pub fn compound_operators() {
+        let source = "+= -= *= /= %= ++ -- % -> - /";
+        let tokens = [
+            Token::PlusEqual,
+            Token::MinusEqual,
+            Token::StarEqual,
+            Token::SlashEqual,
+            Token::PercentEqual,
+            Token::PlusPlus,
+            Token::MinusMinus,
+            Token::Percent,
+            Token::ThinArrow,
+            Token::Minus,
+            Token::Slash,
+            Token::Eof,
+        ];
+
+        let mut lex = Lexer::new(std::io::Cursor::new(source));
+
+        for t in tokens.into_iter() {
+            assert_eq!(t, lex.next());
+        }
+    }
+}
@@ -0,0 +1,242 @@
+#[cfg(test)]
+mod limits {
+    use std::io::Cursor;
+
+    use asterisk::{
+        errors::vm::InterpretResult,
+        primitives::primitive::Primitive,
+        vm::{limits::Limits, Vm},
+    };
+
+    const RECURSION: &str = r"
+        fn deep(n: Int) { return deep(n + 1); }
+    ";
+
+    #[test]
+    fn fuel_suspends_infinite_loop() {
+        let mut vm = Vm { limits: Limits { fuel: Some(1000), ..Limits::default() }, ..Vm::default() };
+        let source = r"
+            let mut n = 0;
+            while (true) { n = n + 1; }
+        ";
+
+        let err = vm.interpret(Cursor::new(source)).unwrap_err();
+        assert_eq!(err._type, InterpretResult::OutOfFuel);
+
+        let n = vm.globals.get("n").unwrap();
+        let Primitive::Int(before) = n.borrow().value else { panic!() };
+        assert!(before > 0);
+
+        vm.refuel(1000);
+        let err = vm.resume().unwrap_err();
+        assert_eq!(err._type, InterpretResult::OutOfFuel);
+
+        let Primitive::Int(after) = n.borrow().value else { panic!() };
+        assert!(after > before);
+    }
+
+    #[test]
+    fn resume_until_finished() {
+        let mut vm = Vm { limits: Limits { fuel: Some(50), ..Limits::default() }, ..Vm::default() };
+        let source = r"
+            fn add(a: Int, b: Int) { return a + b; }
+
+            let mut n = 0;
+            while (n < 100) { n = add(n, 1); }
+        ";
+
+        let mut result = vm.interpret(Cursor::new(source));
+        let mut slices = 1;
+
+        while result.as_ref().is_err_and(|e| e._type == InterpretResult::OutOfFuel) {
+            vm.refuel(50);
+            result = vm.resume();
+            slices += 1;
+        }
+
+        result.unwrap();
+        assert!(slices > 10);
+
+        let n = vm.globals.get("n").unwrap();
+        assert_eq!(n.borrow().value, Primitive::Int(100));
+    }
+
+    #[test]
+    fn out_of_fuel_is_catchable() {
+        let mut vm = Vm { limits: Limits { fuel: Some(1000), ..Limits::default() }, ..Vm::default() };
+        let source = r"
+            let mut kind = '';
+            try { while (true) {} } catch (e) { kind = e.kind; }
+        ";
+
+        /* Suspended on the catch block, which runs once refueled */
+        let err = vm.interpret(Cursor::new(source)).unwrap_err();
+        assert_eq!(err._type, InterpretResult::OutOfFuel);
+
+        let kind = vm.globals.get("kind").unwrap();
+        assert_eq!(kind.borrow().value, Primitive::String("".into()));
+
+        vm.refuel(100);
+        vm.resume().unwrap();
+
+        assert_eq!(kind.borrow().value, Primitive::String("OutOfFuel".into()));
+    }
+
+    #[test]
+    fn call_depth_limit() {
+        let mut vm = Vm { limits: Limits { max_frames: Some(64), ..Limits::default() }, ..Vm::default() };
+        let source = format!("{RECURSION} deep(0);");
+
+        let err = vm.interpret(Cursor::new(source)).unwrap_err();
+
+        assert_eq!(err._type, InterpretResult::CallDepthExceeded);
+        assert!(vm.frames.is_empty());
+    }
+
+    #[test]
+    fn limit_errors_are_catchable() {
+        let mut vm = Vm { limits: Limits { max_frames: Some(64), ..Limits::default() }, ..Vm::default() };
+        let source = format!("
+            {RECURSION}
+            let mut message = '';
+            let mut kind = '';
+            try {{ deep(0); }} catch (e) {{ message = e.message; kind = e.kind; }}
+        ");
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let message = vm.globals.get("message").unwrap();
+        assert_eq!(message.borrow().value, Primitive::String("Maximum call depth of 64 exceeded.".into()));
+
+        let kind = vm.globals.get("kind").unwrap();
+        assert_eq!(kind.borrow().value, Primitive::String("CallDepthExceeded".into()));
+    }
+
+    #[test]
+    fn stack_limit() {
+        let mut vm = Vm { limits: Limits { max_stack: Some(256), ..Limits::default() }, ..Vm::default() };
+        let source = format!("{RECURSION} deep(0);");
+
+        let err = vm.interpret(Cursor::new(source)).unwrap_err();
+
+        assert_eq!(err._type, InterpretResult::StackOverflow);
+    }
+
+    #[test]
+    fn heap_limit() {
+        let mut vm = Vm { limits: Limits { max_heap_bytes: Some(1 << 20), ..Limits::default() }, ..Vm::default() };
+        let source = r"
+            let mut s = 'asterisk';
+            while (true) { s = s + s; }
+        ";
+
+        let err = vm.interpret(Cursor::new(source)).unwrap_err();
+
+        assert_eq!(err._type, InterpretResult::OutOfMemory);
+        assert!(vm.gc_stats().bytes < 1 << 22);
+    }
+
+    #[test]
+    fn heap_limit_local_values() {
+        let mut vm = Vm { limits: Limits { max_heap_bytes: Some(1 << 20), ..Limits::default() }, ..Vm::default() };
+        let source = r"
+            fn grow() {
+                let mut s = 'asterisk';
+                while (true) { s = s + s; }
+            }
+
+            grow();
+        ";
+
+        let err = vm.interpret(Cursor::new(source)).unwrap_err();
+
+        assert_eq!(err._type, InterpretResult::OutOfMemory);
+        assert!(vm.gc_stats().bytes < 1 << 22);
+    }
+
+    #[test]
+    fn heap_limit_checked_before_allocating() {
+        /* Each would build far more than the limit in a single instruction */
+        let sources = [
+            "let s = repeat('ab', 100000000);",
+            "let s = repeat('ab', 100000); let t = replace(s, 'a', repeat('x', 1000));",
+            "let s = repeat('ab', 100000); let t = join((s, s, s, s, s, s, s, s, s, s, s, s), '');",
+            "let s = repeat('ab', 100000); let t = split(s, '');",
+            "let mut s = repeat('ab', 200000); s = s + s + s;",
+            "let mut s = repeat('ab', 200000); s = '${s}${s}${s}';",
+        ];
+
+        for source in sources {
+            let mut vm = Vm { limits: Limits { max_heap_bytes: Some(1 << 20), ..Limits::default() }, ..Vm::default() };
+
+            let err = vm.interpret(Cursor::new(source)).unwrap_err();
+
+            assert_eq!(err._type, InterpretResult::OutOfMemory, "{source}");
+            assert!(vm.gc_stats().bytes < 1 << 20, "{source}");
+        }
+
+        let mut vm = Vm { limits: Limits { max_heap_bytes: Some(1 << 20), ..Limits::default() }, ..Vm::default() };
+        let source = r"
+            let mut kind = '';
+            try { let s = repeat('ab', 100000000); } catch (e) { kind = e.kind; }
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let kind = vm.globals.get("kind").unwrap();
+        assert_eq!(kind.borrow().value, Primitive::String("OutOfMemory".into()));
+    }
+
+    #[test]
+    fn heap_bytes_follow_stack_values() {
+        let limits = Limits { fuel: Some(10_000), max_heap_bytes: Some(1 << 30), ..Limits::default() };
+        let mut vm = Vm { limits, ..Vm::default() };
+        let source = r"
+            struct Holder { s: String }
+
+            fn hold() {
+                let t = (repeat('a', 65536), 1);
+                let h = Holder { s: repeat('b', 65536) };
+                while (true) {}
+            }
+
+            hold();
+        ";
+
+        let err = vm.interpret(Cursor::new(source)).unwrap_err();
+
+        assert_eq!(err._type, InterpretResult::OutOfFuel);
+        assert!(vm.gc_stats().bytes > 2 << 16);
+    }
+
+    #[test]
+    fn heap_bytes_follow_global_values() {
+        let mut vm = Vm { limits: Limits { max_heap_bytes: Some(1 << 30), ..Limits::default() }, ..Vm::default() };
+        let source = r"
+            let mut s = 'asterisk';
+            for (let mut i = 0; i < 10; i += 1) { s = s + s; }
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+        assert!(vm.gc_stats().bytes > 8 << 10);
+
+        vm.interpret(Cursor::new("s = '';")).unwrap();
+        assert!(vm.gc_stats().bytes < 1 << 10);
+    }
+
+    #[test]
+    fn heap_bytes_are_counted_per_vm() {
+        let limits = Limits { max_heap_bytes: Some(1 << 30), ..Limits::default() };
+        let mut big = Vm { limits: limits.clone(), ..Vm::default() };
+        let mut small = Vm { limits, ..Vm::default() };
+
+        big.interpret(Cursor::new("let s = repeat('a', 65536);")).unwrap();
+        small.interpret(Cursor::new("let s = 'a';")).unwrap();
+
+        assert!(big.gc_stats().bytes > 1 << 16);
+        assert!(small.gc_stats().bytes < 1 << 10);
+
+        drop(big);
+        assert!(small.gc_stats().bytes < 1 << 10);
+    }
+}
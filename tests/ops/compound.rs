@@ -0,0 +1,277 @@
+#[cfg(test)]
+mod compound {
+    use std::{io::Cursor, panic::catch_unwind};
+
+    use asterisk::{primitives::primitive::Primitive, vm::{compiler::compile, globals::Slots, Vm}};
+
+    #[test]
+    fn compound_assign_variables() {
+        let mut vm = Vm::default();
+        let source = r"
+            let mut g = 10;
+            g += 5;
+            g -= 3;
+            g *= 4;
+            g /= 6;
+            g %= 5;
+
+            let mut s = 'a';
+            s += 'b';
+
+            fn f() -> Int {
+                let mut total = 0;
+                for (let mut i = 0; i < 5; i += 1) {
+                    total += i * 2;
+                }
+
+                let add = fn (n: Int) { total += n; };
+                add(100);
+
+                return total;
+            }
+
+            let l = f();
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let g = vm.globals.get("g").unwrap();
+        assert_eq!(g.borrow().value, Primitive::Int(3));
+
+        let s = vm.globals.get("s").unwrap();
+        assert_eq!(s.borrow().value, Primitive::String("ab".into()));
+
+        let l = vm.globals.get("l").unwrap();
+        assert_eq!(l.borrow().value, Primitive::Int(120));
+    }
+
+    #[test]
+    fn assign_struct_fields() {
+        let mut vm = Vm::default();
+        let source = r"
+            struct Point { x: Int, y: Float }
+
+            let mut p = Point { x: 1, y: 1.5 };
+            let copy = p;
+            p.x = 10;
+            p.x += 5;
+            p.y *= 2.0;
+
+            fn f() -> Int {
+                let mut q = Point { x: 7, y: 0.0 };
+                q.x %= 4;
+                return q.x;
+            }
+
+            let px = p.x;
+            let py = p.y;
+            let cx = copy.x;
+            let l = f();
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let px = vm.globals.get("px").unwrap();
+        assert_eq!(px.borrow().value, Primitive::Int(15));
+
+        let py = vm.globals.get("py").unwrap();
+        assert_eq!(py.borrow().value, Primitive::Float(3.0));
+
+        /* Instances are copied on assignment */
+        let cx = vm.globals.get("cx").unwrap();
+        assert_eq!(cx.borrow().value, Primitive::Int(1));
+
+        let l = vm.globals.get("l").unwrap();
+        assert_eq!(l.borrow().value, Primitive::Int(3));
+    }
+
+    #[test]
+    fn assign_nested_fields() {
+        let mut vm = Vm::default();
+        let source = r"
+            struct V { v: Int }
+            struct Q { q: V, pair: Tuple }
+            struct P { p: Q }
+
+            let mut p = P { p: Q { q: V { v: 1 }, pair: (1, 2) } };
+            let copy = p;
+            p.p.q.v += 1;
+            p.p.q.v *= 10;
+            p.p.pair.1 = 5;
+
+            fn f() -> Int {
+                let mut local = Q { q: V { v: 3 }, pair: (0, 0) };
+                local.q.v = 4;
+                local.q.v -= 1;
+                return local.q.v;
+            }
+
+            let v = p.p.q.v;
+            let second = p.p.pair.1;
+            let cv = copy.p.q.v;
+            let l = f();
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let v = vm.globals.get("v").unwrap();
+        assert_eq!(v.borrow().value, Primitive::Int(20));
+
+        let second = vm.globals.get("second").unwrap();
+        assert_eq!(second.borrow().value, Primitive::Int(5));
+
+        let cv = vm.globals.get("cv").unwrap();
+        assert_eq!(cv.borrow().value, Primitive::Int(1));
+
+        let l = vm.globals.get("l").unwrap();
+        assert_eq!(l.borrow().value, Primitive::Int(3));
+    }
+
+    #[test]
+    fn compound_assign_checks_are_catchable() {
+        let sources = [
+            /* Immutable bindings */
+            r"let g = 1; try { g += 1; } catch (e) { caught = true; }",
+            r"struct P { x: Int } let p = P { x: 1 }; try { p.x += 1; } catch (e) { caught = true; }",
+            r"struct V { v: Int } struct P { x: V } let p = P { x: V { v: 1 } }; try { p.x.v = 2; } catch (e) { caught = true; }",
+            /* Types */
+            r"let mut g = 1; try { g += 'a'; } catch (e) { caught = true; }",
+            r"struct P { x: Int } let mut p = P { x: 1 }; try { p.x = 1.5; } catch (e) { caught = true; }",
+            r"struct V { v: Int } struct P { x: V } let mut p = P { x: V { v: 1 } }; try { p.x.v += 1.5; } catch (e) { caught = true; }",
+            r"let mut g = 1; try { g %= 0; } catch (e) { caught = true; }",
+        ];
+
+        for source in sources {
+            let mut vm = Vm::default();
+            vm.interpret(Cursor::new(format!("let mut caught = false; {source}"))).unwrap();
+
+            let caught = vm.globals.get("caught").unwrap();
+            assert_eq!(caught.borrow().value, Primitive::Bool(true), "{source}");
+        }
+    }
+
+    #[test]
+    fn increments() {
+        let mut vm = Vm::default();
+        let source = r"
+            struct V { v: Int }
+            struct P { x: Int, q: V }
+
+            let mut g = 1;
+            g++;
+            ++g;
+            g--;
+
+            let mut p = P { x: 1, q: V { v: 10 } };
+            p.x++;
+            --p.q.v;
+            p.q.v--;
+
+            fn f() -> Int {
+                let mut total = 0;
+                for (let mut i = 0; i < 5; i++) {
+                    total++;
+                }
+
+                let dec = fn () { --total; };
+                dec();
+
+                return total;
+            }
+
+            let l = f();
+            let x = p.x;
+            let v = p.q.v;
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let g = vm.globals.get("g").unwrap();
+        assert_eq!(g.borrow().value, Primitive::Int(2));
+
+        let x = vm.globals.get("x").unwrap();
+        assert_eq!(x.borrow().value, Primitive::Int(2));
+
+        let v = vm.globals.get("v").unwrap();
+        assert_eq!(v.borrow().value, Primitive::Int(8));
+
+        let l = vm.globals.get("l").unwrap();
+        assert_eq!(l.borrow().value, Primitive::Int(4));
+    }
+
+    #[test]
+    fn increment_checks() {
+        let sources = [
+            r"let mut g = 9223372036854775807; try { g++; } catch (e) { caught = true; }",
+            r"let mut g = -9223372036854775807 - 1; try { --g; } catch (e) { caught = true; }",
+            r"let g = 1; try { g++; } catch (e) { caught = true; }",
+            r"let mut g = 1.5; try { ++g; } catch (e) { caught = true; }",
+        ];
+
+        for source in sources {
+            let mut vm = Vm::default();
+            vm.interpret(Cursor::new(format!("let mut caught = false; {source}"))).unwrap();
+
+            let caught = vm.globals.get("caught").unwrap();
+            assert_eq!(caught.borrow().value, Primitive::Bool(true), "{source}");
+        }
+
+        let failing = [
+            ("const A = 1; A++;", "Cannot assign to constant"),
+            ("const A = 1; --A;", "Cannot assign to constant"),
+            ("let mut g = 1; g++ + 1;", "Increment can't be used as an operand."),
+            ("let mut g = 1; ++g * 2;", "Increment can't be used as an operand."),
+            ("let mut g = 1; g + ++g;", "Invalid assignment target."),
+            ("let mut g = 1; ++g = 2;", "Expect ';'"),
+            ("fn f() -> Int { return 1; } ++f();", "Invalid assignment target."),
+            ("++1;", "Invalid assignment target."),
+        ];
+
+        for (source, message) in failing {
+            let result = catch_unwind(|| compile(Cursor::new(source), &Slots::default()));
+            let error = result.expect_err(source);
+            let error = error.downcast_ref::<String>().unwrap();
+
+            assert!(error.contains(message), "{source}: {error}");
+        }
+    }
+
+    #[test]
+    fn assignments_are_statements() {
+        let failing = [
+            "let mut i = 1; print i++;",
+            "let mut i = 1; print i += 3;",
+            "let mut i = 1; let j = i += 2;",
+            "let mut i = 1; let j = i = 2;",
+            "let mut i = 1; let j = --i;",
+            "fn f() -> Int { let mut i = 1; return i++; }",
+            "fn f() -> Int { let mut i = 1; print i += 3; return i; }",
+            "struct P { x: Int } let mut p = P { x: 1 }; print p.x = 2;",
+            "struct P { x: Int } let mut p = P { x: 1 }; let x = p.x++;",
+            "let mut i = 1; let mut j = 1; i = (j = 2);",
+            "fn f(n: Int) {} let mut i = 1; f(i += 1);",
+        ];
+
+        for source in failing {
+            let result = catch_unwind(|| compile(Cursor::new(source), &Slots::default()));
+            let error = result.expect_err(source);
+            let error = error.downcast_ref::<String>().unwrap();
+
+            assert!(error.contains("Assignment can only be used as a statement."), "{source}: {error}");
+        }
+
+        /* Statements and for increment clauses are still assignments */
+        let mut vm = Vm::default();
+        let source = r"
+            let mut i = 0;
+            let mut n = 0;
+            for (i = 1; i < 10; i += 2) { n++; }
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let n = vm.globals.get("n").unwrap();
+        assert_eq!(n.borrow().value, Primitive::Int(5));
+        assert!(vm.stack.is_empty());
+    }
+}
@@ -1,6 +1,5 @@
 #[cfg(test)]
 mod div {
-    use std::{cell::RefCell, rc::Rc};
     use asterisk::{primitives::{primitive::Primitive, value::Value}, vm::Vm};
 
     #[test]
@@ -15,12 +14,12 @@ mod div {
             value: Primitive::Int(3),
             ..Default::default()
         };
-        vm.stack.push(Rc::new(RefCell::new(a)));
-        vm.stack.push(Rc::new(RefCell::new(b)));
+        vm.stack.push(a);
+        vm.stack.push(b);
 
         let _ = vm.binary_op("/");
 
-        let result = match vm.stack.pop().unwrap().borrow().value {
+        let result = match vm.stack.pop().unwrap().value {
             Primitive::Int(i) => i,
             _ => panic!("Invalid output result.")
         };
@@ -40,12 +39,12 @@ mod div {
             value: Primitive::Int(10),
             ..Default::default()
         };
-        vm.stack.push(Rc::new(RefCell::new(a)));
-        vm.stack.push(Rc::new(RefCell::new(b)));
+        vm.stack.push(a);
+        vm.stack.push(b);
 
         let _ = vm.binary_op("/");
 
-        let result = match vm.stack.pop().unwrap().borrow().value {
+        let result = match vm.stack.pop().unwrap().value {
             Primitive::Int(i) => i,
             _ => panic!("Invalid output result.")
         };
@@ -65,12 +64,12 @@ mod div {
             value: Primitive::Float(3.0),
             ..Default::default()
         };
-        vm.stack.push(Rc::new(RefCell::new(a)));
-        vm.stack.push(Rc::new(RefCell::new(b)));
+        vm.stack.push(a);
+        vm.stack.push(b);
 
         let _ = vm.binary_op("/");
 
-        let result = match vm.stack.pop().unwrap().borrow().value {
+        let result = match vm.stack.pop().unwrap().value {
             Primitive::Float(f) => f,
             _ => panic!("Invalid output result.")
         };
@@ -90,12 +89,12 @@ mod div {
             value: Primitive::Float(3.0),
             ..Default::default()
         };
-        vm.stack.push(Rc::new(RefCell::new(a)));
-        vm.stack.push(Rc::new(RefCell::new(b)));
+        vm.stack.push(a);
+        vm.stack.push(b);
 
         let _ = vm.binary_op("/");
 
-        let result = match vm.stack.pop().unwrap().borrow().value {
+        let result = match vm.stack.pop().unwrap().value {
             Primitive::Float(f) => f,
             _ => panic!("Invalid output result.")
         };
@@ -20,7 +20,7 @@ mod equal {
             let d2 = true == false;
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
 
         const QNTT: usize = 8;
 
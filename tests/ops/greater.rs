@@ -12,7 +12,7 @@ mod greater {
             let b = 2.0 > 1.0;
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         // var decl
         parser = parser.declaration();
         parser = parser.declaration();
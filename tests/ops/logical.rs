@@ -13,7 +13,7 @@ mod logical {
             let mut b = true || false;
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         // var decl
         parser = parser.declaration();
         parser = parser.declaration();
@@ -36,7 +36,7 @@ mod logical {
             let mut b = true && false;
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         // var decl
         parser = parser.declaration();
         parser = parser.declaration();
@@ -58,7 +58,7 @@ mod logical {
             let mut a = !true;
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         // var decl
         parser = parser.declaration();
 
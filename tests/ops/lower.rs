@@ -14,7 +14,7 @@ mod lower {
             let b = 1.0 < 2.0;
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         // var decl
         parser = parser.declaration();
         parser = parser.declaration();
@@ -4,4 +4,8 @@ mod div;
 mod logical;
 mod greater;
 mod equal;
-mod lower;
\ No newline at end of file
+mod lower;
+mod rem;
+mod compound;
+mod sub;
+mod overload;
@@ -0,0 +1,95 @@
+#[cfg(test)]
+mod overload {
+    use std::io::Cursor;
+
+    use asterisk::{primitives::primitive::Primitive, vm::Vm};
+
+    #[test]
+    fn operators_dispatch_to_methods() {
+        let mut vm = Vm::default();
+        let source = r"
+            struct Vec2 { x: Float, y: Float }
+
+            impl Vec2 {
+                fn add(this, o: Struct) { return Vec2 { x: this.x + o.x, y: this.y + o.y }; }
+                fn sub(this, o: Struct) { return Vec2 { x: this.x - o.x, y: this.y - o.y }; }
+                fn mul(this, k: Float) { return Vec2 { x: this.x * k, y: this.y * k }; }
+                fn div(this, k: Float) { return Vec2 { x: this.x / k, y: this.y / k }; }
+                fn rem(this, k: Float) { return Vec2 { x: this.x % k, y: this.y % k }; }
+                fn neg(this) { return Vec2 { x: -this.x, y: -this.y }; }
+                fn len(this) -> Float { return this.x * this.x + this.y * this.y; }
+                fn lt(this, o: Struct) -> Bool { return this.len() < o.len(); }
+                fn gt(this, o: Struct) -> Bool { return this.len() > o.len(); }
+            }
+
+            let a = Vec2 { x: 1.0, y: 2.0 };
+            let b = Vec2 { x: 3.0, y: 0.5 };
+
+            let results = '${a + b} ${a - b} ${a * 2.0} ${a / 2.0} ${b % 2.0} ${-a}';
+            let compared = (a < b, a > b, a <= b, a >= b);
+
+            let mut c = a;
+            c += b;
+            c -= a;
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let results = vm.globals.get("results").unwrap();
+        assert_eq!(results.borrow().value.to_string(), [
+            "Vec2 { x: 4.0, y: 2.5 }",
+            "Vec2 { x: -2.0, y: 1.5 }",
+            "Vec2 { x: 2.0, y: 4.0 }",
+            "Vec2 { x: 0.5, y: 1.0 }",
+            "Vec2 { x: 1.0, y: 0.5 }",
+            "Vec2 { x: -1.0, y: -2.0 }",
+        ].join(" "));
+
+        let compared = vm.globals.get("compared").unwrap();
+        assert_eq!(compared.borrow().value.to_string(), "(true, false, true, false)");
+
+        let c = vm.globals.get("c").unwrap();
+        assert_eq!(c.borrow().value.to_string(), "Vec2 { x: 3.0, y: 0.5 }");
+    }
+
+    #[test]
+    fn unary_and_equality_methods() {
+        let mut vm = Vm::default();
+        let source = r"
+            struct Flag { on: Bool }
+
+            impl Flag {
+                fn not(this) { return Flag { on: !this.on }; }
+                fn eq(this, other: Struct) -> Bool { return true; }
+            }
+
+            let off = !(Flag { on: true });
+            let equal = off == (Flag { on: true });
+            let different = off != (Flag { on: true });
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let off = vm.globals.get("off").unwrap();
+        assert_eq!(off.borrow().value.to_string(), "Flag { on: false }");
+
+        let equal = vm.globals.get("equal").unwrap();
+        assert_eq!(equal.borrow().value, Primitive::Bool(true));
+
+        let different = vm.globals.get("different").unwrap();
+        assert_eq!(different.borrow().value, Primitive::Bool(false));
+    }
+
+    #[test]
+    fn operators_without_methods() {
+        let failing = [
+            (r"struct P { x: Int } let p = (P { x: 1 }) + 1;", "Operation '+' not allowed between P { x: 1 } and 1."),
+            (r"struct P { x: Int } impl P { fn lt(this, o: Int) { return 1; } } let p = (P { x: 1 }) < 1;", "Method 'P::lt' must return Bool but returned Int."),
+        ];
+
+        for (source, message) in failing {
+            let err = Vm::default().interpret(Cursor::new(source)).unwrap_err();
+            assert_eq!(err.message, message);
+        }
+    }
+}
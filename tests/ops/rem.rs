@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod rem {
+    use std::io::Cursor;
+
+    use asterisk::{primitives::{primitive::Primitive, value::Value}, vm::Vm};
+
+    #[test]
+    fn rem_int_and_float() {
+        let mut vm = Vm::default();
+        let operands = [
+            (Primitive::Int(17), Primitive::Int(5), Primitive::Int(2)),
+            (Primitive::Int(-7), Primitive::Int(2), Primitive::Int(-1)),
+            (Primitive::Float(5.5), Primitive::Float(2.0), Primitive::Float(1.5)),
+        ];
+
+        for (a, b, result) in operands {
+            vm.stack.push(Value { value: a, ..Default::default() });
+            vm.stack.push(Value { value: b, ..Default::default() });
+
+            vm.binary_op("%").unwrap();
+
+            assert_eq!(vm.stack.pop().unwrap().value, result);
+        }
+    }
+
+    #[test]
+    fn rem_by_zero() {
+        let mut vm = Vm::default();
+        vm.stack.push(Value { value: Primitive::Int(1), ..Default::default() });
+        vm.stack.push(Value { value: Primitive::Int(0), ..Default::default() });
+
+        assert!(vm.binary_op("%").is_err());
+    }
+
+    #[test]
+    fn int_overflow_is_catchable() {
+        let sources = [
+            "(-9223372036854775807 - 1) % -1",
+            "(-9223372036854775807 - 1) / -1",
+            "9223372036854775807 + 1",
+            "9223372036854775807 * 2",
+            "-(-9223372036854775807 - 1)",
+        ];
+
+        for source in sources {
+            let mut vm = Vm::default();
+            let script = format!("let mut message = ''; try {{ let n = {source}; }} catch (e) {{ message = e.message; }}");
+
+            vm.interpret(Cursor::new(script)).unwrap();
+
+            let message = vm.globals.get("message").unwrap();
+            assert!(message.borrow().value.to_string().starts_with("Integer overflow"), "{source}");
+        }
+    }
+}
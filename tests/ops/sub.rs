@@ -0,0 +1,31 @@
+#[cfg(test)]
+mod sub {
+    use asterisk::{primitives::{primitive::Primitive, value::Value}, vm::Vm};
+
+    #[test]
+    fn sub_int_and_float() {
+        let mut vm = Vm::default();
+        let operands = [
+            (Primitive::Int(7), Primitive::Int(10), Primitive::Int(-3)),
+            (Primitive::Float(5.5), Primitive::Float(2.0), Primitive::Float(3.5)),
+        ];
+
+        for (a, b, result) in operands {
+            vm.stack.push(Value { value: a, ..Default::default() });
+            vm.stack.push(Value { value: b, ..Default::default() });
+
+            vm.binary_op("-").unwrap();
+
+            assert_eq!(vm.stack.pop().unwrap().value, result);
+        }
+    }
+
+    #[test]
+    fn sub_strings() {
+        let mut vm = Vm::default();
+        vm.stack.push(Value { value: Primitive::String("a".into()), ..Default::default() });
+        vm.stack.push(Value { value: Primitive::String("b".into()), ..Default::default() });
+
+        assert!(vm.binary_op("-").is_err());
+    }
+}
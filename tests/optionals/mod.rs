@@ -0,0 +1,153 @@
+#[cfg(test)]
+mod optionals {
+    use std::{io::Cursor, rc::Rc};
+
+    use asterisk::{primitives::{primitive::Primitive, types::Type}, vm::Vm};
+
+    use crate::common::mk_parser;
+
+    #[test]
+    fn optional_binding_accepts_nil() {
+        let mut vm = Vm::default();
+        let source = r"
+            let mut a: Int? = nil;
+            let b = a == nil;
+            a = 3;
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let a = vm.globals.get(&"a".to_string()).unwrap();
+        assert_eq!(a.borrow().value, Primitive::Int(3));
+        assert_eq!(a.borrow()._type, Type::Optional(Rc::new(Type::Int)));
+
+        let b = vm.globals.get(&"b".to_string()).unwrap();
+        assert_eq!(b.borrow().value, Primitive::Bool(true));
+    }
+
+    #[test]
+    fn non_optional_rejects_nil() {
+        let mut vm = Vm::default();
+        let source = r"
+            let a: Int = nil;
+        ";
+
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
+        parser = parser.declaration();
+
+        vm.call(Rc::new(parser.end_compiler()), 0);
+
+        assert!(vm.run().is_err());
+
+        let err = Vm::default().interpret(Cursor::new("let a: Int? = 'a';")).unwrap_err();
+        assert_eq!(err.message, "Cannot assign String to Int?.");
+    }
+
+    #[test]
+    fn null_coalescing() {
+        let mut vm = Vm::default();
+        let source = r"
+            let a: Int? = nil;
+            let b = a ?? 5;
+            let c: Int? = 2;
+            let d = c ?? 5;
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let b = vm.globals.get(&"b".to_string()).unwrap();
+        assert_eq!(b.borrow().value, Primitive::Int(5));
+
+        let d = vm.globals.get(&"d".to_string()).unwrap();
+        assert_eq!(d.borrow().value, Primitive::Int(2));
+    }
+
+    #[test]
+    fn optional_field_access() {
+        let mut vm = Vm::default();
+        let source = r"
+            struct P { x: Int }
+
+            let p: Struct? = nil;
+            let a = p?.x;
+
+            let q: Struct? = P { x: 1 };
+            let b = q?.x;
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let a = vm.globals.get(&"a".to_string()).unwrap();
+        assert_eq!(a.borrow().value, Primitive::Nil);
+
+        let b = vm.globals.get(&"b".to_string()).unwrap();
+        assert_eq!(b.borrow().value, Primitive::Int(1));
+    }
+
+    #[test]
+    fn struct_optional_field_access() {
+        let mut vm = Vm::default();
+        let source = r"
+            struct P { x: Int }
+
+            let p: P? = nil;
+            let a = p?.x;
+
+            let mut q: P? = nil;
+            q = P { x: 1 };
+            let b = q?.x;
+
+            fn f(p: P?) -> Int { return p?.x ?? 0; }
+            let c = f(nil) + f(P { x: 2 });
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let a = vm.globals.get("a").unwrap();
+        assert_eq!(a.borrow().value, Primitive::Nil);
+
+        let b = vm.globals.get("b").unwrap();
+        assert_eq!(b.borrow().value, Primitive::Int(1));
+
+        let c = vm.globals.get("c").unwrap();
+        assert_eq!(c.borrow().value, Primitive::Int(2));
+
+        let err = Vm::default().interpret(Cursor::new("struct P { x: Int } struct Q { x: Int } let p: P? = Q { x: 1 };")).unwrap_err();
+        assert_eq!(err.message, "Cannot assign Q to P?.");
+    }
+
+    #[test]
+    fn struct_optional_fields() {
+        let mut vm = Vm::default();
+        let source = r"
+            struct M { v: Int }
+            struct N { m: M? }
+
+            let n = N { m: M { v: 1 } };
+            let a = n.m?.v;
+            let b = N {}.m?.v;
+
+            struct A { x: Int }
+            struct B { y: Int }
+            struct Q { a: A?, b: B, c: A }
+
+            let q = Q { a: nil, b: B { y: 2 }, c: A { x: 3 } };
+            let c = q.b.y + q.c.x;
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let a = vm.globals.get("a").unwrap();
+        assert_eq!(a.borrow().value, Primitive::Int(1));
+
+        let b = vm.globals.get("b").unwrap();
+        assert_eq!(b.borrow().value, Primitive::Nil);
+
+        let c = vm.globals.get("c").unwrap();
+        assert_eq!(c.borrow().value, Primitive::Int(5));
+
+        /* Each field gets the struct it was declared with */
+        let source = "struct A { x: Int } struct B { y: Int } struct Q { a: A?, b: B } let q = Q { a: nil, b: A { x: 1 } };";
+        assert!(Vm::default().interpret(Cursor::new(source)).is_err());
+    }
+}
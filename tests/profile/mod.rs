@@ -0,0 +1,48 @@
+#[cfg(test)]
+mod profile {
+    use std::{io::Cursor, path::Path};
+
+    use asterisk::{
+        utils::bench::bench_file,
+        vm::{profile::Profile, Vm},
+    };
+
+    #[test]
+    fn counts_executed_instructions() {
+        let mut vm = Vm { profile: Some(Profile::default()), ..Vm::default() };
+        let source = r"
+            let mut n = 0;
+
+            while (n < 3) {
+                n = n + 1;
+            }
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let profile = vm.profile.unwrap();
+        let counts = profile.by_opcode();
+
+        assert!(counts.contains(&("Add", 3)));
+        assert!(counts.contains(&("Less", 4)));
+        assert!(counts.contains(&("Loop", 3)));
+        assert_eq!(profile.total(), counts.iter().map(|(_, count)| count).sum::<u64>());
+    }
+
+    #[test]
+    fn profile_is_off_by_default() {
+        let mut vm = Vm::default();
+
+        vm.interpret(Cursor::new("let a = 1;")).unwrap();
+
+        assert!(vm.profile.is_none());
+    }
+
+    #[test]
+    fn bench_suite_runs() {
+        let report = bench_file(Path::new("benches/fib.ask")).unwrap();
+
+        assert_eq!(report.name, "fib.ask");
+        assert!(report.profile.total() > 0);
+    }
+}
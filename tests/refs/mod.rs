@@ -0,0 +1,276 @@
+#[cfg(test)]
+mod refs {
+    use std::{io::Cursor, panic::catch_unwind};
+
+    use asterisk::{primitives::primitive::Primitive, vm::{compiler::compile, globals::Slots, Vm}};
+
+    #[test]
+    fn global_ref_follows_reassign() {
+        let mut vm = Vm::default();
+        let source = r"
+            let mut x = 1;
+            let r = &x;
+            x = 2;
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let r = vm.globals.get(&"r".to_string()).unwrap();
+        let Primitive::Ref(ref referenced) = r.borrow().value else {
+            panic!("Expect reference.");
+        };
+
+        assert_eq!(referenced.borrow().value, Primitive::Int(2));
+    }
+
+    #[test]
+    fn local_ref_follows_reassign() {
+        let mut vm = Vm::default();
+        let source = r"
+            fn f() {
+                let mut y = 1;
+                let r = &y;
+                y = 3;
+                /* References to locals can't outlive them */
+                return *r;
+            }
+
+            let r = f();
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let r = vm.globals.get(&"r".to_string()).unwrap();
+        assert_eq!(r.borrow().value, Primitive::Int(3));
+    }
+
+    #[test]
+    fn closure_and_ref_share_local() {
+        let mut vm = Vm::default();
+        let source = r"
+            fn f() {
+                let mut y = 1;
+                let r = &y;
+                let inc = fn () { y = y + 1; };
+                inc();
+                inc();
+                return *r;
+            }
+
+            let r = f();
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let r = vm.globals.get(&"r".to_string()).unwrap();
+        assert_eq!(r.borrow().value, Primitive::Int(3));
+    }
+
+    #[test]
+    fn reading_variable_does_not_move_it() {
+        let mut vm = Vm::default();
+        let source = r"
+            let a = 5;
+            let b = !true;
+            let c = a + a;
+            let d = a;
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let a = vm.globals.get(&"a".to_string()).unwrap();
+        assert_eq!(a.borrow().value, Primitive::Int(5));
+
+        let c = vm.globals.get(&"c".to_string()).unwrap();
+        assert_eq!(c.borrow().value, Primitive::Int(10));
+    }
+
+    #[test]
+    fn deref_reads_referenced_value() {
+        let mut vm = Vm::default();
+        let source = r"
+            let mut x = 1;
+            let r = &x;
+            x = 5;
+            let a = *r + 1;
+
+            struct Point { x: Int }
+            let p = Point { x: 4 };
+            let rp = &p;
+            let b = rp.x;
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let a = vm.globals.get("a").unwrap();
+        assert_eq!(a.borrow().value, Primitive::Int(6));
+
+        let b = vm.globals.get("b").unwrap();
+        assert_eq!(b.borrow().value, Primitive::Int(4));
+    }
+
+    #[test]
+    fn write_through_mutable_reference() {
+        let mut vm = Vm::default();
+        let source = r"
+            let mut g = 1;
+            let rg: &mut Int = &mut g;
+            *rg = *rg + 10;
+
+            fn f() -> Int {
+                let mut y = 1;
+                {
+                    let r = &mut y;
+                    *r = 3;
+                }
+                let shared: &Int = &mut y;
+                return y + *shared;
+            }
+
+            let l = f();
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let g = vm.globals.get("g").unwrap();
+        assert_eq!(g.borrow().value, Primitive::Int(11));
+
+        let l = vm.globals.get("l").unwrap();
+        assert_eq!(l.borrow().value, Primitive::Int(6));
+    }
+
+    #[test]
+    fn set_fields_through_mutable_reference() {
+        let mut vm = Vm::default();
+        let source = r"
+            struct Q { v: Int }
+            struct P { x: Int, q: Q }
+
+            let mut p = P { x: 1, q: Q { v: 1 } };
+            let r = &mut p;
+            r.x = 7;
+            r.x += 1;
+            r.q.v += 1;
+
+            fn set(q) { q.x = q.x * 2; }
+            set(&mut p);
+
+            fn f() -> Int {
+                let mut t = (1, 2);
+                let r = &mut t;
+                r.1 = 5;
+                return t.0 + t.1;
+            }
+
+            let x = p.x;
+            let v = p.q.v;
+            let l = f();
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let x = vm.globals.get("x").unwrap();
+        assert_eq!(x.borrow().value, Primitive::Int(16));
+
+        let v = vm.globals.get("v").unwrap();
+        assert_eq!(v.borrow().value, Primitive::Int(2));
+
+        let l = vm.globals.get("l").unwrap();
+        assert_eq!(l.borrow().value, Primitive::Int(6));
+    }
+
+    #[test]
+    fn reference_misuses_are_catchable() {
+        let sources = [
+            /* Writing through a shared reference */
+            r"let mut x = 1; let r = &x; try { *r = 2; } catch (e) { caught = true; }",
+            /* Mutable reference to an immutable binding */
+            r"let x = 1; try { let r = &mut x; } catch (e) { caught = true; }",
+            /* Shared reference where a mutable one is expected */
+            r"let mut x = 1; try { let r: &mut Int = &x; } catch (e) { caught = true; }",
+            /* Type mismatch through reference */
+            r"let mut x = 1; let r = &mut x; try { *r = 'str'; } catch (e) { caught = true; }",
+            r"try { let v = *1; } catch (e) { caught = true; }",
+            /* Setting a field through a shared reference */
+            r"struct P { x: Int } let mut p = P { x: 1 }; let r = &p; try { r.x = 2; } catch (e) { caught = true; }",
+            /* Reassigning an immutable variable holding a reference */
+            r"let mut x = 1; let mut y = 2; let r = &mut x; try { r = &mut y; } catch (e) { caught = true; }",
+        ];
+
+        for source in sources {
+            let mut vm = Vm::default();
+            vm.interpret(Cursor::new(format!("let mut caught = false; {source}"))).unwrap();
+
+            let caught = vm.globals.get("caught").unwrap();
+            assert_eq!(caught.borrow().value, Primitive::Bool(true), "{source}");
+        }
+    }
+
+    #[test]
+    fn reference_parameters() {
+        let mut vm = Vm::default();
+        let source = r"
+            fn swap(a: &mut Int, b: &mut Int) {
+                let t = *a;
+                *a = *b;
+                *b = t;
+            }
+            fn get(r: &Int) -> Int { return *r; }
+            fn forward(r: &Int) -> &Int { return r; }
+
+            let mut x = 1;
+            let mut y = 2;
+            swap(&mut x, &mut y);
+
+            fn f() -> Int {
+                let mut a = 10;
+                let mut b = 20;
+                swap(&mut a, &mut b);
+                return a - get(&mut b) + *forward(&a);
+            }
+
+            let l = f();
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let x = vm.globals.get("x").unwrap();
+        assert_eq!(x.borrow().value, Primitive::Int(2));
+
+        let y = vm.globals.get("y").unwrap();
+        assert_eq!(y.borrow().value, Primitive::Int(1));
+
+        let l = vm.globals.get("l").unwrap();
+        assert_eq!(l.borrow().value, Primitive::Int(30));
+    }
+
+    #[test]
+    fn local_references_cannot_escape() {
+        let sources = [
+            r"fn f() { let y = 1; return &y; }",
+            r"fn f() { let y = 1; let r = &y; return r; }",
+            r"let x = 0; let mut g = &x; fn f() { let y = 1; g = &y; }",
+            r"fn f() { let a = 1; let mut r = &a; { let y = 1; r = &y; } }",
+        ];
+
+        for source in sources {
+            let result = catch_unwind(|| compile(Cursor::new(source), &Slots::default()));
+            let error = result.expect_err(source);
+            let message = error.downcast_ref::<String>().unwrap();
+
+            /* Both where the reference is taken and where it escapes */
+            assert!(message.contains("Reference to 'y' taken at line 1, column"), "{message}");
+            assert!(message.contains("escapes it's scope at line 1, column"), "{message}");
+        }
+
+        let sources = [
+            r"fn f() -> Int { let y = 1; let r = &y; return *r; }",
+            r"fn f() { let y = 1; { let mut r = &y; r = &y; } }",
+            r"fn f(r: &Int) -> &Int { let y = 1; let mut s = &y; s = r; return s; }",
+        ];
+
+        for source in sources {
+            compile(Cursor::new(source), &Slots::default());
+        }
+    }
+}
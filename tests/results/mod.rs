@@ -0,0 +1,132 @@
+#[cfg(test)]
+mod results {
+    use std::io::Cursor;
+
+    use asterisk::{
+        primitives::{functions::NativeFn, primitive::Primitive, types::{Modifier, Type}, value::Value},
+        vm::Vm,
+    };
+
+    #[test]
+    fn result_values() {
+        let mut vm = Vm::default();
+        let source = r"
+            let a = Ok(1);
+            let b = Err('bad');
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let a = vm.globals.get(&"a".to_string()).unwrap();
+        assert_eq!(a.borrow()._type, Type::Result);
+        assert_eq!(a.borrow().value.to_string(), "Ok(1)");
+
+        let b = vm.globals.get(&"b".to_string()).unwrap();
+        assert_eq!(b.borrow().value.to_string(), "Err(bad)");
+    }
+
+    #[test]
+    fn propagate_unwraps_ok_and_returns_err() {
+        let mut vm = Vm::default();
+        let source = r"
+            fn half(n: Int) -> Result {
+                if (n < 0) { return Err('negative'); }
+                return Ok(n / 2);
+            }
+
+            fn quarter(n: Int) -> Result {
+                let h = half(n)?;
+                return Ok(half(h)?);
+            }
+
+            let a = quarter(8);
+            let b = quarter(-8);
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let a = vm.globals.get(&"a".to_string()).unwrap();
+        assert_eq!(a.borrow().value.to_string(), "Ok(2)");
+
+        let b = vm.globals.get(&"b".to_string()).unwrap();
+        assert_eq!(b.borrow().value.to_string(), "Err(negative)");
+    }
+
+    #[test]
+    fn propagate_rejects_non_result() {
+        let mut vm = Vm::default();
+        let source = r"
+            fn f() { return 1?; }
+            f();
+        ";
+
+        assert!(vm.interpret(Cursor::new(source)).is_err());
+    }
+
+    #[test]
+    fn switch_on_result_variants() {
+        let mut vm = Vm::default();
+        let source = r"
+            let mut a = 0;
+            let mut b = '';
+
+            switch (Ok(41)) {
+                case Ok(v) => { a = v + 1; }
+                case Err(e) => { a = -1; }
+            }
+
+            switch (Err('failed')) {
+                case Ok(v) => { b = 'ok'; }
+                case Err(e) => { b = e; }
+            }
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let a = vm.globals.get(&"a".to_string()).unwrap();
+        assert_eq!(a.borrow().value, Primitive::Int(42));
+
+        let b = vm.globals.get(&"b".to_string()).unwrap();
+        assert_eq!(b.borrow().value, Primitive::String("failed".into()));
+    }
+
+    fn checked_div(args: &[Value]) -> Value {
+        let (Primitive::Int(a), Primitive::Int(b)) = (&args[0].value, &args[1].value) else {
+            panic!("Expect integers.");
+        };
+
+        let int = |i| Value { value: Primitive::Int(i), _type: Type::Int, modifier: Modifier::Const };
+        match b {
+            0 => Value::err(Value { value: Primitive::String("division by zero".into()), _type: Type::String, modifier: Modifier::Const }),
+            b => Value::ok(int(a / b)),
+        }
+    }
+
+    #[test]
+    fn native_returns_result() {
+        let mut vm = Vm::default();
+        vm.globals.insert(
+            &String::from("checked_div"),
+            Value {
+                value: Primitive::NativeFunction(NativeFn { name: "checked_div", arity: 2, _fn: checked_div, deferred: None, allocates: None }),
+                _type: Type::NativeFn,
+                modifier: Modifier::Const,
+            },
+        );
+
+        let source = r"
+            fn ratio(a: Int, b: Int) -> Result { return Ok(checked_div(a, b)? * 10); }
+
+            let a = ratio(4, 2);
+            let b = ratio(4, 0);
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let a = vm.globals.get(&"a".to_string()).unwrap();
+        assert_eq!(a.borrow().value.to_string(), "Ok(20)");
+
+        let b = vm.globals.get(&"b".to_string()).unwrap();
+        assert_eq!(b.borrow().value.to_string(), "Err(division by zero)");
+    }
+}
@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod scopes {
-    use asterisk::vm::Vm;
+    use asterisk::vm::{globals::Slots, Vm};
     use std::{io::Cursor, panic::{catch_unwind, AssertUnwindSafe}, rc::Rc};
 
     use crate::common::mk_parser;
@@ -15,7 +15,7 @@ mod scopes {
             "
         ];
 
-        let mut parser = mk_parser(Cursor::new(source[0]));
+        let mut parser = mk_parser(Cursor::new(source[0]), &Slots::default());
         let result = catch_unwind(AssertUnwindSafe(|| {
                 parser.advance();
                 parser.begin_scope();
@@ -47,7 +47,7 @@ mod scopes {
             "
         ];
 
-        let mut parser = mk_parser(Cursor::new(source[0]));
+        let mut parser = mk_parser(Cursor::new(source[0]), &Slots::default());
         let result = catch_unwind(AssertUnwindSafe(|| {
                 parser.advance();
                 parser.begin_scope();
@@ -90,7 +90,7 @@ mod scopes {
 
             outer();
         ";
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         parser.advance();
         parser = parser.fun_declaration();
         assert_eq!(parser.scopes.len(), 0);
@@ -125,6 +125,6 @@ mod scopes {
             outer();
         ";
 
-        let parser = mk_parser(Cursor::new(source));
+        let parser = mk_parser(Cursor::new(source), &Slots::default());
     }
 }
\ No newline at end of file
@@ -1 +1,2 @@
-mod natives;
\ No newline at end of file
+mod natives;
+mod strings;
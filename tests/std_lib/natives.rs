@@ -13,7 +13,7 @@ mod native {
             print duration();
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         parser = parser.statement();
 
         vm.call(Rc::new(parser.end_compiler()), 0);
@@ -36,7 +36,7 @@ mod native {
             print typeof(n);
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         parser.advance();
         parser = parser.var_declaration();
         parser = parser.statement();
@@ -44,8 +44,8 @@ mod native {
         vm.call(Rc::new(parser.end_compiler()), 0);
         let result = catch_unwind(AssertUnwindSafe(|| {
             let _ = vm.run();
-            // Only the local variable rests on stack
-            assert!(vm.stack.len() == 1);
+            // Arguments are popped along with the function, `n` is a global
+            assert!(vm.stack.len() == 0);
         }));
 
         assert!(result.is_ok());
@@ -61,7 +61,7 @@ mod native {
             print typeof(n);
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         parser.advance();
         parser = parser.var_declaration();
         parser = parser.statement();
@@ -69,10 +69,53 @@ mod native {
         vm.call(Rc::new(parser.end_compiler()), 0);
         let result = catch_unwind(AssertUnwindSafe(|| {
             let _ = vm.run();
-            // Only the local variable rests on stack
-            assert!(vm.stack.len() == 1);
+            // Arguments are popped along with the function, `n` is a global
+            assert!(vm.stack.len() == 0);
         }));
 
         assert!(result.is_ok());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn typeof_function_signature() {
+        let mut vm = Vm::default();
+        vm.init_std_lib();
+
+        let source = r"
+            fn add(a: Int, b: Int) -> Int { return a + b; }
+            let t = typeof(add);
+        ";
+
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
+        parser = parser.declaration();
+        parser = parser.declaration();
+
+        vm.call(Rc::new(parser.end_compiler()), 0);
+        vm.run().unwrap();
+
+        let t = vm.globals.get(&"t".to_string()).unwrap();
+        assert_eq!(t.borrow().value.to_string(), "fn(Int, Int) -> Int");
+    }
+
+    #[test]
+    fn typeof_tuples_and_instances() {
+        let mut vm = Vm::default();
+        let source = r#"
+            struct Point { x: Int, y: Int }
+            let p = Point { x: 1, y: 2 };
+
+            let tuple = typeof((1, "a"));
+            let instance = typeof(p);
+            let blueprint = typeof(Point);
+        "#;
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let expected = [("tuple", "(Int, String)"), ("instance", "Point"), ("blueprint", "Struct")];
+
+        for (name, t) in expected {
+            let value = vm.globals.get(name).unwrap();
+            assert_eq!(value.borrow().value.to_string(), t, "{name}");
+        }
+    }
+}
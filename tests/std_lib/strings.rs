@@ -0,0 +1,127 @@
+#[cfg(test)]
+mod strings {
+    use std::io::Cursor;
+
+    use asterisk::{primitives::primitive::Primitive, vm::Vm};
+
+    fn interpret(source: &str) -> Vm {
+        let mut vm = Vm::default();
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        vm
+    }
+
+    /// Get global's printed value.
+    ///
+    fn global(vm: &Vm, name: &str) -> String {
+        let value = vm.globals.get(name).unwrap();
+        let printed = value.borrow().value.to_string();
+
+        printed
+    }
+
+    #[test]
+    fn length_and_indexing() {
+        let source = r#"
+            let s = "héllo wörld";
+            let a = len(s);
+            let b = char_at(s, 1);
+            let c = substring(s, 6, 11);
+            let d = slice(s, -5, 100);
+            let e = slice(s, 3, 1);
+        "#;
+        let vm = interpret(source);
+
+        assert_eq!(global(&vm, "a"), "11");
+        assert_eq!(global(&vm, "b"), "é");
+        assert_eq!(global(&vm, "c"), "wörld");
+        assert_eq!(global(&vm, "d"), "wörld");
+        assert_eq!(global(&vm, "e"), "");
+    }
+
+    #[test]
+    fn searching() {
+        let source = r#"
+            let s = "¿qué tal?";
+            let a = find(s, "tal");
+            let b = find(s, "nope") ?? -1;
+            let c = contains(s, "qué");
+            let d = starts_with(s, "¿");
+            let e = ends_with(s, "tal");
+        "#;
+        let vm = interpret(source);
+
+        assert_eq!(global(&vm, "a"), "5");
+        assert_eq!(global(&vm, "b"), "-1");
+        assert_eq!(global(&vm, "c"), "true");
+        assert_eq!(global(&vm, "d"), "true");
+        assert_eq!(global(&vm, "e"), "false");
+    }
+
+    #[test]
+    fn transformations() {
+        let source = r#"
+            let a = join(split("a,b,,c", ","), "-");
+            let b = join(split("añb", ""), " ");
+            let c = trim("  padded \n");
+            let d = to_upper("straße");
+            let e = to_lower("ÀBC");
+            let f = replace("one two one", "one", "1");
+            let g = repeat("ab", 3);
+        "#;
+        let vm = interpret(source);
+
+        assert_eq!(global(&vm, "a"), "a-b--c");
+        assert_eq!(global(&vm, "b"), "a ñ b");
+        assert_eq!(global(&vm, "c"), "padded");
+        assert_eq!(global(&vm, "d"), "STRASSE");
+        assert_eq!(global(&vm, "e"), "àbc");
+        assert_eq!(global(&vm, "f"), "1 two 1");
+        assert_eq!(global(&vm, "g"), "ababab");
+    }
+
+    #[test]
+    fn parsing() {
+        let source = r#"
+            let a = parse_int("-42");
+            let b = parse_int("4x");
+            let c = parse_float("2.5");
+            fn next(s: String) -> Result { return Ok(parse_int(s)? + 1); }
+            let d = next("7");
+        "#;
+        let vm = interpret(source);
+
+        assert_eq!(global(&vm, "a"), "Ok(-42)");
+        assert_eq!(global(&vm, "b"), "Err(Invalid integer '4x'.)");
+        assert_eq!(global(&vm, "c"), "Ok(2.5)");
+        assert_eq!(global(&vm, "d"), "Ok(8)");
+    }
+
+    #[test]
+    fn invalid_arguments_are_catchable() {
+        let sources = [
+            r#"try { len(1); } catch (e) { message = e.message; }"#,
+            r#"try { char_at("abc", 3); } catch (e) { message = e.message; }"#,
+            r#"try { substring("abc", 2, 1); } catch (e) { message = e.message; }"#,
+            r#"try { repeat("abc", -1); } catch (e) { message = e.message; }"#,
+            r#"try { repeat("ab", 9223372036854775807); } catch (e) { message = e.message; }"#,
+        ];
+        let messages = [
+            "Function 'len' expects String but got Int.",
+            "Index 3 out of range for string of length 3.",
+            "Range 2..1 out of bounds for string of length 3.",
+            "Cannot repeat string -1 times.",
+            "Cannot repeat string 9223372036854775807 times.",
+        ];
+
+        for (source, message) in sources.into_iter().zip(messages) {
+            let source = format!("let mut message = ''; {source}");
+            let mut vm = Vm::default();
+            vm.interpret(Cursor::new(source)).unwrap();
+
+            let value = vm.globals.get("message").unwrap();
+            assert_eq!(value.borrow().value, Primitive::String(message.into()));
+            assert_eq!(vm.stack.len(), 0);
+        }
+    }
+}
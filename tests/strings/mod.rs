@@ -0,0 +1,127 @@
+#[cfg(test)]
+mod strings {
+    use std::io::Cursor;
+
+    use asterisk::{
+        objects::string::{intern, sweep_strings},
+        primitives::primitive::Primitive,
+        vm::Vm,
+    };
+
+    #[test]
+    fn equal_strings_are_interned() {
+        let a = intern("asterisk");
+        let b = intern(&["aster", "isk"].concat());
+
+        assert_eq!(a, b);
+        assert_ne!(a, intern("other"));
+    }
+
+    #[test]
+    fn runtime_strings_are_interned() {
+        let mut vm = Vm::default();
+        let source = r"
+            let a = 'abc';
+            let b = 'ab' + 'c';
+            let same = a == b;
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let a = vm.globals.get("a").unwrap();
+        let b = vm.globals.get("b").unwrap();
+        assert_eq!(a.borrow().value, b.borrow().value);
+
+        let same = vm.globals.get("same").unwrap();
+        assert_eq!(same.borrow().value, Primitive::Bool(true));
+    }
+
+    #[test]
+    fn long_runtime_strings_compare_contents() {
+        let mut vm = Vm::default();
+        let half = "a".repeat(25);
+        let source = format!("
+            let a = '{half}{half}';
+            let b = '{half}' + '{half}';
+            let same = a == b;
+        ");
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let same = vm.globals.get("same").unwrap();
+        assert_eq!(same.borrow().value, Primitive::Bool(true));
+    }
+
+    #[test]
+    fn unreferenced_strings_are_swept() {
+        let kept = intern("kept");
+        drop(intern("dropped"));
+
+        assert!(sweep_strings() >= 1);
+        assert_eq!(kept, intern("kept"));
+    }
+
+    #[test]
+    fn gc_sweeps_strings() {
+        let mut vm = Vm::default();
+        let source = r"
+            let mut s = '';
+            let mut i = 0;
+
+            while (i < 50) {
+                s = s + 'x';
+                i = i + 1;
+            }
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+        vm.collect_garbage();
+
+        /* Intermediate strings are gone, only the current one is still interned */
+        assert_eq!(sweep_strings(), 0);
+
+        let s = vm.globals.get("s").unwrap();
+        assert_eq!(s.borrow().value, Primitive::String("x".repeat(50).into()));
+    }
+
+    #[test]
+    fn interpolation() {
+        let mut vm = Vm::default();
+        let source = r#"
+            let name = "ann";
+            let age = 41;
+            let greeting = "hello ${name}, you are ${age + 1}";
+            let nested = 'list: ${"[${1.5}, ${true}, ${nil}]"}';
+            let escaped = "\${name}";
+        "#;
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let greeting = vm.globals.get("greeting").unwrap();
+        assert_eq!(greeting.borrow().value, Primitive::String("hello ann, you are 42".into()));
+
+        let nested = vm.globals.get("nested").unwrap();
+        assert_eq!(nested.borrow().value, Primitive::String("list: [1.5, true, nil]".into()));
+
+        let escaped = vm.globals.get("escaped").unwrap();
+        assert_eq!(escaped.borrow().value, Primitive::String("${name}".into()));
+    }
+
+    #[test]
+    fn unicode_identifiers() {
+        let mut vm = Vm::default();
+        let source = r#"
+            let café = "crème";
+            fn größe(ñ: Int) -> Int { return ñ * 2; }
+            let 結果 = größe(21);
+        "#;
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let cafe = vm.globals.get("café").unwrap();
+        assert_eq!(cafe.borrow().value, Primitive::String("crème".into()));
+
+        let result = vm.globals.get("結果").unwrap();
+        assert_eq!(result.borrow().value, Primitive::Int(42));
+    }
+}
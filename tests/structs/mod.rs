@@ -1,8 +1,8 @@
 #[cfg(test)]
 pub mod structs {
-    use std::{io::Cursor, panic::{AssertUnwindSafe, catch_unwind}, rc::Rc};
+    use std::{io::Cursor, rc::Rc};
 
-    use asterisk::{primitives::{primitive::Primitive, types::{Dyn, Modifier, Type}, value::Value}, vm::Vm};
+    use asterisk::{primitives::{functions::NativeFn, primitive::Primitive, types::{Dyn, Modifier, Type}, value::Value}, vm::Vm};
 
     use crate::common::mk_parser;
 
@@ -15,10 +15,10 @@ pub mod structs {
             }
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut vm = Vm::default();
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         parser = parser.declaration();
 
-        let mut vm = Vm::default();
         vm.call(Rc::new(parser.end_compiler()), 0);
 
         vm.run().unwrap();
@@ -34,8 +34,8 @@ pub mod structs {
         assert_eq!(a_struct.name, "S");
         assert_eq!(a_struct.field_count, 2);
         assert_eq!(a.borrow()._type, Type::Struct);
-        assert!(a_struct.field_indices.contains_key(&"id".to_string()));
-        assert!(a_struct.field_indices.contains_key(&"name".to_string()));
+        assert!(a_struct.field_indices.contains_key(&"id".into()));
+        assert!(a_struct.field_indices.contains_key(&"name".into()));
     }
 
     #[test]
@@ -48,13 +48,13 @@ pub mod structs {
             let l = L { name: 'some' };
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut vm = Vm::default();
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         // define_struct()
         parser = parser.declaration();
         // var_declaration()
         parser = parser.declaration();
 
-        let mut vm = Vm::default();
         vm.call(Rc::new(parser.end_compiler()), 0);
 
         vm.run().unwrap();
@@ -67,17 +67,12 @@ pub mod structs {
             _ => panic!("Invalid instance object.")
         };
 
-        let l_struct = match l._struct.borrow().value {
-            Primitive::Struct(ref _struct) => {
-                _struct.clone()
-            }
-            _ => panic!("Invalid instance's blueprint state.")
-        };
+        let l_struct = &l._struct;
 
-        let l_value = l.values[l_struct.field_indices.get("name").unwrap().1].clone();
+        let l_value = l.values[l_struct.field_indices.get(&"name".into()).unwrap().1].clone();
 
         assert_eq!(l_struct.name, "L");
-        assert_eq!(l_value, Value { value: Primitive::String("some".to_string()), _type: Type::String, modifier: Modifier::Const});
+        assert_eq!(l_value, Value { value: Primitive::String("some".into()), _type: Type::String, modifier: Modifier::Const});
     }
 
     #[test]
@@ -94,7 +89,7 @@ pub mod structs {
             let n = l.name;
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         // struct decl
         parser = parser.declaration();
         // var decl
@@ -112,7 +107,7 @@ pub mod structs {
         };
 
         assert_eq!(n._type, Type::String);
-        assert_eq!(n_val, "some");
+        assert_eq!(n_val.as_str(), "some");
     }
 
     #[test]
@@ -130,7 +125,7 @@ pub mod structs {
             }
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         // struct decl
         parser = parser.declaration();
         // var decl
@@ -142,7 +137,7 @@ pub mod structs {
             vm.exec_code().unwrap();
         }
 
-        let n = vm.stack.get(1).unwrap().take();
+        let n = vm.stack.get(1).unwrap().clone();
 
         let n_val = match n.value {
             Primitive::String(str) => str,
@@ -150,7 +145,7 @@ pub mod structs {
         };
 
         assert_eq!(n._type, Type::String);
-        assert_eq!(n_val, "some");
+        assert_eq!(n_val.as_str(), "some");
     }
 
     #[test]
@@ -166,13 +161,13 @@ pub mod structs {
             }
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut vm = Vm::default();
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         // define_struct()
         parser = parser.declaration();
         // var_declaration()
         parser = parser.declaration();
 
-        let mut vm = Vm::default();
         vm.call(Rc::new(parser.end_compiler()), 0);
 
         vm.run().unwrap();
@@ -184,7 +179,7 @@ pub mod structs {
             panic!("Expect struct.")
         };
 
-        let field = _struct.field_indices.get(&"str".to_string()).unwrap();
+        let field = _struct.field_indices.get(&"str".into()).unwrap();
         assert_eq!(field.0, Type::Dyn(Dyn { 0: l }));
     }
 
@@ -204,7 +199,8 @@ pub mod structs {
             let s = S { id: 1, str: l };
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut vm = Vm::default();
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         // define_struct()
         parser = parser.declaration();
         // define_struct()
@@ -215,7 +211,6 @@ pub mod structs {
         // var_declaration()
         parser = parser.declaration();
 
-        let mut vm = Vm::default();
         vm.call(Rc::new(parser.end_compiler()), 0);
 
         vm.run().unwrap();
@@ -227,11 +222,9 @@ pub mod structs {
             panic!();
         };
 
-        let Primitive::Struct(ref _struct) = instance._struct.borrow().value else {
-            panic!()
-        };
+        let _struct = &instance._struct;
 
-        assert_eq!(instance.values[_struct.field_indices.get("str").unwrap().1]._type, Type::Struct);
+        assert_eq!(instance.values[_struct.field_indices.get(&"str".into()).unwrap().1]._type, Type::Struct);
     }
 
     #[test]
@@ -249,7 +242,8 @@ pub mod structs {
             let s = S { id: 1, str: L { name: 'some' } };
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut vm = Vm::default();
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         // define_struct()
         parser = parser.declaration();
         // var_declaration()
@@ -257,7 +251,6 @@ pub mod structs {
         // var_declaration()
         parser = parser.declaration();
 
-        let mut vm = Vm::default();
         vm.call(Rc::new(parser.end_compiler()), 0);
 
         vm.run().unwrap();
@@ -269,11 +262,9 @@ pub mod structs {
             panic!();
         };
 
-        let Primitive::Struct(ref _struct) = instance._struct.borrow().value else {
-            panic!()
-        };
+        let _struct = &instance._struct;
 
-        assert_eq!(instance.values[_struct.field_indices.get("str").unwrap().1]._type, Type::Struct);
+        assert_eq!(instance.values[_struct.field_indices.get(&"str".into()).unwrap().1]._type, Type::Struct);
     }
 
     #[test]
@@ -292,7 +283,8 @@ pub mod structs {
             let n = s.str;
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut vm = Vm::default();
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         // define_struct()
         parser = parser.declaration();
         // define_struct()
@@ -302,7 +294,6 @@ pub mod structs {
         // var_declaration()
         parser = parser.declaration();
 
-        let mut vm = Vm::default();
         vm.call(Rc::new(parser.end_compiler()), 0);
 
         vm.run().unwrap();
@@ -313,12 +304,10 @@ pub mod structs {
             panic!();
         };
 
-        let Primitive::Struct(ref _struct) = instance._struct.borrow().value else {
-            panic!()
-        };
+        let _struct = &instance._struct;
 
         assert_eq!(n._type, Type::Struct);
-        assert_eq!(instance.values[_struct.field_indices.get("name").unwrap().1]._type, Type::String);
+        assert_eq!(instance.values[_struct.field_indices.get(&"name".into()).unwrap().1]._type, Type::String);
     }
 
     #[test]
@@ -335,7 +324,8 @@ pub mod structs {
             let g = n(s);
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut vm = Vm::default();
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         // define_struct()
         parser = parser.declaration();
         // define_struct()
@@ -345,14 +335,9 @@ pub mod structs {
         // var_declaration()
         parser = parser.declaration();
 
-        let mut vm = Vm::default();
         vm.call(Rc::new(parser.end_compiler()), 0);
 
-        let result = catch_unwind(AssertUnwindSafe(|| {
-            let _ = vm.run();
-        }));
-
-        assert!(result.is_err());
+        assert!(vm.run().is_err());
     }
 
     #[test]
@@ -369,7 +354,8 @@ pub mod structs {
             let g = S { other: 'some' };
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut vm = Vm::default();
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         // define_struct()
         parser = parser.declaration();
         // define_struct()
@@ -377,14 +363,9 @@ pub mod structs {
         // var_declaration()
         parser = parser.declaration();
 
-        let mut vm = Vm::default();
         vm.call(Rc::new(parser.end_compiler()), 0);
 
-        let result = catch_unwind(AssertUnwindSafe(|| {
-            let _ = vm.run();
-        }));
-
-        assert!(result.is_err());
+        assert!(vm.run().is_err());
     }
 
     #[test]
@@ -401,7 +382,8 @@ pub mod structs {
             let g = n(s);
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut vm = Vm::default();
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         // define_struct()
         parser = parser.declaration();
         // define_struct()
@@ -411,7 +393,6 @@ pub mod structs {
         // var_declaration()
         parser = parser.declaration();
 
-        let mut vm = Vm::default();
         vm.call(Rc::new(parser.end_compiler()), 0);
 
         vm.run().unwrap();
@@ -422,12 +403,10 @@ pub mod structs {
             panic!();
         };
 
-        let Primitive::Struct(ref _struct) = instance._struct.borrow().value else {
-            panic!()
-        };
+        let _struct = &instance._struct;
 
         assert_eq!(g._type, Type::Struct);
-        assert_eq!(instance.values[_struct.field_indices.get("name").unwrap().1]._type, Type::String);
+        assert_eq!(instance.values[_struct.field_indices.get(&"name".into()).unwrap().1]._type, Type::String);
     }
 
     #[test]
@@ -441,10 +420,10 @@ pub mod structs {
             }
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut vm = Vm::default();
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         parser = parser.declaration();
 
-        let mut vm = Vm::default();
         vm.call(Rc::new(parser.end_compiler()), 0);
 
         // const
@@ -452,9 +431,7 @@ pub mod structs {
         // def_local
         vm.exec_code().unwrap();
 
-        let s = Rc::clone(&vm.stack[0]);
-
-        let s = match s.borrow().value {
+        let s = match vm.stack[0].value {
             Primitive::Struct(ref _struct) => {
                 _struct.clone()
             }
@@ -463,7 +440,310 @@ pub mod structs {
 
         assert_eq!(s.name, "S");
         assert_eq!(s.field_count, 2);
-        assert!(s.field_indices.contains_key(&"id".to_string()));
-        assert!(s.field_indices.contains_key(&"name".to_string()));
+        assert!(s.field_indices.contains_key(&"id".into()));
+        assert!(s.field_indices.contains_key(&"name".into()));
+    }
+
+    #[test]
+    fn struct_default_and_missing_fields() {
+        let mut vm = Vm::default();
+        let source = r"
+            struct Counter { name: String, count: Int = 0, step: Int = -1, note: String? }
+            let c = Counter { name: 'a' };
+            let step = c.step;
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let c = vm.globals.get("c").unwrap();
+        let Primitive::Instance(ref instance) = c.borrow().value else { panic!("Expect instance.") };
+        assert_eq!(instance.values[1].value, Primitive::Int(0));
+        assert_eq!(instance.values[3].value, Primitive::Nil);
+
+        let step = vm.globals.get("step").unwrap();
+        assert_eq!(step.borrow().value, Primitive::Int(-1));
+
+        let err = Vm::default().interpret(Cursor::new(r"
+            struct P { x: Int, y: Int }
+            let p = P { y: 1 };
+        ")).unwrap_err();
+        assert_eq!(err.message, "Missing field 'x' in P.");
+    }
+
+    #[test]
+    fn struct_functional_update() {
+        let mut vm = Vm::default();
+        let source = r"
+            struct P { x: Int, y: Int, z: Int = 3 }
+            let a = P { x: 1, y: 2, z: 4 };
+            let b = P { y: 5, ..a };
+            let c = P { ..b };
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        for (name, values) in [("a", [1, 2, 4]), ("b", [1, 5, 4]), ("c", [1, 5, 4])] {
+            let p = vm.globals.get(name).unwrap();
+            let Primitive::Instance(ref instance) = p.borrow().value else { panic!("Expect instance.") };
+
+            let found: Vec<Primitive> = instance.values.iter().map(|v| v.value.clone()).collect();
+            assert_eq!(found, values.map(Primitive::Int));
+        }
+
+        let err = Vm::default().interpret(Cursor::new(r"
+            struct P { x: Int }
+            struct Q { x: Int }
+            let q = Q { ..P { x: 1 } };
+        ")).unwrap_err();
+        assert_eq!(err.message, "Cannot update Q from P { x: 1 }.");
+    }
+
+    #[test]
+    fn struct_destructuring() {
+        let mut vm = Vm::default();
+        let source = r"
+            struct P { x: Int, y: Int, z: Int }
+            let p = P { x: 1, y: 2, z: 3 };
+            let P { x, y: b, z } = p;
+
+            fn f(p: Struct) {
+                let P { z: mut c, .. } = p;
+                c += 10;
+                return c;
+            }
+            let c = f(p);
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        for (name, value) in [("x", 1), ("b", 2), ("z", 3), ("c", 13)] {
+            let v = vm.globals.get(name).unwrap();
+            assert_eq!(v.borrow().value, Primitive::Int(value), "{name}");
+        }
+
+        let failing = [
+            (r"struct P { x: Int, y: Int } let P { x } = P { x: 1, y: 2 };", "Missing field 'y' in P pattern."),
+            (r"struct P { x: Int } let P { w, .. } = P { x: 1 };", "Invalid field access: w is not in P."),
+            (r"struct P { x: Int } struct Q { x: Int } let Q { x } = P { x: 1 };", "Cannot destructure P { x: 1 } as Q."),
+        ];
+
+        for (source, message) in failing {
+            let err = Vm::default().interpret(Cursor::new(source)).unwrap_err();
+            assert_eq!(err.message, message);
+        }
+    }
+
+    #[test]
+    fn struct_derived_display_equality_and_ordering() {
+        let mut vm = Vm::default();
+        let source = r"
+            struct Point { x: Int, y: Int }
+            struct Line { from: Point, to: Point }
+            let p = Point { x: 1, y: 2 };
+            let shown = '${p} ${Line { from: p, to: Point { x: 0, y: 0 } }}';
+
+            let same = p == (Point { x: 1, y: 2 });
+            let different = p == (Point { x: 1, y: 3 });
+            let lower = p < (Point { x: 1, y: 3 });
+            let greater = p > (Point { x: 0, y: 9 });
+            let tuples = (1, 'b') < (1, 'c');
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let shown = vm.globals.get("shown").unwrap();
+        assert_eq!(
+            shown.borrow().value,
+            Primitive::String("Point { x: 1, y: 2 } Line { from: Point { x: 1, y: 2 }, to: Point { x: 0, y: 0 } }".into())
+        );
+
+        for (name, expected) in [("same", true), ("different", false), ("lower", true), ("greater", true), ("tuples", true)] {
+            let v = vm.globals.get(name).unwrap();
+            assert_eq!(v.borrow().value, Primitive::Bool(expected), "{name}");
+        }
+
+        let err = Vm::default().interpret(Cursor::new(r"
+            struct P { x: Int }
+            struct Q { x: Int }
+            let lower = (P { x: 1 }) < (Q { x: 2 });
+        ")).unwrap_err();
+        assert_eq!(err.message, "Operation '<' not allowed between P { x: 1 } and Q { x: 2 }.");
+    }
+
+    #[test]
+    fn struct_literals_as_operands() {
+        let mut vm = Vm::default();
+        let source = r"
+            struct P { x: Int }
+            let a = P { x: 1 };
+            print a == P { x: 1 };
+
+            let same = a == P { x: 1 };
+            let literals = P { x: 1 } == P { x: 1 };
+            let different = P { x: 1 } != P { x: 2 };
+            let lower = P { x: 1 } < P { x: 2 } and a == P { x: 1 };
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        for name in ["same", "literals", "different", "lower"] {
+            let v = vm.globals.get(name).unwrap();
+            assert_eq!(v.borrow().value, Primitive::Bool(true), "{name}");
+        }
+
+        let err = Vm::default().interpret(Cursor::new("let n = 1; let i = n { x: 1 };")).unwrap_err();
+        assert_eq!(err.message, "Cannot instantiate 1 of type Int.");
+    }
+
+    #[test]
+    fn struct_methods_override_defaults() {
+        let mut vm = Vm::default();
+        let source = r"
+            struct Money { cents: Int, currency: String }
+
+            impl Money {
+                fn to_string(this) -> String { return '${this.cents / 100}.${this.cents % 100} ${this.currency}'; }
+                fn eq(this, other: Struct) -> Bool { return this.cents == other.cents; }
+                /* Reversed on purpose */
+                fn cmp(this, other: Struct) -> Int { return other.cents - this.cents; }
+                fn double(this) { return Money { cents: this.cents * 2, ..this }; }
+            }
+
+            let m = Money { cents: 1050, currency: 'EUR' };
+            let shown = 'cost: ${m}';
+            let equal = m == (Money { cents: 1050, currency: 'USD' });
+            let lower = m < (Money { cents: 1, currency: 'EUR' });
+            let doubled = m.double().cents;
+            let bound = m.double;
+            let called = '${bound()}';
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let shown = vm.globals.get("shown").unwrap();
+        assert_eq!(shown.borrow().value, Primitive::String("cost: 10.50 EUR".into()));
+
+        let equal = vm.globals.get("equal").unwrap();
+        assert_eq!(equal.borrow().value, Primitive::Bool(true));
+
+        let lower = vm.globals.get("lower").unwrap();
+        assert_eq!(lower.borrow().value, Primitive::Bool(true));
+
+        let doubled = vm.globals.get("doubled").unwrap();
+        assert_eq!(doubled.borrow().value, Primitive::Int(2100));
+
+        let called = vm.globals.get("called").unwrap();
+        assert_eq!(called.borrow().value, Primitive::String("21.0 EUR".into()));
+    }
+
+    #[test]
+    fn struct_to_string_in_nested_values() {
+        let mut vm = Vm::default();
+        let source = r"
+            struct Money { cents: Int }
+            struct Wallet { owner: String, cash: Money }
+            struct Broken { n: Int }
+
+            impl Money { fn to_string(this) -> String { return '${this.cents}c'; } }
+            impl Broken { fn to_string(this) -> String { return '${10 / this.n}'; } }
+
+            let m = Money { cents: 5 };
+            let nested = '${(m, 1)} ${Wallet { owner: 'ann', cash: m }} ${Ok((m,))}';
+            let joined = join((m, 'and', Money { cents: 7 }), ' ');
+
+            let mut message = '';
+            try { let s = '${(m, Broken { n: 0 })}'; } catch (e) { message = e.message; }
+            let after = '${Broken { n: 2 }}';
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let nested = vm.globals.get("nested").unwrap();
+        assert_eq!(nested.borrow().value, Primitive::String("(5c, 1) Wallet { owner: ann, cash: 5c } Ok((5c,))".into()));
+
+        let joined = vm.globals.get("joined").unwrap();
+        assert_eq!(joined.borrow().value, Primitive::String("5c and 7c".into()));
+
+        let message = vm.globals.get("message").unwrap();
+        assert_eq!(message.borrow().value, Primitive::String("Division by zero.".into()));
+
+        let after = vm.globals.get("after").unwrap();
+        assert_eq!(after.borrow().value, Primitive::String("5".into()));
+
+        assert!(vm.renders.is_empty());
+        assert_eq!(vm.stack.len(), 0);
+    }
+
+    #[test]
+    fn struct_hash_agrees_with_equality() {
+        let mut vm = Vm::default();
+        let source = r"
+            struct Point { x: Int, y: Int }
+            struct Money { cents: Int, currency: String }
+
+            impl Money {
+                fn eq(this, other: Struct) -> Bool { return this.cents == other.cents; }
+                fn hash(this) -> Int { return hash(this.cents); }
+            }
+
+            let p = Point { x: 1, y: 2 };
+            let same = hash(p) == hash(Point { x: 1, y: 2 });
+            let different = hash(p) == hash(Point { x: 2, y: 1 });
+            let nested = hash((p, 0.0)) == hash((Point { x: 1, y: 2 }, -0.0));
+            let overridden = hash(Money { cents: 1, currency: 'EUR' }) == hash(Money { cents: 1, currency: 'USD' });
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        for (name, expected) in [("same", true), ("different", false), ("nested", true), ("overridden", true)] {
+            let v = vm.globals.get(name).unwrap();
+            assert_eq!(v.borrow().value, Primitive::Bool(expected), "{name}");
+        }
+    }
+
+    #[test]
+    fn host_natives_do_not_call_methods() {
+        fn constant(_args: &[Value]) -> Value {
+            Value { value: Primitive::Int(7), _type: Type::Int, modifier: Modifier::Const }
+        }
+
+        let mut vm = Vm::default();
+        /* Same name as the std lib native, but not marked as deferred */
+        vm.globals.insert(
+            "digest",
+            Value {
+                value: Primitive::NativeFunction(NativeFn { name: "hash", arity: 1, _fn: constant, deferred: None, allocates: None }),
+                _type: Type::NativeFn,
+                modifier: Modifier::Const,
+            },
+        );
+
+        let source = r"
+            struct P { x: Int }
+            impl P { fn hash(this) -> Int { return 1; } }
+
+            let h = digest(P { x: 1 });
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let h = vm.globals.get("h").unwrap();
+        assert_eq!(h.borrow().value, Primitive::Int(7));
+    }
+
+    #[test]
+    fn struct_methods_errors() {
+        let failing = [
+            (r"struct P { x: Int } impl P { fn to_string(this) { return 1; } } let s = '${P { x: 1 }}';", "Method 'P::to_string' must return String but returned Int."),
+            (r"struct P { x: Int } impl P { fn x(this) { return 1; } }", "Method 'x' conflicts with field of P."),
+            (r"let n = 1; impl n { fn f(this) { return 1; } }", "Cannot implement methods for Int."),
+            (r"struct P { x: Int } let y = (P { x: 1 }).y;", "Invalid field access: y is not in P."),
+            (r"struct P { x: Int } impl P { fn hash(this) { return 'h'; } } let h = hash(P { x: 1 });", "Method 'P::hash' must return Int but returned String."),
+        ];
+
+        for (source, message) in failing {
+            let err = Vm::default().interpret(Cursor::new(source)).unwrap_err();
+            assert_eq!(err.message, message);
+        }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,125 @@
+#[cfg(test)]
+mod traits {
+    use std::{io::Cursor, panic::catch_unwind};
+
+    use asterisk::{
+        primitives::primitive::Primitive,
+        vm::{compiler::compile, globals::Slots, Vm},
+    };
+
+    #[test]
+    fn trait_dispatch() {
+        let mut vm = Vm::default();
+        let source = r#"
+            trait Shape {
+                fn area(this) -> Float;
+                fn name(this) -> String;
+            }
+
+            struct Circle { r: Float }
+            struct Square { s: Float }
+
+            impl Shape for Circle {
+                fn area(this) -> Float { return 3.0 * this.r * this.r; }
+                fn name(this) -> String { return "circle"; }
+            }
+
+            /* Methods may come from other impl blocks */
+            impl Square {
+                fn area(this) -> Float { return this.s * this.s; }
+            }
+
+            impl Shape for Square {
+                fn name(this) -> String { return "square"; }
+            }
+
+            fn describe(s: Shape) -> String { return "${s.name()} ${s.area()}"; }
+
+            let described = "${describe(Circle { r: 1.0 })}, ${describe(Square { s: 2.0 })}";
+
+            let mut shape: Shape = Circle { r: 2.0 };
+            shape = Square { s: 3.0 };
+            let area = shape.area();
+
+            struct Holder { shape: Shape, other: Shape? }
+            let holder = Holder { shape: Circle { r: 1.0 } };
+            let held = holder.shape.name();
+        "#;
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let described = vm.globals.get("described").unwrap();
+        assert_eq!(described.borrow().value, Primitive::String("circle 3.0, square 4.0".into()));
+
+        let shape = vm.globals.get("shape").unwrap();
+        assert_eq!(shape.borrow()._type.to_string(), "Shape");
+
+        let area = vm.globals.get("area").unwrap();
+        assert_eq!(area.borrow().value, Primitive::Float(9.0));
+
+        let held = vm.globals.get("held").unwrap();
+        assert_eq!(held.borrow().value, Primitive::String("circle".into()));
+    }
+
+    #[test]
+    fn trait_declared_in_function() {
+        let mut vm = Vm::default();
+        let source = r#"
+            struct P { x: Int }
+            impl P { fn get(this) { return this.x; } }
+
+            fn read(p) {
+                trait Get { fn get(this); }
+
+                let g: Get = p;
+                return g.get();
+            }
+
+            let x = read(P { x: 7 });
+        "#;
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let x = vm.globals.get("x").unwrap();
+        assert_eq!(x.borrow().value, Primitive::Int(7));
+    }
+
+    #[test]
+    fn trait_conformance_errors() {
+        let failing = [
+            ("trait S { fn a(this); } struct P { x: Int } impl S for P {}", "P does not implement 'a' of S."),
+            ("trait S { fn a(this); } struct P { x: Int } impl S for P { fn a(this, y) {} }", "Method 'P::a' takes 2 parameters but S declares 1."),
+            ("trait S { fn a(this); } let x = 1; impl S for x {}", "Cannot implement S for Int."),
+            ("trait S { fn a(this); } struct P { x: Int } fn f(s: S) {} f(P { x: 1 });", "Function 'f' expects S on argument 1 but got P: P does not implement 'a' of S."),
+            ("trait S { fn a(this); } struct P { x: Int } fn f() -> S { return P { x: 1 }; } f();", "Function 'f' must return S but returned P: P does not implement 'a' of S."),
+            ("trait S { fn a(this); } let s: S = 1;", "Cannot assign Int to S."),
+            ("trait S { fn a(this); } struct P { x: Int } let s: S = P { x: 1 };", "Cannot assign P to S: P does not implement 'a' of S."),
+            ("trait S { fn a(this); } struct P { x: Int } fn f() { let s: S = P { x: 1 }; } f();", "Cannot assign P to S: P does not implement 'a' of S."),
+            ("trait S { fn a(this, y); } struct P { x: Int } impl P { fn a(this) {} } let s: S = P { x: 1 };", "Cannot assign P to S: Method 'P::a' takes 1 parameters but S declares 2."),
+        ];
+
+        for (source, message) in failing {
+            let err = Vm::default().interpret(Cursor::new(source)).unwrap_err();
+            assert_eq!(err.message, message, "{source}");
+        }
+    }
+
+    #[test]
+    fn trait_compile_errors() {
+        let failing = [
+            ("trait S { fn a(this); } struct P { x: Int } impl S for P { fn b(this) {} }", "Method 'b' is not a member of trait S."),
+            ("struct P { x: Int } impl S for P {}", "Unknown trait 'S'."),
+            ("trait S { fn a(this); fn a(this); }", "Method 'a' is already declared in S."),
+            ("trait S { fn a(this) }", "Expect ';' after method declaration."),
+            ("fn f() { trait S { fn a(this); } } let s: S = 1;", "Invalid Var Type."),
+        ];
+
+        for (source, message) in failing {
+            let result = catch_unwind(|| compile(Cursor::new(source), &Slots::default()));
+            let error = result.expect_err(source);
+            let error = error.downcast_ref::<String>().unwrap();
+
+            assert!(error.contains(message), "{error}");
+        }
+    }
+}
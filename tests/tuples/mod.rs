@@ -0,0 +1,164 @@
+#[cfg(test)]
+mod tuples {
+    use std::{io::Cursor, panic::catch_unwind};
+
+    use asterisk::{
+        parser::lexer::{Lexer, Token},
+        primitives::{primitive::Primitive, types::Type},
+        vm::{compiler::compile, globals::Slots, Vm},
+    };
+
+    #[test]
+    fn tuple_literals() {
+        let mut vm = Vm::default();
+        let source = r#"
+            let t = (1, "a", true);
+            let single = (1,);
+            let empty = ();
+            let grouped = (1);
+            let s = "${t} ${single} ${empty}";
+        "#;
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let t = vm.globals.get("t").unwrap();
+        assert_eq!(t.borrow()._type.to_string(), "(Int, String, Bool)");
+
+        let single = vm.globals.get("single").unwrap();
+        assert_eq!(single.borrow()._type.to_string(), "(Int,)");
+
+        let grouped = vm.globals.get("grouped").unwrap();
+        assert_eq!(grouped.borrow().value, Primitive::Int(1));
+
+        let s = vm.globals.get("s").unwrap();
+        assert_eq!(s.borrow().value, Primitive::String("(1, a, true) (1,) ()".into()));
+    }
+
+    #[test]
+    fn positional_access() {
+        let mut vm = Vm::default();
+        let source = r#"
+            let t = (1, ("a", 2.5), true);
+            let a = t.0;
+            let b = t.1.1;
+            fn f() {
+                let mut p = (1, 2);
+                p.0 = 5;
+                p.1 += 3;
+                return p.0 + p.1;
+            }
+            let c = f();
+        "#;
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let a = vm.globals.get("a").unwrap();
+        assert_eq!(a.borrow().value, Primitive::Int(1));
+
+        let b = vm.globals.get("b").unwrap();
+        assert_eq!(b.borrow().value, Primitive::Float(2.5));
+
+        let c = vm.globals.get("c").unwrap();
+        assert_eq!(c.borrow().value, Primitive::Int(10));
+    }
+
+    #[test]
+    fn index_after_dot_is_not_float() {
+        let mut lex = Lexer::new(Cursor::new("t.0.1 1.5"));
+
+        let tokens: Vec<Token> = (0..6).map(|_| lex.next()).collect();
+
+        assert_eq!(tokens, [
+            Token::Identifier("t".to_string()),
+            Token::Dot,
+            Token::Integer(0),
+            Token::Dot,
+            Token::Integer(1),
+            Token::Float(1.5),
+        ]);
+    }
+
+    #[test]
+    fn tuple_annotations() {
+        let mut vm = Vm::default();
+        let source = r#"
+            let t: (Int, String?) = (1, nil);
+            let any: Tuple = (1, 2, 3);
+            let grouped: (Int) = 1;
+            fn f(p: (Int, Int)) -> (Int, Int) { return (p.1, p.0); }
+            let swapped = f((1, 2));
+        "#;
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let swapped = vm.globals.get("swapped").unwrap();
+        assert_eq!(swapped.borrow()._type, Type::Tuple(Some([Type::Int, Type::Int].into())));
+        assert_eq!(swapped.borrow().value.to_string(), "(2, 1)");
+
+        let failing = [
+            (r#"let t: (Int, String) = (1, 2);"#, "Cannot assign (Int, Int) to (Int, String)."),
+            (r#"let t: (Int, Int) = (1, 2, 3);"#, "Cannot assign (Int, Int, Int) to (Int, Int)."),
+            (r#"fn f() { let t: (Int, String) = ("a", 1); } f();"#, "Cannot assign (String, Int) to (Int, String)."),
+            (r#"let mut t = (1, 2); t.0 = "a";"#, "Cannot assign String to Int."),
+            (r#"let t = (1, 2); let x = t.2;"#, "Index 2 out of range for tuple of 2 items."),
+        ];
+
+        for (source, message) in failing {
+            let err = Vm::default().interpret(Cursor::new(source)).unwrap_err();
+            assert_eq!(err.message, message, "{source}");
+        }
+    }
+
+    #[test]
+    fn destructuring() {
+        let mut vm = Vm::default();
+        let source = r#"
+            fn div(a: Int, b: Int) -> (Int, Int) {
+                return (a / b, a % b);
+            }
+
+            let (q, r) = div(17, 5);
+
+            fn f() {
+                let (mut a, b): (Int, String) = (1, "b");
+                a += 1;
+                /* Outer variables are read before new ones are declared */
+                let (a, b) = (b, a);
+                return "${a}${b}";
+            }
+            let s = f();
+        "#;
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let q = vm.globals.get("q").unwrap();
+        assert_eq!(q.borrow().value, Primitive::Int(3));
+
+        let r = vm.globals.get("r").unwrap();
+        assert_eq!(r.borrow().value, Primitive::Int(2));
+
+        let s = vm.globals.get("s").unwrap();
+        assert_eq!(s.borrow().value, Primitive::String("b2".into()));
+
+        let failing = [
+            r#"let (a, b) = (1, 2, 3);"#,
+            r#"let (a, b) = 1;"#,
+            r#"let (a, b) = (1, 2); a = 3;"#,
+        ];
+
+        for source in failing {
+            assert!(Vm::default().interpret(Cursor::new(source)).is_err(), "{source}");
+        }
+    }
+
+    #[test]
+    fn tuples_cannot_carry_escaping_references() {
+        let source = r"fn f() { let y = 1; return (1, &y); }";
+
+        let result = catch_unwind(|| compile(Cursor::new(source), &Slots::default()));
+        let error = result.expect_err(source);
+        let message = error.downcast_ref::<String>().unwrap();
+
+        assert!(message.contains("Reference to 'y' taken at line 1, column"), "{message}");
+    }
+}
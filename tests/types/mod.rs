@@ -1,7 +1,7 @@
 #[cfg(test)]
 mod types {
     use std::{io::Cursor, rc::Rc};
-    use asterisk::{primitives::types::Type, vm::Vm};
+    use asterisk::{primitives::{primitive::Primitive, types::{Signature, Type}}, vm::Vm};
 
     use crate::common::mk_parser;
 
@@ -12,7 +12,7 @@ mod types {
             let a = 32;
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         parser.advance();
         parser = parser.var_declaration();
 
@@ -30,7 +30,7 @@ mod types {
             let a: Int = 32;
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         parser.advance();
         parser = parser.var_declaration();
 
@@ -48,7 +48,7 @@ mod types {
             let a = 32.0;
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         parser.advance();
         parser = parser.var_declaration();
 
@@ -66,7 +66,7 @@ mod types {
             let a: Float = 10.5;
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         parser.advance();
         parser = parser.var_declaration();
 
@@ -84,7 +84,7 @@ mod types {
             let a = false;
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         parser.advance();
         parser = parser.var_declaration();
 
@@ -102,7 +102,7 @@ mod types {
             let a: Bool = true;
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         parser.advance();
         parser = parser.var_declaration();
 
@@ -120,7 +120,7 @@ mod types {
             let a = 't';
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         parser.advance();
         parser = parser.var_declaration();
 
@@ -138,7 +138,7 @@ mod types {
             let a: String = 'xyz';
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         parser.advance();
         parser = parser.var_declaration();
 
@@ -157,7 +157,7 @@ mod types {
             let a = f;
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         parser.advance();
         parser = parser.fun_declaration();
 
@@ -168,7 +168,7 @@ mod types {
         let _ = vm.run();
 
         let a = vm.globals.get(&String::from("a")).unwrap();
-        assert_eq!(a.borrow()._type, Type::Fn);
+        assert_eq!(a.borrow()._type, Type::Fn(Some(Rc::new(Signature::default()))));
     }
 
     #[test]
@@ -184,7 +184,7 @@ mod types {
             let a = f();
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         parser.advance();
         parser = parser.fun_declaration();
 
@@ -198,7 +198,88 @@ mod types {
         assert_eq!(a.borrow()._type, Type::Closure);
     }
 
+    #[test]
+    fn function_signature() {
+        let mut vm = Vm::default();
+        let source = r"
+            fn is_zero(n: Int) -> Bool { return n == 0; }
+            let cb: fn(Int) -> Bool = is_zero;
+        ";
+
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
+        parser.advance();
+        parser = parser.fun_declaration();
+
+        parser.advance();
+        parser = parser.var_declaration();
+
+        vm.call(Rc::new(parser.end_compiler()), 0);
+        vm.run().unwrap();
+
+        let cb = vm.globals.get(&String::from("cb")).unwrap();
+        assert_eq!(cb.borrow()._type.to_string(), "fn(Int) -> Bool");
+    }
+
+    #[test]
+    fn function_signature_mismatch() {
+        let mut vm = Vm::default();
+        let source = r"
+            fn is_zero(n: Int) -> Bool { return n == 0; }
+            let cb: fn(String) -> Bool = is_zero;
+        ";
+
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
+        parser.advance();
+        parser = parser.fun_declaration();
+
+        parser.advance();
+        parser = parser.var_declaration();
+
+        vm.call(Rc::new(parser.end_compiler()), 0);
+
+        assert!(vm.run().is_err());
+    }
+
     #[test]
     fn references() {}
+
+    #[test]
+    fn type_aliases() {
+        let mut vm = Vm::default();
+        let source = r#"
+            type UserId = Int;
+            type Name = String?;
+            type Point = (Int, Int);
+            type Callback = fn(UserId) -> Bool;
+
+            struct User { id: UserId, name: Name }
+
+            fn is_root(id: UserId) -> Bool { return id == 0; }
+
+            let id: UserId = 7;
+            let name: Name = nil;
+            let p: Point = (1, 2);
+            let cb: Callback = is_root;
+            let user = User { id: 0 };
+            let root = cb(user.id);
+        "#;
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let name = vm.globals.get("name").unwrap();
+        assert_eq!(name.borrow()._type.to_string(), "String?");
+
+        let p = vm.globals.get("p").unwrap();
+        assert_eq!(p.borrow()._type.to_string(), "(Int, Int)");
+
+        let cb = vm.globals.get("cb").unwrap();
+        assert_eq!(cb.borrow()._type.to_string(), "fn(Int) -> Bool");
+
+        let root = vm.globals.get("root").unwrap();
+        assert_eq!(root.borrow().value, Primitive::Bool(true));
+
+        let source = r#"type UserId = Int; let id: UserId = "7";"#;
+        assert!(Vm::default().interpret(Cursor::new(source)).is_err());
+    }
 }
     
\ No newline at end of file
@@ -16,12 +16,12 @@ mod global {
             "
         ];
 
-        let mut parser = mk_parser(Cursor::new(sources[0]));
+        let mut parser = mk_parser(Cursor::new(sources[0]), &vm.globals.slots);
         parser.advance();
         parser = parser.var_declaration();
 
-        // "a" and "32"
-        assert_eq!(parser.function.chunk.constants.len(), 2);
+        // Only "32", "a" is resolved to a global slot
+        assert_eq!(parser.function.chunk.constants.len(), 1);
         // No locals were added
         assert_eq!(parser.scopes.len(), 0);
 
@@ -38,7 +38,7 @@ mod global {
 
         assert_eq!(var_value, 32);
 
-        let mut parser = mk_parser(Cursor::new(sources[1]));
+        let mut parser = mk_parser(Cursor::new(sources[1]), &vm.globals.slots);
         parser = parser.statement();
 
         vm.call(Rc::new(parser.end_compiler()), 0);
@@ -65,12 +65,12 @@ mod global {
         ];
 
         // 1
-        let mut parser = mk_parser(Cursor::new(sources[0]));
+        let mut parser = mk_parser(Cursor::new(sources[0]), &vm.globals.slots);
         parser.advance();
         parser = parser.var_declaration();
 
-        // "a" and "32"
-        assert_eq!(parser.function.chunk.constants.len(), 2);
+        // Only "32", "a" is resolved to a global slot
+        assert_eq!(parser.function.chunk.constants.len(), 1);
         // No locals were added
         assert_eq!(parser.scopes.len(), 0);
 
@@ -88,7 +88,7 @@ mod global {
         assert_eq!(var_value, 32);
 
         // 2
-        let mut parser = mk_parser(Cursor::new(sources[1]));
+        let mut parser = mk_parser(Cursor::new(sources[1]), &vm.globals.slots);
         parser = parser.statement();
 
         vm.call(Rc::new(parser.end_compiler()), 0);
@@ -104,4 +104,58 @@ mod global {
 
         assert_eq!(var_value, 2);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn forward_reference_inside_function() {
+        let mut vm = Vm::default();
+        let source = r"
+            fn first() { return second(); }
+            fn second() { return 7; }
+
+            let r = first();
+        ";
+
+        vm.interpret(Cursor::new(source)).unwrap();
+
+        let r = vm.globals.get(&"r".to_string()).unwrap();
+        assert_eq!(r.borrow().value, Primitive::Int(7));
+    }
+
+    #[test]
+    fn read_before_definition() {
+        let mut vm = Vm::default();
+        let source = r"
+            fn f() { return later; }
+
+            let r = f();
+            let later = 1;
+        ";
+
+        let err = vm.interpret(Cursor::new(source)).unwrap_err();
+        assert_eq!(err.message, "Use of undeclared variable 'later'.");
+    }
+
+    #[test]
+    fn globals_persist_between_interpret_calls() {
+        let mut vm = Vm::default();
+
+        vm.interpret(Cursor::new("let mut a = 1;")).unwrap();
+        vm.interpret(Cursor::new("a = a + 1;")).unwrap();
+
+        let a = vm.globals.get(&"a".to_string()).unwrap();
+        assert_eq!(a.borrow().value, Primitive::Int(2));
+    }
+
+    #[test]
+    fn slots_belong_to_each_vm() {
+        let mut first = Vm::default();
+        let mut second = Vm::default();
+
+        first.interpret(Cursor::new("let only_first = 1;")).unwrap();
+        second.interpret(Cursor::new("let only_second = 2;")).unwrap();
+
+        assert!(second.globals.slots.borrow().lookup("only_first").is_none());
+        assert!(first.globals.slots.borrow().lookup("only_second").is_none());
+        assert_eq!(second.globals.get("only_second").unwrap().borrow().value, Primitive::Int(2));
+    }
+}
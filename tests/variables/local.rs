@@ -15,7 +15,7 @@ mod local {
             }
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         parser.advance();
         parser.begin_scope();
         parser = parser.block();
@@ -42,7 +42,7 @@ mod local {
             }
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         parser.advance();
         parser.begin_scope();
         parser = parser.block();
@@ -51,7 +51,7 @@ mod local {
         let result = catch_unwind(AssertUnwindSafe(|| {
             let _ = vm.run();
         }));
-        let var_value = match vm.stack[0].borrow().value {
+        let var_value = match vm.stack[0].value {
             Primitive::Int(i) => i,
             _ => panic!("Invalid value on local slot.")
         };
@@ -19,7 +19,7 @@ mod variables {
             a();
         ";
 
-        let mut parser = mk_parser(Cursor::new(source));
+        let mut parser = mk_parser(Cursor::new(source), &vm.globals.slots);
         vm.call(Rc::new(parser.end_compiler()), 0);
 
         let result = catch_unwind(AssertUnwindSafe(|| {